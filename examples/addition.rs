@@ -2,11 +2,11 @@ use skewes::Natural;
 
 fn main() {
     let bound = Natural::from_string("100000000");
-    let mut n = Natural::from(1);
-    let mut acc = Natural::from(0);
+    let mut n = Natural::from(1u64);
+    let mut acc = Natural::from(0u64);
 
     while n < bound {
         acc.add_mut(&n);
-        n.add_mut(&Natural::from(1));
+        n.add_mut(&Natural::from(1u64));
     }
 }
\ No newline at end of file
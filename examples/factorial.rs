@@ -1,21 +1,21 @@
 use skewes::Natural;
 
 fn factorial(n: Natural) -> Natural {
-    if n == Natural::from(1) {
-        Natural::from(1)
+    if n == Natural::from(1u64) {
+        Natural::from(1u64)
     }
     else {
-        let mut acc = Natural::from(1);
+        let mut acc = Natural::from(1u64);
         let mut m = n.clone();
         while m > Natural::ZERO {
             acc = &acc * &m;
-            m = m.sub(&Natural::from(1)).unwrap();
+            m = m.sub(&Natural::from(1u64)).unwrap();
         }
         acc
     }
 }
 
 fn main() {
-    let n = Natural::from(100);
+    let n = Natural::from(100u64);
     println!("100!: {}", factorial(n));
 }
\ No newline at end of file
@@ -101,28 +101,28 @@ mod tests {
     fn add_with_carry_one_plus_nine_wo_carry() {
         let (result, carry) = add_with_carry(NINE, ONE, false);
         assert_eq!(result, 0);
-        assert_eq!(carry, true);
+        assert!(carry);
     }
 
     #[test]
     fn add_with_carry_one_plus_nine_w_carry() {
         let (result, carry) = add_with_carry(NINE, ONE, true);
         assert_eq!(result, 1);
-        assert_eq!(carry, true);
+        assert!(carry);
     }
 
     #[test]
     fn add_with_carry_one_plus_eight_wo_carry() {
         let (result, carry) = add_with_carry(EIGHT, ONE, false);
         assert_eq!(result, NINE);
-        assert_eq!(carry, false);
+        assert!(!carry);
     }
 
     #[test]
     fn add_with_carry_one_plus_eight_w_carry() {
         let (result, carry) = add_with_carry(EIGHT, ONE, true);
         assert_eq!(result, 0);
-        assert_eq!(carry, true);
+        assert!(carry);
     }
 
     /*
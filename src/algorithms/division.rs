@@ -1,10 +1,9 @@
-use crate::Natural;
-use crate::Sign;
+use crate::{Natural, Limb};
 
 use crate::division_result::DivisionResult;
+use super::add_mut;
 use super::multiplication::mul_by_single_digit;
-use super::subtraction::{sub_signed,sub_slice_assign};
-use super::util::shl;
+use super::subtraction::sub_slice_assign;
 
 #[inline]
 pub fn div(p: &Natural, q: &Natural) -> (Natural, Natural) {
@@ -43,6 +42,9 @@ pub fn mul_by_2_to_power_k(n: &Natural, k: u32) -> Natural {
 }
 
 pub fn div_by_2_to_power_k(n: &Natural, k: u32) -> Natural {
+    if n.digits.is_empty() {
+        return Natural::ZERO;
+    }
     let mut carry = 0;
     let low_mask  = (1<<(64-k)) - 1;
     let high_mask = u64::MAX ^ low_mask;
@@ -63,58 +65,67 @@ pub fn div_by_2_to_power_k(n: &Natural, k: u32) -> Natural {
 
 /// Returns the result (quotient, remainder) of p / q
 /// 
-/// q must be normalised
+/// q must be normalised, i.e. the most significant bit of its leading digit is set.
+/// This is Algorithm D from Knuth, TAOCP Vol. 2, 4.3.1
 fn div_normalised(p: &Natural, q: &Natural) -> (Natural, Natural) {
-    // This is a mess
-    let mut a = p.clone();
     let n = q.digits.len();
-    if n > a.digits.len() {
-        (Natural::ZERO, q.clone())
+    if p < q {
+        return (Natural::ZERO, p.clone());
     }
-    else {
-        let m = a.digits.len() - n;
-        let mut digits = DivisionResult::new(m+1);
-        let first_candidate = shl(&q, m);
-        if a >= first_candidate {
-            digits.push(1);
-            sub_slice_assign(&mut a.digits, &first_candidate.digits);
-        }
-        else {
-            digits.push(0);
+    if n == 1 {
+        let (d, r) = div_by_single_digit(&p.digits, q.digits[0]);
+        return (Natural::from(d), Natural::from(r));
+    }
+
+    let m = p.digits.len() - n;
+    let mut a = p.digits.clone();
+    a.push(0);
+    let mut digits = DivisionResult::new(m+1);
+    let q_1 = q.digits[n-1];
+    let q_2 = q.digits[n-2];
+
+    for j in (0..=m).rev() {
+        // Estimate the quotient digit from the leading digits, which is at most two too large
+        let mut q_j = short_div(a[j+n], a[j+n-1], q_1);
+        let mut r_j = (((a[j+n] as u128) << 64) | a[j+n-1] as u128) - (q_j as u128) * (q_1 as u128);
+        while r_j >> 64 == 0
+            && (q_j as u128) * (q_2 as u128) > ((r_j << 64) | a[j+n-2] as u128) {
+            q_j -= 1;
+            r_j += q_1 as u128;
         }
 
-        for j in (0..m).rev() {
-            // Need to handle cases where we've had a near-exact division here
-            if a.digits.len() == 1 {
-                let last_digit = a.digits[0] / q.digits[n-1];
-                digits.push(last_digit);
-            }
-            if a == Natural::ZERO {
-                // Can we put all the remaining zeroes in at once?
-                digits.push(0);
-                continue;
-            }
-
-            // Otherwise we have lots of work to do
-            let mut q_j = short_div(a.digits[n+j], a.digits[n+j-1], q.digits[n-1]);
-            let mut sign: Sign;
-            let (s, temp_a) = sub_signed(&a, &Natural::from(mul_by_single_digit(&q.digits, q_j, j as u64)));
-            sign = s;
-            a = temp_a;
-            while sign == Sign::Negative {
-                q_j -= 1;
-                let (s, temp_a) = sub_signed(&shl(&q, j), &a);
-                sign = s;
-                a = temp_a;
-            }
-            digits.push(q_j);
+        // Subtract q_j * q from the current window of a
+        let product = mul_by_single_digit(&q.digits, q_j, 0);
+        let borrow = sub_slice_assign(&mut a[j..j+n+1], &product);
+        if borrow {
+            // The estimate was one too large; add q back
+            q_j -= 1;
+            add_mut(&mut a[j..j+n+1], &q.digits);
         }
+        digits.push(q_j);
+    }
 
-        let mut n = Natural::from(digits);
-        normalize(&mut n);
-        normalize(&mut a);
-        (n, a)
+    let mut d = Natural::from(digits);
+    a.truncate(n);
+    let mut r = Natural::from(a);
+    normalize(&mut d);
+    normalize(&mut r);
+    (d, r)
+}
+
+/// Divides a slice by a single digit, returning the quotient and the remainder
+pub fn div_by_single_digit(p: &[Limb], q: Limb) -> (Vec<Limb>, Limb) {
+    let mut result = vec![0; p.len()];
+    let mut r: u128 = 0;
+    for (i, digit) in p.iter().enumerate().rev() {
+        let current = (r << 64) | (*digit as u128);
+        result[i] = (current / q as u128) as Limb;
+        r = current % q as u128;
+    }
+    while let Some(&0) = result.last() {
+        result.pop();
     }
+    (result, r as Limb)
 }
 
 /// Returns the result of min(floor[(p_1 * Base + p_0) / q_0], Base - 1)
@@ -155,7 +166,7 @@ mod tests {
         let n = Natural::from(vec!(1, 7));
         assert_eq!(mul_by_2_to_power_k(&n, 3), Natural::from(vec!(8, 56)));
 
-        let n = Natural::from(156);
+        let n = Natural::from(156u64);
         assert_eq!(mul_by_2_to_power_k(&n, 60), Natural::from(vec!(156 << 60, 156u64.rotate_left(60) & 0xff)))
     }
 
@@ -172,7 +183,7 @@ mod tests {
         let a = Natural::from(vec!(1, 7));
         let b = Natural::from(vec!(7));
         let c = Natural::from(vec!(0, 1));
-        assert_eq!(div(&a, &b), (c, Natural::from(1)));
+        assert_eq!(div(&a, &b), (c, Natural::from(1u64)));
     }
 
     #[test]
@@ -226,11 +237,11 @@ mod tests {
 
     #[test]
     fn test_div_by_ten() {
-        let a = Natural::from(156);
-        let b = Natural::from(10);
+        let a = Natural::from(156u64);
+        let b = Natural::from(10u64);
         let (d, r) = div(&a, &b);
-        assert_eq!(d, Natural::from(15));
-        assert_eq!(r, Natural::from(6));
+        assert_eq!(d, Natural::from(15u64));
+        assert_eq!(r, Natural::from(6u64));
     }
 
     #[test]
@@ -239,9 +250,35 @@ mod tests {
         // The regression in this case was that the test result was actually 0, rather
         // than the expected here.
         let p = Natural::from(vec!(0, 0, 4788272403190906880));
-        let q = Natural::from(11529215046068469760);
+        let q = Natural::from(11529215046068469760u64);
         assert_eq!(p.div(&q), (Natural::from(vec!(0, 7661235845105451008)), 
                                Natural::ZERO)
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_div_smaller_by_larger() {
+        let p = Natural::from(23u64);
+        let q = Natural::from(vec!(5, 7));
+        assert_eq!(div(&p, &q), (Natural::ZERO, Natural::from(23u64)));
+    }
+
+    #[test]
+    fn test_div_multi_digit_needs_add_back() {
+        // Exercises the rare add-back step of the long division
+        let p = Natural::from(vec!(0, 0, 1 << 63, 1 << 63));
+        let q = Natural::from(vec!(1, 0, 1 << 63));
+        let (d, r) = div(&p, &q);
+        assert_eq!(&(&d * &q) + &r, p);
+        assert!(r < q);
+    }
+
+    #[test]
+    fn test_div_invariant_multi_digit() {
+        let p = Natural::from(vec!(NINE, 0, NINE, 1 << 63, 12345, NINE, 7));
+        let q = Natural::from(vec!(3, NINE, 1 << 62));
+        let (d, r) = div(&p, &q);
+        assert_eq!(&(&d * &q) + &r, p);
+        assert!(r < q);
+    }
+}
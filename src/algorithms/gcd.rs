@@ -0,0 +1,260 @@
+use core::convert::TryFrom;
+use core::mem;
+use crate::{Natural, Integer, Limb};
+use super::half_gcd::{HALF_GCD_THRESHOLD, half_gcd_gcd, half_gcd_extended_gcd};
+
+/// Numbers with at most this many digits have their gcd found by the binary algorithm
+/// on native 128-bit integers
+const BINARY_LIMIT: usize = 2;
+
+/// Returns the greatest common divisor of a and b
+///
+/// Small numbers use the binary algorithm, larger ones Lehmer's algorithm and the
+/// largest the half-gcd algorithm.
+pub fn gcd(a: &Natural, b: &Natural) -> Natural {
+    let (a, b) = if a >= b { (a, b) } else { (b, a) };
+    if a.digits.len() <= BINARY_LIMIT {
+        Natural::from(binary_gcd(to_u128(a), to_u128(b)))
+    }
+    else if b.digits.len() >= HALF_GCD_THRESHOLD {
+        half_gcd_gcd(a.clone(), b.clone())
+    }
+    else {
+        lehmer_gcd(a.clone(), b.clone())
+    }
+}
+
+/// Returns (g, x, y) such that g = gcd(a, b) = ax + by
+pub fn extended_gcd(a: &Natural, b: &Natural) -> (Natural, Integer, Integer) {
+    if a < b {
+        let (g, y, x) = extended_gcd(b, a);
+        return (g, x, y);
+    }
+    if b.is_zero() {
+        let x = if a.is_zero() { 0 } else { 1 };
+        return (a.clone(), Integer::from(x), Integer::from(0));
+    }
+    if b.digits.len() >= HALF_GCD_THRESHOLD {
+        return half_gcd_extended_gcd(a, b);
+    }
+    let (g, x) = lehmer_extended_gcd(a.clone(), b.clone());
+    // g - ax is an exact multiple of b
+    let y = &(&Integer::from(g.clone()) - &(&x * &Integer::from(a.clone()))) / &Integer::from(b.clone());
+    (g, x, y)
+}
+
+#[inline]
+fn to_u128(n: &Natural) -> u128 {
+    u128::try_from(n).unwrap()
+}
+
+/// Stein's binary gcd algorithm on native integers
+fn binary_gcd(mut a: u128, mut b: u128) -> u128 {
+    if a == 0 {
+        return b;
+    }
+    if b == 0 {
+        return a;
+    }
+    let shift = (a | b).trailing_zeros();
+    a >>= a.trailing_zeros();
+    loop {
+        b >>= b.trailing_zeros();
+        if a > b {
+            mem::swap(&mut a, &mut b);
+        }
+        b -= a;
+        if b == 0 {
+            return a << shift;
+        }
+    }
+}
+
+/// Returns the 63 bits of n starting at the given bit position
+fn bits_at(n: &Natural, shift: u64) -> i128 {
+    let limb = (shift / 64) as usize;
+    let offset = shift % 64;
+    let low = n.digits.get(limb).copied().unwrap_or(0) as u128;
+    let high = n.digits.get(limb + 1).copied().unwrap_or(0) as u128;
+    ((((high << 64) | low) >> offset) as u64 & (Limb::MAX >> 1)) as i128
+}
+
+/// The matrix of cofactors found by a step of Lehmer's algorithm, such that
+/// (a, b) -> (A a + B b, C a + D b) performs several steps of Euclid's algorithm
+pub(super) struct Cofactors {
+    pub(super) a: i128,
+    pub(super) b: i128,
+    pub(super) c: i128,
+    pub(super) d: i128,
+}
+
+/// Simulates Euclid's algorithm on the leading 63 bits of a and b, returning the
+/// cofactors of the quotient sequence which is certain to agree with the full
+/// numbers (Algorithm L, Knuth TAOCP Vol. 2, 4.5.2)
+pub(super) fn lehmer_cofactors(a: &Natural, b: &Natural) -> Cofactors {
+    lehmer_quotients(a, b, |_| ())
+}
+
+/// As `lehmer_cofactors`, also passing each quotient of the sequence to record
+pub(super) fn lehmer_quotients<F: FnMut(u64)>(a: &Natural, b: &Natural, mut record: F) -> Cofactors {
+    let shift = a.bit_length().saturating_sub(63);
+    let mut x = bits_at(a, shift);
+    let mut y = bits_at(b, shift);
+    let mut m = Cofactors { a: 1, b: 0, c: 0, d: 1 };
+    // x + A and x + B bound the leading part of a from below and above, so the
+    // quotient is known when both bounds agree
+    while y + m.c > 0 && y + m.d > 0 {
+        let q = (x + m.a) / (y + m.c);
+        if q != (x + m.b) / (y + m.d) {
+            break;
+        }
+        record(q as u64);
+        m = Cofactors { a: m.c, b: m.d, c: m.a - q * m.c, d: m.b - q * m.d };
+        let t = x - q * y;
+        x = y;
+        y = t;
+    }
+    m
+}
+
+/// Returns pa + qb, which must be non-negative
+fn combine(p: i128, a: &Integer, q: i128, b: &Integer) -> Integer {
+    &(&Integer::from(p) * a) + &(&Integer::from(q) * b)
+}
+
+/// Applies the cofactors to (a, b), returning false if they describe no progress
+pub(super) fn apply_cofactors(m: &Cofactors, a: &mut Natural, b: &mut Natural) -> bool {
+    if m.b == 0 {
+        return false;
+    }
+    let (x, y) = (Integer::from(a.clone()), Integer::from(b.clone()));
+    *a = Natural::try_from(combine(m.a, &x, m.b, &y)).unwrap();
+    *b = Natural::try_from(combine(m.c, &x, m.d, &y)).unwrap();
+    true
+}
+
+/// Lehmer's gcd algorithm, where a >= b
+pub(super) fn lehmer_gcd(mut a: Natural, mut b: Natural) -> Natural {
+    while b.digits.len() > BINARY_LIMIT {
+        let m = lehmer_cofactors(&a, &b);
+        if !apply_cofactors(&m, &mut a, &mut b) {
+            // The leading digits gave no information, so do a full division step
+            let r = a.div(&b).1;
+            a = mem::replace(&mut b, r);
+        }
+    }
+    if b.is_zero() {
+        return a;
+    }
+    a = a.div(&b).1;
+    Natural::from(binary_gcd(to_u128(&a), to_u128(&b)))
+}
+
+/// Lehmer's algorithm tracking the cofactor of the original a, where a >= b > 0
+///
+/// Returns (g, x) such that g = gcd(a, b) and g = ax (mod b)
+pub(super) fn lehmer_extended_gcd(mut a: Natural, mut b: Natural) -> (Natural, Integer) {
+    // Invariants: a = s_0 a_orig (mod b_orig), b = s_1 a_orig (mod b_orig)
+    let mut s_0 = Integer::from(1);
+    let mut s_1 = Integer::from(0);
+    while !b.is_zero() {
+        let m = if b.digits.len() > BINARY_LIMIT {
+            lehmer_cofactors(&a, &b)
+        }
+        else {
+            Cofactors { a: 1, b: 0, c: 0, d: 1 }
+        };
+        if apply_cofactors(&m, &mut a, &mut b) {
+            let s = combine(m.a, &s_0, m.b, &s_1);
+            s_1 = combine(m.c, &s_0, m.d, &s_1);
+            s_0 = s;
+        }
+        else {
+            let (q, r) = a.div(&b);
+            a = mem::replace(&mut b, r);
+            let s = &s_0 - &(&Integer::from(q) * &s_1);
+            s_0 = mem::replace(&mut s_1, s);
+        }
+    }
+    (a, s_0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NINE: u64 = u64::MAX;
+
+    /// Euclid's algorithm, as a reference
+    fn euclid(a: &Natural, b: &Natural) -> Natural {
+        let (mut a, mut b) = (a.clone(), b.clone());
+        while !b.is_zero() {
+            let r = a.div(&b).1;
+            a = mem::replace(&mut b, r);
+        }
+        a
+    }
+
+    fn random_digits(state: &mut u64, n: usize) -> Natural {
+        let digits = (0..n).map(|_| {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            *state
+        }).collect::<Vec<_>>();
+        let mut n = Natural::from(digits);
+        while let Some(&0) = n.digits.last() {
+            n.digits.pop();
+        }
+        n
+    }
+
+    #[test]
+    fn test_binary_gcd() {
+        assert_eq!(binary_gcd(0, 0), 0);
+        assert_eq!(binary_gcd(0, 12), 12);
+        assert_eq!(binary_gcd(48, 18), 6);
+        assert_eq!(binary_gcd(u128::MAX, 5), 5);
+        assert_eq!(binary_gcd(1 << 100, 3 << 90), 1 << 90);
+    }
+
+    #[test]
+    fn test_gcd_with_common_factor() {
+        let g = Natural::from(vec!(NINE, 12345, 1));
+        let a = &g * &Natural::from(vec!(17, 0, 0, 5));
+        let b = &g * &Natural::from(vec!(19, 3, 7));
+        assert_eq!(gcd(&a, &b), g);
+        assert_eq!(gcd(&b, &a), g);
+        assert_eq!(gcd(&a, &Natural::ZERO), a);
+        assert_eq!(gcd(&Natural::ZERO, &Natural::ZERO), Natural::ZERO);
+    }
+
+    #[test]
+    fn test_gcd_matches_euclid() {
+        let mut state = 0x853c_49e6_748f_ea9b;
+        for &(m, n) in &[(3, 3), (5, 2), (8, 7), (20, 20), (40, 3)] {
+            for _ in 0..5 {
+                let common = random_digits(&mut state, 2);
+                let a = &random_digits(&mut state, m) * &common;
+                let b = &random_digits(&mut state, n) * &common;
+                assert_eq!(gcd(&a, &b), euclid(&a, &b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_extended_gcd_bezout() {
+        let mut state = 0xda94_2042_e4dd_58b5;
+        for &(m, n) in &[(1, 1), (2, 1), (3, 3), (6, 4), (12, 12), (30, 5)] {
+            for _ in 0..5 {
+                let common = random_digits(&mut state, 1);
+                let a = &random_digits(&mut state, m) * &common;
+                let b = &random_digits(&mut state, n) * &common;
+                let (g, x, y) = extended_gcd(&a, &b);
+                assert_eq!(g, euclid(&a, &b));
+                let lhs = &(&Integer::from(a.clone()) * &x) + &(&Integer::from(b.clone()) * &y);
+                assert_eq!(lhs, Integer::from(g));
+            }
+        }
+    }
+}
@@ -0,0 +1,267 @@
+// Subquadratic gcd by the half-gcd method of Schönhage, in the form described by
+// Möller ("On Schönhage's algorithm and subquadratic integer gcd computation", 2008)
+//
+// The half-gcd of (a, b) is a matrix N of determinant ±1 such that (α, β) = N (a, b)
+// has β about half the size of a. It is found recursively from the half-gcd of the
+// most significant halves of a and b, which agrees with that of the full numbers in
+// all but the last few steps.
+//
+// Any matrix of determinant ±1 preserves the gcd, so rather than proving in advance
+// that the matrix found from the leading digits is valid for the full numbers, the
+// result of applying it is checked and repaired with a sign change or a swap. Both
+// are rare and cheap, and the few Euclidean steps which follow restore the invariants.
+
+use core::mem;
+use crate::{Natural, Integer, Sign, Limb};
+use super::gcd::{Cofactors, lehmer_cofactors, apply_cofactors, lehmer_gcd, lehmer_extended_gcd};
+
+/// Numbers with fewer digits than this use Lehmer's algorithm
+pub const HALF_GCD_THRESHOLD: usize = 256;
+
+/// A 2x2 matrix of determinant ±1, stored by rows
+pub(super) struct Matrix([[Integer; 2]; 2]);
+
+impl Matrix {
+    pub(super) fn identity() -> Self {
+        Matrix([[Integer::from(1), Integer::from(0)], [Integer::from(0), Integer::from(1)]])
+    }
+
+    /// The matrix which maps (a, b) to (b, a - qb)
+    pub(super) fn division_step(q: Natural) -> Self {
+        let q = Integer::from_sign_and_size(Sign::Negative, q);
+        Matrix([[Integer::from(0), Integer::from(1)], [Integer::from(1), q]])
+    }
+
+    /// The matrix which maps (b, a - qb) back to (a, b)
+    pub(super) fn undo_division_step(q: Natural) -> Self {
+        Matrix([[Integer::from(q), Integer::from(1)], [Integer::from(1), Integer::from(0)]])
+    }
+
+    pub(super) fn from_cofactors(m: &Cofactors) -> Self {
+        Matrix([[Integer::from(m.a), Integer::from(m.b)], [Integer::from(m.c), Integer::from(m.d)]])
+    }
+
+    /// Returns the product of the rows of the matrix with (a, b)
+    pub(super) fn apply(&self, a: &Integer, b: &Integer) -> (Integer, Integer) {
+        let [r, s] = &self.0;
+        (&(&r[0] * a) + &(&r[1] * b), &(&s[0] * a) + &(&s[1] * b))
+    }
+
+    /// Returns the matrix which applies self after first
+    pub(super) fn after(&self, first: &Matrix) -> Matrix {
+        let [r, s] = &first.0;
+        let (r0, s0) = self.apply(&r[0], &s[0]);
+        let (r1, s1) = self.apply(&r[1], &s[1]);
+        Matrix([[r0, r1], [s0, s1]])
+    }
+}
+
+/// Builds a Natural from limbs which may have leading zeros
+pub(super) fn from_limbs(digits: &[Limb]) -> Natural {
+    let len = digits.iter().rposition(|&d| d != 0).map_or(0, |i| i + 1);
+    Natural::from(digits[..len].to_vec())
+}
+
+/// Returns the digits of n from position k upwards, i.e. the floor of n / 2^(64k)
+pub(super) fn high_limbs(n: &Natural, k: usize) -> Natural {
+    from_limbs(n.digits.get(k..).unwrap_or(&[]))
+}
+
+/// Returns n * 2^(64k)
+pub(super) fn shift_limbs(n: Integer, k: usize) -> Integer {
+    if n.size.is_zero() {
+        return n;
+    }
+    let mut digits = vec!(0; k);
+    digits.extend_from_slice(&n.size.digits);
+    Integer::from_sign_and_size(n.sign, Natural::from(digits))
+}
+
+/// Makes (a, b) = N (a_orig, b_orig) satisfy a >= b >= 0 by changing signs and
+/// swapping rows of N as needed, returning the repaired pair as Naturals
+fn repair(n: &mut Matrix, a: Integer, b: Integer) -> (Natural, Natural) {
+    let mut pair = [a, b];
+    for (row, value) in n.0.iter_mut().zip(pair.iter_mut()) {
+        if value.sign == Sign::Negative {
+            value.sign = Sign::Positive;
+            for entry in row.iter_mut() {
+                let size = mem::replace(&mut entry.size, Natural::ZERO);
+                *entry = Integer::from_sign_and_size(entry.sign.negate(), size);
+            }
+        }
+    }
+    let [a, b] = pair;
+    let (mut a, mut b) = (a.size, b.size);
+    if a < b {
+        n.0.swap(0, 1);
+        mem::swap(&mut a, &mut b);
+    }
+    (a, b)
+}
+
+/// Takes Lehmer and Euclidean steps on a >= b until b has at most s digits,
+/// accumulating them into n
+fn reduce(n: &mut Matrix, a: &mut Natural, b: &mut Natural, s: usize) {
+    while b.digits.len() > s + 1 {
+        let m = lehmer_cofactors(a, b);
+        if apply_cofactors(&m, a, b) {
+            *n = Matrix::from_cofactors(&m).after(n);
+        }
+        else {
+            division_step(n, a, b);
+        }
+    }
+    // Single steps avoid overshooting the target by a whole digit
+    while b.digits.len() > s {
+        division_step(n, a, b);
+    }
+}
+
+/// Replaces (a, b) with (b, a mod b), accumulating the step into n
+fn division_step(n: &mut Matrix, a: &mut Natural, b: &mut Natural) {
+    let (q, r) = a.div(b);
+    *a = mem::replace(b, r);
+    *n = Matrix::division_step(q).after(n);
+}
+
+/// Finds a matrix N of determinant ±1 such that (α, β) = N (a, b) has α >= β
+/// and β at most s digits long, where a >= b
+///
+/// When s is about half the length of a, the entries of N are too.
+fn half_gcd(a: &Natural, b: &Natural, s: usize) -> (Matrix, Natural, Natural) {
+    let mut n = Matrix::identity();
+    let (mut a, mut b) = (a.clone(), b.clone());
+    if b.digits.len() <= s {
+        return (n, a, b);
+    }
+    if a.digits.len() >= HALF_GCD_THRESHOLD && s * 2 >= a.digits.len() {
+        // Reducing the top n - s digits to about half their length takes the
+        // full numbers down to about 3n/4 digits
+        let (n1, a1, b1) = split_reduce(&a, &b, s);
+        n = n1;
+        a = a1;
+        b = b1;
+        // Then the top 2(n' - s) digits of what remains take them down to about s
+        let len = a.digits.len();
+        if b.digits.len() > s && 2 * s > len {
+            let (n2, a2, b2) = split_reduce(&a, &b, 2 * s - len);
+            n = n2.after(&n);
+            a = a2;
+            b = b2;
+        }
+    }
+    reduce(&mut n, &mut a, &mut b, s);
+    (n, a, b)
+}
+
+/// Finds the half-gcd matrix of the digits of a and b above position p and applies
+/// it to the full numbers
+fn split_reduce(a: &Natural, b: &Natural, p: usize) -> (Matrix, Natural, Natural) {
+    let (a_high, b_high) = (high_limbs(a, p), high_limbs(b, p));
+    let target = a_high.digits.len() / 2 + 1;
+    let (mut n, alpha, beta) = half_gcd(&a_high, &b_high, target);
+    // N (a, b) = 2^(64p) N (a_high, b_high) + N (a_low, b_low)
+    let a_low = Integer::from(from_limbs(&a.digits[..p.min(a.digits.len())]));
+    let b_low = Integer::from(from_limbs(&b.digits[..p.min(b.digits.len())]));
+    let (x, y) = n.apply(&a_low, &b_low);
+    let x = &shift_limbs(Integer::from(alpha), p) + &x;
+    let y = &shift_limbs(Integer::from(beta), p) + &y;
+    let (a, b) = repair(&mut n, x, y);
+    (n, a, b)
+}
+
+/// Returns the gcd of a >= b by repeated half-gcd reductions
+pub fn half_gcd_gcd(mut a: Natural, mut b: Natural) -> Natural {
+    while b.digits.len() >= HALF_GCD_THRESHOLD {
+        let (_, alpha, beta) = half_gcd(&a, &b, a.digits.len() / 2 + 1);
+        a = alpha;
+        b = beta;
+        // A division step guarantees progress even if the half-gcd made none
+        if !b.is_zero() {
+            let r = a.div(&b).1;
+            a = mem::replace(&mut b, r);
+        }
+    }
+    lehmer_gcd(a, b)
+}
+
+/// Returns (g, x, y) such that g = gcd(a, b) = ax + by, where a >= b
+pub fn half_gcd_extended_gcd(a: &Natural, b: &Natural) -> (Natural, Integer, Integer) {
+    let mut n = Matrix::identity();
+    let (mut alpha, mut beta) = (a.clone(), b.clone());
+    while beta.digits.len() >= HALF_GCD_THRESHOLD {
+        let (step, a1, b1) = half_gcd(&alpha, &beta, alpha.digits.len() / 2 + 1);
+        n = step.after(&n);
+        alpha = a1;
+        beta = b1;
+        if !beta.is_zero() {
+            division_step(&mut n, &mut alpha, &mut beta);
+        }
+    }
+    // With g = x α + y β and (α, β) = N (a, b), the coefficients of (a, b) are (x, y) N
+    let (g, x, y) = if beta.is_zero() {
+        (alpha, Integer::from(1), Integer::from(0))
+    }
+    else {
+        let (g, x) = lehmer_extended_gcd(alpha.clone(), beta.clone());
+        let y = &(&Integer::from(g.clone()) - &(&x * &Integer::from(alpha))) / &Integer::from(beta);
+        (g, x, y)
+    };
+    let [r, s] = &n.0;
+    let coefficient_a = &(&x * &r[0]) + &(&y * &s[0]);
+    let coefficient_b = &(&x * &r[1]) + &(&y * &s[1]);
+    (g, coefficient_a, coefficient_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::gcd::lehmer_gcd;
+
+    fn random_natural(state: &mut u64, n: usize) -> Natural {
+        let digits = (0..n).map(|_| {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            *state
+        }).collect::<Vec<_>>();
+        from_limbs(&digits)
+    }
+
+    #[test]
+    fn test_half_gcd_reduces() {
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        let a = random_natural(&mut state, 1000);
+        let b = random_natural(&mut state, 990);
+        let (n, alpha, beta) = half_gcd(&a, &b, 501);
+        assert!(beta.digits.len() <= 501 && alpha >= beta);
+        let (x, y) = n.apply(&Integer::from(a), &Integer::from(b));
+        assert_eq!((x, y), (Integer::from(alpha), Integer::from(beta)));
+    }
+
+    #[test]
+    fn test_half_gcd_gcd_matches_lehmer() {
+        let mut state = 0x1405_7b7e_f767_814f;
+        for &(m, n) in &[(300, 300), (700, 400), (1500, 1499)] {
+            let common = random_natural(&mut state, 40);
+            let a = &random_natural(&mut state, m) * &common;
+            let b = &random_natural(&mut state, n) * &common;
+            let (a, b) = if a >= b { (a, b) } else { (b, a) };
+            assert_eq!(half_gcd_gcd(a.clone(), b.clone()), lehmer_gcd(a, b));
+        }
+    }
+
+    #[test]
+    fn test_half_gcd_extended_gcd_bezout() {
+        let mut state = 0x9e37_79b9_7f4a_7c15;
+        for &(m, n) in &[(300, 280), (900, 900)] {
+            let common = random_natural(&mut state, 3);
+            let a = &random_natural(&mut state, m) * &common;
+            let b = &random_natural(&mut state, n) * &common;
+            let (a, b) = if a >= b { (a, b) } else { (b, a) };
+            let (g, x, y) = half_gcd_extended_gcd(&a, &b);
+            assert_eq!(g, lehmer_gcd(a.clone(), b.clone()));
+            assert_eq!(&(&Integer::from(a) * &x) + &(&Integer::from(b) * &y), Integer::from(g));
+        }
+    }
+}
@@ -0,0 +1,339 @@
+// Jacobi symbols of large numbers
+//
+// The binary algorithm computes (a/n), for odd n, by removing factors of two from a
+// with (2/n) = (-1)^((n^2 - 1) / 8), and then, with both odd, using reciprocity,
+// (a/n) = (n/a) (-1)^((a - 1)(n - 1) / 4), to arrange a < n before subtracting.
+//
+// The subquadratic variant follows the steps (x, y) -> (y, x - qy) of a half-gcd. The
+// symbol is carried as ±(y/x) or ±(x/y), whichever of x and y is odd being the
+// denominator, and each step changes the sign in a way which depends only on the
+// residues of x, y and q modulo 8. When the numerator is the one reduced this is
+// periodicity; otherwise it is reciprocity if both are odd, and if the numerator y is
+// even the fact that (y/m) depends only on m modulo y when y = 0 (mod 4), and only on
+// m modulo 4y when y = 2 (mod 4). The half-gcd of the leading digits is recorded as its
+// list of quotients, and any trailing steps which turn out to be wrong for the full
+// numbers are undone. Any steps which leave both numbers non-negative are valid, since
+// each earlier pair is a non-negative combination of the last.
+
+use core::mem;
+use crate::{Natural, Integer, Sign};
+use super::gcd::{Cofactors, apply_cofactors, lehmer_quotients};
+use super::half_gcd::{HALF_GCD_THRESHOLD, Matrix, from_limbs, high_limbs, shift_limbs};
+
+/// Moduli with at least this many digits use the half-gcd variant
+pub const JACOBI_THRESHOLD: usize = 64;
+
+/// Returns the Jacobi symbol (a/n) for odd n
+pub fn jacobi(a: &Natural, n: &Natural) -> i32 {
+    debug_assert!(n.digits.first().is_some_and(|&d| d & 1 == 1));
+    let a = if a >= n { a.div(n).1 } else { a.clone() };
+    if n.digits.len() >= JACOBI_THRESHOLD {
+        half_gcd_jacobi(a, n.clone())
+    }
+    else {
+        binary_jacobi(a, n.clone())
+    }
+}
+
+/// Returns the residue of n modulo 8
+fn low_bits(n: &Natural) -> u64 {
+    n.digits.first().map_or(0, |&d| d & 7)
+}
+
+/// Whether (2/m) = -1 for odd m, from its residue modulo 8
+fn two_is_non_residue(m: u64) -> bool {
+    m == 3 || m == 5
+}
+
+/// Whether reciprocity changes the sign for odd numbers with these residues
+fn reciprocity_flips(a: u64, b: u64) -> bool {
+    a % 4 == 3 && b % 4 == 3
+}
+
+/// The binary algorithm, for a < n with n odd
+fn binary_jacobi(mut a: Natural, mut n: Natural) -> i32 {
+    let mut result = 1;
+    let one = Natural::from(1u64);
+    while !a.is_zero() {
+        let twos = a.trailing_zeros().unwrap();
+        if twos > 0 {
+            a = Natural::from(super::shr_slice(&a.digits, twos as usize));
+            if twos & 1 == 1 && two_is_non_residue(low_bits(&n)) {
+                result = -result;
+            }
+        }
+        if a < n {
+            if reciprocity_flips(low_bits(&a), low_bits(&n)) {
+                result = -result;
+            }
+            mem::swap(&mut a, &mut n);
+        }
+        // Subtracting is not enough when the sizes are far apart
+        a = if a.digits.len() > n.digits.len() + 1 { a.div(&n).1 } else { &a - &n };
+    }
+    if n == one { result } else { 0 }
+}
+
+/// The symbol ±(y/x) or ±(x/y) for a pair (x, y), tracked by residues modulo 8
+struct Symbol {
+    x: u64,
+    y: u64,
+    /// Whether x is the denominator, rather than y
+    x_denominator: bool,
+    negative: bool,
+}
+
+impl Symbol {
+    /// The symbol (y/x) for odd x
+    fn new(x: &Natural, y: &Natural) -> Self {
+        Symbol { x: low_bits(x), y: low_bits(y), x_denominator: true, negative: false }
+    }
+
+    /// Follows the step (x, y) -> (y, x - qy)
+    fn step(&mut self, q: u64) {
+        let (x, y) = (self.x, self.y);
+        let r = x.wrapping_sub(q.wrapping_mul(y)) & 7;
+        if self.x_denominator {
+            if y & 1 == 1 {
+                // (y/x) = ±(x/y) = ±(r/y), with y now first
+                self.negative ^= reciprocity_flips(x, y);
+            }
+            else {
+                // (y/x) against (y/r), with r odd and now the denominator
+                if y % 4 == 2 {
+                    let m_flips = y == 6 && (x % 4 == 3) != (r % 4 == 3);
+                    self.negative ^= two_is_non_residue(x) != two_is_non_residue(r);
+                    self.negative ^= m_flips;
+                }
+                self.x_denominator = false;
+                self.x = y;
+                self.y = r;
+                return;
+            }
+        }
+        // (x/y) = (r/y), with y now first
+        self.x_denominator = true;
+        self.x = y;
+        self.y = r;
+    }
+
+    fn sign(&self) -> i32 {
+        if self.negative { -1 } else { 1 }
+    }
+}
+
+/// Steps of Euclid's algorithm, as their product and their quotients in order
+struct Steps {
+    matrix: Matrix,
+    quotients: Vec<Natural>,
+}
+
+impl Steps {
+    fn new() -> Self {
+        Steps { matrix: Matrix::identity(), quotients: Vec::new() }
+    }
+
+    /// Records the step (a, b) -> (b, a - qb)
+    fn push(&mut self, q: Natural) {
+        self.matrix = Matrix::division_step(q.clone()).after(&self.matrix);
+        self.quotients.push(q);
+    }
+
+    /// Records the steps of a Lehmer step, with cofactors m and these quotients
+    fn push_lehmer(&mut self, m: &Cofactors, quotients: Vec<Natural>) {
+        self.matrix = Matrix::from_cofactors(m).after(&self.matrix);
+        self.quotients.extend(quotients);
+    }
+
+    /// Records the steps of later after these
+    fn extend(&mut self, later: Steps) {
+        self.matrix = later.matrix.after(&self.matrix);
+        self.quotients.extend(later.quotients);
+    }
+
+    /// Removes the last step, returning its quotient
+    fn pop(&mut self) -> Natural {
+        let q = self.quotients.pop().unwrap();
+        self.matrix = Matrix::undo_division_step(q.clone()).after(&self.matrix);
+        q
+    }
+}
+
+/// Takes Lehmer and Euclidean steps on a >= b until b has at most s digits,
+/// recording them in steps
+fn reduce(steps: &mut Steps, a: &mut Natural, b: &mut Natural, s: usize) {
+    while b.digits.len() > s + 1 {
+        let mut quotients = Vec::new();
+        let m = lehmer_quotients(a, b, |q| quotients.push(Natural::from(q)));
+        if apply_cofactors(&m, a, b) {
+            steps.push_lehmer(&m, quotients);
+        }
+        else {
+            division_step(steps, a, b);
+        }
+    }
+    while b.digits.len() > s {
+        division_step(steps, a, b);
+    }
+}
+
+/// Replaces (a, b) with (b, a mod b), recording the step
+fn division_step(steps: &mut Steps, a: &mut Natural, b: &mut Natural) {
+    let (q, r) = a.div(b);
+    *a = mem::replace(b, r);
+    steps.push(q);
+}
+
+/// Finds steps taking (a, b), where a >= b, to (α, β) with α >= β and β at most s
+/// digits long
+fn half_gcd(a: &Natural, b: &Natural, s: usize) -> (Steps, Natural, Natural) {
+    let mut steps = Steps::new();
+    let (mut a, mut b) = (a.clone(), b.clone());
+    if b.digits.len() <= s {
+        return (steps, a, b);
+    }
+    if a.digits.len() >= HALF_GCD_THRESHOLD && s * 2 >= a.digits.len() {
+        let (first, a1, b1) = split_reduce(&a, &b, s);
+        steps = first;
+        a = a1;
+        b = b1;
+        let len = a.digits.len();
+        if b.digits.len() > s && 2 * s > len {
+            let (second, a2, b2) = split_reduce(&a, &b, 2 * s - len);
+            steps.extend(second);
+            a = a2;
+            b = b2;
+        }
+    }
+    reduce(&mut steps, &mut a, &mut b, s);
+    (steps, a, b)
+}
+
+/// Finds the half-gcd steps of the digits of a and b above position p and applies
+/// them to the full numbers, undoing any at the end which take them below zero
+fn split_reduce(a: &Natural, b: &Natural, p: usize) -> (Steps, Natural, Natural) {
+    let (a_high, b_high) = (high_limbs(a, p), high_limbs(b, p));
+    let target = a_high.digits.len() / 2 + 1;
+    let (mut steps, alpha, beta) = half_gcd(&a_high, &b_high, target);
+    let a_low = Integer::from(from_limbs(&a.digits[..p.min(a.digits.len())]));
+    let b_low = Integer::from(from_limbs(&b.digits[..p.min(b.digits.len())]));
+    let (x, y) = steps.matrix.apply(&a_low, &b_low);
+    let mut x = &shift_limbs(Integer::from(alpha), p) + &x;
+    let mut y = &shift_limbs(Integer::from(beta), p) + &y;
+    while x.sign == Sign::Negative || y.sign == Sign::Negative {
+        let q = Integer::from(steps.pop());
+        let previous = &(&q * &x) + &y;
+        y = mem::replace(&mut x, previous);
+    }
+    let (mut a, mut b) = (x.size, y.size);
+    if a < b {
+        steps.push(Natural::ZERO);
+        mem::swap(&mut a, &mut b);
+    }
+    (steps, a, b)
+}
+
+/// The half-gcd variant, for a < n with n odd
+fn half_gcd_jacobi(a: Natural, n: Natural) -> i32 {
+    let (mut x, mut y) = (n, a);
+    let mut symbol = Symbol::new(&x, &y);
+    while y.digits.len() >= JACOBI_THRESHOLD {
+        let (steps, alpha, beta) = half_gcd(&x, &y, x.digits.len() / 2 + 1);
+        steps.quotients.iter().for_each(|q| symbol.step(low_bits(q)));
+        x = alpha;
+        y = beta;
+        if !y.is_zero() {
+            let mut steps = Steps::new();
+            division_step(&mut steps, &mut x, &mut y);
+            symbol.step(low_bits(&steps.quotients[0]));
+        }
+    }
+    debug_assert_eq!((symbol.x, symbol.y), (low_bits(&x), low_bits(&y)));
+    let (numerator, denominator) = if symbol.x_denominator { (y, x) } else { (x, y) };
+    let numerator = if numerator >= denominator { numerator.div(&denominator).1 } else { numerator };
+    symbol.sign() * binary_jacobi(numerator, denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_natural(state: &mut u64, n: usize) -> Natural {
+        let digits = (0..n).map(|_| {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            *state
+        }).collect::<Vec<_>>();
+        from_limbs(&digits)
+    }
+
+    fn odd(n: Natural) -> Natural {
+        if n.digits[0] & 1 == 1 { n } else { &n + &Natural::from(1u64) }
+    }
+
+    #[test]
+    fn test_binary_jacobi() {
+        // (a/p) is a^((p-1)/2) modulo a prime p
+        let p = Natural::from(u128::MAX >> 1);
+        let exponent = Natural::from(u128::MAX >> 2);
+        let mut state = 0x3c6e_f372_fe94_f82b;
+        for _ in 0..20 {
+            let a = random_natural(&mut state, 2).div(&p).1;
+            let euler = a.pow_mod(&exponent, &p);
+            let expected = if euler == Natural::from(1u64) { 1 } else if euler.is_zero() { 0 } else { -1 };
+            assert_eq!(binary_jacobi(a, p.clone()), expected);
+        }
+        assert_eq!(binary_jacobi(Natural::from(21u64), Natural::from(35u64)), 0);
+        assert_eq!(binary_jacobi(Natural::ZERO, Natural::from(1u64)), 1);
+    }
+
+    #[test]
+    fn test_symbol_steps() {
+        // Every step of Euclid's algorithm on small numbers, against the binary algorithm
+        for x in (1u64..200).step_by(2) {
+            for y in 0..x {
+                let (mut a, mut b) = (Natural::from(x), Natural::from(y));
+                let mut symbol = Symbol::new(&a, &b);
+                while !b.is_zero() {
+                    let mut steps = Steps::new();
+                    division_step(&mut steps, &mut a, &mut b);
+                    symbol.step(low_bits(&steps.quotients[0]));
+                }
+                let expected = binary_jacobi(Natural::from(y), Natural::from(x));
+                assert_eq!(if a == Natural::from(1u64) { symbol.sign() } else { 0 }, expected, "({}/{})", y, x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_steps_pop() {
+        let (a, b) = (Integer::from(1_000_003i64), Integer::from(77_777i64));
+        let mut steps = Steps::new();
+        steps.push(Natural::from(12u64));
+        steps.push(Natural::from(1u64));
+        let (x, y) = steps.matrix.apply(&a, &b);
+        assert_eq!((x, y), (Integer::from(66_679i64), Integer::from(11_098i64)));
+        steps.push(Natural::from(1u64));
+        assert_eq!(steps.pop(), Natural::from(1u64));
+        assert_eq!(steps.matrix.apply(&a, &b), (Integer::from(66_679i64), Integer::from(11_098i64)));
+        assert_eq!(steps.quotients.len(), 2);
+    }
+
+    #[test]
+    fn test_half_gcd_jacobi_matches_binary() {
+        let mut state = 0x6a09_e667_f3bc_c908;
+        for &(m, n) in &[(64, 64), (100, 70), (300, 300), (700, 650)] {
+            for _ in 0..4 {
+                let modulus = odd(random_natural(&mut state, m));
+                let a = random_natural(&mut state, n).div(&modulus).1;
+                assert_eq!(half_gcd_jacobi(a.clone(), modulus.clone()), binary_jacobi(a, modulus));
+            }
+        }
+        // A common factor makes the symbol zero
+        let common = odd(random_natural(&mut state, 10));
+        let modulus = &odd(random_natural(&mut state, 400)) * &common;
+        let a = &random_natural(&mut state, 390) * &common;
+        assert_eq!(half_gcd_jacobi(a, modulus), 0);
+    }
+}
@@ -1,11 +1,21 @@
 mod addition;
 mod comparison;
 mod division;
+mod gcd;
+mod half_gcd;
+mod jacobi;
+mod montgomery;
 mod multiplication;
+mod power;
+mod shift;
 mod subtraction;
-mod util;
 pub use addition::{add, add_mut};
 pub use comparison::cmp_slice;
-pub use division::div;
-pub use multiplication::{mul, mul_by_single_digit};
+pub use division::{div, div_by_single_digit};
+pub use gcd::{gcd, extended_gcd};
+pub use jacobi::jacobi;
+pub use montgomery::{neg_limb_inverse, redc};
+pub use multiplication::{mul, mul_into, mul_add_single_digit_mut};
+pub use power::{pow, pow_mod, window_pow};
+pub use shift::{shl_slice, shr_slice};
 pub use subtraction::sub_signed;
@@ -0,0 +1,81 @@
+use core::cmp::Ordering;
+use crate::Limb;
+use super::comparison::cmp_slice;
+use super::subtraction::sub_slice_assign;
+
+/// Returns -m^-1 mod 2^64 for an odd limb m
+pub fn neg_limb_inverse(m: Limb) -> Limb {
+    debug_assert!(m & 1 == 1);
+    // Each Newton step x -> x(2 - mx) doubles the number of correct low bits, and
+    // x = m starts correct to three bits since m^2 = 1 (mod 8) for odd m
+    let mut x = m;
+    for _ in 0..5 {
+        x = x.wrapping_mul(2u64.wrapping_sub(m.wrapping_mul(x)));
+    }
+    x.wrapping_neg()
+}
+
+/// Montgomery reduction of t < mR, returning t / R mod m where R = 2^(64n) for an
+/// n-digit odd modulus m, and m_inv = -m^-1 mod 2^64
+///
+/// One digit at a time, a multiple of m is added to t which clears its lowest
+/// digit, so after n steps t is divisible by R. The buffer t is consumed as scratch space.
+pub fn redc(t: &mut Vec<Limb>, m: &[Limb], m_inv: Limb) -> Vec<Limb> {
+    let n = m.len();
+    t.resize(2 * n + 1, 0);
+    for i in 0..n {
+        let u = t[i].wrapping_mul(m_inv);
+        let mut carry = 0u128;
+        for (j, &digit) in m.iter().enumerate() {
+            let sum = t[i + j] as u128 + u as u128 * digit as u128 + carry;
+            t[i + j] = sum as Limb;
+            carry = sum >> 64;
+        }
+        let mut k = i + n;
+        while carry != 0 {
+            let sum = t[k] as u128 + carry;
+            t[k] = sum as Limb;
+            carry = sum >> 64;
+            k += 1;
+        }
+    }
+    // t / R < 2m, so at most one subtraction brings it into range
+    let mut result = t[n..].to_vec();
+    while let Some(&0) = result.last() {
+        result.pop();
+    }
+    if cmp_slice(&result, m) != Ordering::Less {
+        sub_slice_assign(&mut result, m);
+        while let Some(&0) = result.last() {
+            result.pop();
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Natural;
+
+    #[test]
+    fn test_neg_limb_inverse() {
+        for &m in &[1u64, 3, 0x1234_5678_9abc_def1, u64::MAX] {
+            assert_eq!(m.wrapping_mul(neg_limb_inverse(m)), u64::MAX);
+        }
+    }
+
+    #[test]
+    fn test_redc() {
+        // With m = 2^64 + 3, redc(tR) = t and redc(t) R = t (mod m)
+        let m = [3, 1];
+        let m_inv = neg_limb_inverse(3);
+        let mut t = vec!(0, 0, 5);
+        assert_eq!(redc(&mut t, &m, m_inv), vec!(5));
+        let mut t = vec!(2, 1, 1, 0);
+        let reduced = Natural::from(redc(&mut t, &m, m_inv));
+        let r = Natural::from(vec!(0, 0, 1));
+        let m = Natural::from(vec!(3, 1));
+        assert_eq!(&(&reduced * &r) % &m, &Natural::from(vec!(2, 1, 1)) % &m);
+    }
+}
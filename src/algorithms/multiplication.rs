@@ -2,46 +2,109 @@ use core::convert::TryFrom; // For downcasting u128 to u64
 use core::cmp::min;
 use super::{add, add_mut};
 use super::subtraction::sub_slice;
-use super::util::shl_mut_vec;
+
+/// Operands with fewer digits than this are multiplied by the schoolbook method
+const KARATSUBA_THRESHOLD: usize = 32;
 
 pub fn mul(a: &[u64], b: &[u64]) -> Vec<u64> {
-    let mut accumulator = Vec::new();
-    for (significance, digit) in a.iter().enumerate() {
-        let result = mul_by_single_digit(b, *digit, significance as u64);
-        accumulator = add(&result, &accumulator);
-    }
-    accumulator
+    let mut result = Vec::new();
+    mul_into(a, b, &mut result);
+    result
 }
 
-// Future feature; want to make the multiplication non-recursive
-#[allow(dead_code)]
-fn karatsuba(a: &[u64], b: &[u64]) -> Vec<u64> {
-    // Make recursive
-    let x = a; let y = b;
-    let n = min(x.len(), y.len());
-    let result : Vec<u64>;
-    if n > 16 {
-        // Split x = x_1 * B^[n/2] + x_0;
-        //       y = y_1 * B^[n/2] + y_0;
-        // where B is the base (2^64)
-        let (x_0, x_1) = x.split_at(n / 2); // Little-endian
-        let (y_0, y_1) = y.split_at(n / 2); // Little-endian
-
-        let mut z_2 = karatsuba(x_1, y_1);
-        let z_0 = karatsuba(x_0, y_0);
-        let z = karatsuba(&add(x_0, x_1), &add(y_0, y_1));
-        let mut z_1 = sub_slice(&sub_slice(&z, &z_2), &z_0);
-        shl_mut_vec(&mut z_2, n);
-        shl_mut_vec(&mut z_1, n/2);
-        add_mut(&mut z_2, &z_1);
-        add_mut(&mut z_2, &z_0);
-        result = z_2;
+/// Multiply two slices, storing the product in result
+/// 
+/// The existing contents of result are discarded, but its allocation is reused,
+/// so repeated multiplications into a buffer with enough capacity rarely allocate.
+/// result must not alias a or b.
+pub fn mul_into(a: &[u64], b: &[u64], result: &mut Vec<u64>) {
+    result.clear();
+    if a.is_empty() || b.is_empty() {
+        return;
+    }
+    result.resize(a.len() + b.len(), 0);
+    if min(a.len(), b.len()) >= KARATSUBA_THRESHOLD {
+        karatsuba(a, b, result);
     }
     else {
-        result = mul(&x, &y);
+        schoolbook(a, b, result);
+    }
+    while let Some(&0) = result.last() {
+        result.pop();
     }
+}
 
-    result
+/// Multiply two slices by the schoolbook method, adding the product into result
+/// 
+/// result must have room for a.len() + b.len() digits
+fn schoolbook(a: &[u64], b: &[u64], result: &mut [u64]) {
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry: u128 = 0;
+        for (j, &y) in b.iter().enumerate() {
+            let t = (x as u128) * (y as u128) + (result[i+j] as u128) + carry;
+            result[i+j] = t as u64;
+            carry = t >> 64;
+        }
+        // Propagate the final carry through the rest of the result
+        let mut k = i + b.len();
+        while carry != 0 {
+            let t = (result[k] as u128) + carry;
+            result[k] = t as u64;
+            carry = t >> 64;
+            k += 1;
+        }
+    }
+}
+
+/// Multiply two slices by Karatsuba's method, adding the product into result
+/// 
+/// Splitting x = x_1 * B^m + x_0 and y = y_1 * B^m + y_0, where B is the base (2^64),
+/// the product is z_2 * B^2m + z_1 * B^m + z_0 where z_2 = x_1 y_1, z_0 = x_0 y_0 and
+/// z_1 = (x_0 + x_1)(y_0 + y_1) - z_2 - z_0, needing three half-size products rather than four.
+/// 
+/// result must have room for a.len() + b.len() digits
+fn karatsuba(a: &[u64], b: &[u64], result: &mut [u64]) {
+    let (x, y) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    if y.len() < KARATSUBA_THRESHOLD {
+        schoolbook(x, y, result);
+        return;
+    }
+    if 2 * y.len() <= x.len() {
+        // Very unbalanced operands are multiplied a y-sized chunk of x at a time
+        for (i, chunk) in x.chunks(y.len()).enumerate() {
+            let mut product = vec![0; chunk.len() + y.len()];
+            karatsuba(chunk, y, &mut product);
+            add_mut(&mut result[i * y.len()..], &product);
+        }
+        return;
+    }
+
+    let m = x.len() / 2;
+    let (x_0, x_1) = x.split_at(m); // Little-endian
+    let (y_0, y_1) = y.split_at(m); // Little-endian
+
+    let mut z_0 = vec![0; 2 * m];
+    karatsuba(x_0, y_0, &mut z_0);
+    let mut z_2 = vec![0; x_1.len() + y_1.len()];
+    karatsuba(x_1, y_1, &mut z_2);
+    let x_sum = add(x_0, x_1);
+    let y_sum = add(y_0, y_1);
+    let mut z_1 = vec![0; x_sum.len() + y_sum.len()];
+    karatsuba(&x_sum, &y_sum, &mut z_1);
+    trim(&mut z_0);
+    trim(&mut z_1);
+    trim(&mut z_2);
+    let z_1 = sub_slice(&sub_slice(&z_1, &z_2), &z_0);
+
+    add_mut(result, &z_0);
+    add_mut(&mut result[m..], &z_1);
+    add_mut(&mut result[2 * m..], &z_2);
+}
+
+fn trim(v: &mut Vec<u64>) {
+    while let Some(&0) = v.last() {
+        v.pop();
+    }
 }
 
 // Multiply a slice by a single u64 digit, allowing for significance number of zeroes at the start
@@ -64,6 +127,21 @@ pub fn mul_by_single_digit(digits: &[u64], digit: u64, significance: u64) -> Vec
     result
 }
 
+/// Multiply a vector by a single digit and add a single digit, storing the result in place
+/// 
+/// This is the inner step of converting a string of digits to a number
+pub fn mul_add_single_digit_mut(digits: &mut Vec<u64>, digit: u64, addend: u64) {
+    let mut carry = addend;
+    for d in digits.iter_mut() {
+        let result = (*d as u128) * (digit as u128) + (carry as u128);
+        *d = result as u64;
+        carry = (result >> 64) as u64;
+    }
+    if carry > 0 {
+        digits.push(carry);
+    }
+}
+
 fn mul_with_carry(digita: u64, digitb: u64) -> (u64, u64) {
     let result = (digita as u128) * (digitb as u128);
     (u64::try_from(result & (u64::MAX as u128)).unwrap(),
@@ -108,6 +186,19 @@ mod tests {
         assert_eq!(mul_by_single_digit(a, b, 2), c);
     }
 
+    #[test]
+    fn test_mul_add_single_digit() {
+        let mut a = vec!(NINE, NINE);
+        mul_add_single_digit_mut(&mut a, 2, 3);
+        assert_eq!(a, vec!(1, 0, 2));
+
+        let mut b = vec!();
+        mul_add_single_digit_mut(&mut b, 10, 0);
+        assert!(b.is_empty());
+        mul_add_single_digit_mut(&mut b, 10, 7);
+        assert_eq!(b, vec!(7));
+    }
+
     #[test]
     fn test_two_digit_by_two_digit_multiply() {
         let a = &[NINE, ONE];
@@ -117,6 +208,18 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_mul_into_reuses_buffer() {
+        let mut result = Vec::with_capacity(8);
+        mul_into(&[NINE, NINE], &[NINE], &mut result);
+        assert_eq!(result, vec!(1, NINE, EIGHT));
+        mul_into(&[2], &[3], &mut result);
+        assert_eq!(result, vec!(6));
+        assert_eq!(result.capacity(), 8);
+        mul_into(&[], &[3], &mut result);
+        assert!(result.is_empty());
+    }
+
     #[test]
     fn test_single_mul_temp() {
         const SEVEN: u64 = u64::MAX - 2;
@@ -138,16 +241,52 @@ mod tests {
      Test Karatsuba Multiplication
      ----------------------------------
      */
+    fn karatsuba_product(a: &[u64], b: &[u64]) -> Vec<u64> {
+        let mut result = vec![0; a.len() + b.len()];
+        karatsuba(a, b, &mut result);
+        trim(&mut result);
+        result
+    }
+
+    fn schoolbook_product(a: &[u64], b: &[u64]) -> Vec<u64> {
+        let mut result = vec![0; a.len() + b.len()];
+        schoolbook(a, b, &mut result);
+        trim(&mut result);
+        result
+    }
+
     #[test]
     fn test_karatsuba_big() {
         let a = [1, 2, 3, 4, 5, 6, 7, 8,
                  9, 10, 11, 12, 13, 14, 15, 16];
         let b = [16, 15, 14, 13, 12, 11, 10, 9,
                  8, 7, 6, 5, 4, 3, 2, 1];
-        assert_eq!(karatsuba(&a, &b), 
+        assert_eq!(karatsuba_product(&a, &b), 
                    vec!(16, 47, 92, 150, 220, 301, 392, 492, 600,
                         715, 836, 962, 1092, 1225, 1360, 1496, 
                         1360, 1225, 1092, 962, 836, 715, 600, 
                         492, 392, 301, 220, 150, 92, 47, 16));
     }
+
+    #[test]
+    fn test_karatsuba_matches_schoolbook() {
+        // A simple generator of digits which exercise carries
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut digits = |n: usize| -> Vec<u64> {
+            (0..n).map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                if state.is_multiple_of(3) { NINE } else { state }
+            }).collect()
+        };
+        for &(m, n) in &[(32, 32), (33, 40), (64, 64), (100, 37), (200, 199), (257, 64)] {
+            let a = digits(m);
+            let b = digits(n);
+            assert_eq!(karatsuba_product(&a, &b), schoolbook_product(&a, &b));
+            assert_eq!(mul(&a, &b), schoolbook_product(&a, &b));
+        }
+        let a = vec!(NINE; 80);
+        assert_eq!(karatsuba_product(&a, &a), schoolbook_product(&a, &a));
+    }
 }
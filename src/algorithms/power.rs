@@ -0,0 +1,204 @@
+use core::convert::TryFrom;
+use core::mem;
+use crate::{Natural, Limb};
+use super::division::div;
+use super::multiplication::mul_into;
+use super::shift::{shl_slice, shr_slice};
+
+/// Raise a slice to a power, allocating the result
+/// 
+/// Any factor of two in the base is removed first and restored at the end with a shift,
+/// so that raising a power of two to a power costs no multiplications at all.
+pub fn pow(base: &[Limb], exp: u64) -> Vec<Limb> {
+    if exp == 0 {
+        return vec!(1);
+    }
+    if base.is_empty() {
+        return Vec::new();
+    }
+    let first_nonzero = base.iter().position(|&d| d != 0).unwrap();
+    let zeroes = 64 * first_nonzero + base[first_nonzero].trailing_zeros() as usize;
+    let odd = shr_slice(base, zeroes);
+    let shift = usize::try_from(zeroes as u128 * exp as u128)
+        .expect("Result of exponentiation is too large to represent");
+    shl_slice(&pow_odd(&odd, exp), shift)
+}
+
+/// Raise a non-zero slice to a non-zero power by left-to-right binary exponentiation
+/// 
+/// Both working buffers are allocated up front, so the squarings and multiplications
+/// never reallocate. Each product is written with a.len() + b.len() limbs before it
+/// is trimmed, which can be one limb more than the result needs.
+fn pow_odd(base: &[Limb], exp: u64) -> Vec<Limb> {
+    if base == [1] {
+        return vec!(1);
+    }
+    let base_bits = 64 * base.len() as u128 - base[base.len()-1].leading_zeros() as u128;
+    let limbs = usize::try_from(base_bits * exp as u128 / 64 + 1)
+        .expect("Result of exponentiation is too large to represent");
+    let mut result = Vec::with_capacity(limbs + 1);
+    let mut scratch = Vec::with_capacity(limbs + 1);
+    result.extend_from_slice(base);
+
+    // The leading bit of the exponent is accounted for by starting from the base
+    for i in (0..63 - exp.leading_zeros()).rev() {
+        mul_into(&result, &result, &mut scratch);
+        mem::swap(&mut result, &mut scratch);
+        if (exp >> i) & 1 == 1 {
+            mul_into(&result, base, &mut scratch);
+            mem::swap(&mut result, &mut scratch);
+        }
+    }
+    result
+}
+
+/// Raise a number to a power modulo m by sliding-window exponentiation
+///
+/// Panics if m is zero
+pub fn pow_mod(base: &Natural, exp: &Natural, m: &Natural) -> Natural {
+    if *m == Natural::from(1u64) {
+        return Natural::ZERO;
+    }
+    let mut modulus = Modulus { m, scratch: Vec::new() };
+    window_pow(&div(base, m).1, exp, Natural::from(1u64), |a, b| modulus.mul(a, b))
+}
+
+/// Raise a number to a power by sliding-window exponentiation, using the given
+/// multiplication and its identity
+///
+/// The exponent is consumed in windows of up to k bits which end in a one, using a
+/// table of the odd powers of the base below 2^k. This needs one multiplication per
+/// window rather than one per set bit, at the cost of 2^(k-1) to build the table.
+pub fn window_pow<F>(base: &Natural, exp: &Natural, one: Natural, mut mul: F) -> Natural
+    where F: FnMut(&Natural, &Natural) -> Natural
+{
+    let bits = exp.bit_length();
+    if bits == 0 {
+        return one;
+    }
+    let k = window_size(bits);
+    let base_squared = mul(base, base);
+    let mut table = vec!(base.clone());
+    for i in 1..1 << (k - 1) {
+        let next = mul(&table[i - 1], &base_squared);
+        table.push(next);
+    }
+
+    let bit = |i: u64| (exp.digits[(i / 64) as usize] >> (i % 64)) & 1;
+    let mut result: Option<Natural> = None;
+    // Bits below i are still to be consumed
+    let mut i = bits;
+    while i > 0 {
+        if bit(i - 1) == 0 {
+            result = result.map(|r| mul(&r, &r));
+            i -= 1;
+            continue;
+        }
+        let mut j = i.saturating_sub(k);
+        while bit(j) == 0 {
+            j += 1;
+        }
+        let window = (j..i).rev().fold(0, |w, b| (w << 1) | bit(b)) as usize;
+        let power = &table[window >> 1];
+        result = Some(match result {
+            Some(mut r) => {
+                for _ in j..i {
+                    r = mul(&r, &r);
+                }
+                mul(&r, power)
+            },
+            None => power.clone(),
+        });
+        i = j;
+    }
+    result.unwrap()
+}
+
+/// Chooses the window size which minimises the table size plus the expected
+/// number of windows in an exponent with the given number of bits
+fn window_size(bits: u64) -> u64 {
+    (1..=8).min_by_key(|&k| (1 << (k - 1)) + bits / (k + 1)).unwrap()
+}
+
+/// Multiplication modulo m, reusing one buffer for every product
+struct Modulus<'a> {
+    m: &'a Natural,
+    scratch: Vec<Limb>,
+}
+
+impl Modulus<'_> {
+    fn mul(&mut self, a: &Natural, b: &Natural) -> Natural {
+        mul_into(&a.digits, &b.digits, &mut self.scratch);
+        let product = Natural::from(mem::take(&mut self.scratch));
+        let (_, r) = div(&product, self.m);
+        self.scratch = product.digits;
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pow_small() {
+        assert_eq!(pow(&[3], 0), vec!(1));
+        assert_eq!(pow(&[], 0), vec!(1));
+        assert!(pow(&[], 5).is_empty());
+        assert_eq!(pow(&[3], 5), vec!(243));
+        assert_eq!(pow(&[10], 19), vec!(10_000_000_000_000_000_000));
+    }
+
+    #[test]
+    fn test_pow_powers_of_two() {
+        assert_eq!(pow(&[2], 64), vec!(0, 1));
+        assert_eq!(pow(&[0, 1], 3), vec!(0, 0, 0, 1));
+        assert_eq!(pow(&[12], 2), vec!(144));
+    }
+
+    #[test]
+    fn test_pow_multi_digit() {
+        // (2^64 - 1)^2 = 2^128 - 2^65 + 1
+        assert_eq!(pow(&[u64::MAX], 2), vec!(1, u64::MAX - 1));
+        // (2^64 + 1)^3 = 2^192 + 3 * 2^128 + 3 * 2^64 + 1
+        assert_eq!(pow(&[1, 1], 3), vec!(1, 3, 3, 1));
+    }
+
+    #[test]
+    fn test_pow_odd_capacity() {
+        // (2^64 + 1)^2 fits in three limbs, but mul_into writes four before trimming
+        for (base, exp) in [(vec!(1, 1), 2), (vec!(3, 1), 5), (vec!(u64::MAX), 3), (vec!(5, 0, 1), 7)] {
+            let bits = 64 * base.len() as u64 - base[base.len()-1].leading_zeros() as u64;
+            let limbs = (bits * exp / 64 + 1) as usize;
+            assert_eq!(pow_odd(&base, exp).capacity(), limbs + 1);
+        }
+    }
+
+    #[test]
+    fn test_window_size() {
+        assert_eq!(window_size(1), 1);
+        assert_eq!(window_size(64), 3);
+        assert_eq!(window_size(4096), 7);
+    }
+
+    #[test]
+    fn test_pow_mod_matches_repeated_multiplication() {
+        let m = Natural::from(vec!(0x1234_5678_9abc_def1, 0xfeed, 3));
+        let base = Natural::from(vec!(u64::MAX, 42, 7, 1));
+        let mut expected = Natural::from(1u64);
+        for e in 0u64..200 {
+            assert_eq!(pow_mod(&base, &Natural::from(e), &m), expected, "exponent {}", e);
+            expected = div(&(&expected * &base), &m).1;
+        }
+    }
+
+    #[test]
+    fn test_pow_mod_large_exponent() {
+        // By Fermat's little theorem a^(p-1) = 1 (mod p) for the prime p = 2^127 - 1
+        let p = Natural::from(u128::MAX >> 1);
+        let e = &p - &Natural::from(1u64);
+        assert_eq!(pow_mod(&Natural::from(3u64), &e, &p), Natural::from(1u64));
+        assert_eq!(pow_mod(&Natural::from(3u64), &e, &Natural::from(1u64)), Natural::ZERO);
+        assert_eq!(pow_mod(&Natural::ZERO, &Natural::ZERO, &p), Natural::from(1u64));
+    }
+}
@@ -0,0 +1,78 @@
+use crate::Limb;
+
+/// Shift a little-endian slice left by the given number of bits, allocating the result
+/// 
+/// The result is normalised so long as the input is normalised
+pub fn shl_slice(a: &[Limb], bits: usize) -> Vec<Limb> {
+    if a.is_empty() {
+        return Vec::new();
+    }
+    let limbs = bits / 64;
+    let k = (bits % 64) as u32;
+    let mut result = Vec::with_capacity(a.len() + limbs + 1);
+    result.resize(limbs, 0);
+    if k == 0 {
+        result.extend_from_slice(a);
+    }
+    else {
+        let mut carry = 0;
+        for digit in a {
+            result.push((digit << k) | carry);
+            carry = digit >> (64 - k);
+        }
+        if carry != 0 {
+            result.push(carry);
+        }
+    }
+    result
+}
+
+/// Shift a little-endian slice right by the given number of bits, discarding the bits
+/// shifted out and allocating the result
+pub fn shr_slice(a: &[Limb], bits: usize) -> Vec<Limb> {
+    let limbs = bits / 64;
+    if limbs >= a.len() {
+        return Vec::new();
+    }
+    let k = (bits % 64) as u32;
+    let a = &a[limbs..];
+    let mut result = Vec::with_capacity(a.len());
+    if k == 0 {
+        result.extend_from_slice(a);
+    }
+    else {
+        for (i, digit) in a.iter().enumerate() {
+            let high = a.get(i + 1).map_or(0, |d| d << (64 - k));
+            result.push((digit >> k) | high);
+        }
+    }
+    while let Some(&0) = result.last() {
+        result.pop();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NINE: u64 = u64::MAX;
+
+    #[test]
+    fn test_shl_slice() {
+        assert_eq!(shl_slice(&[1], 0), vec!(1));
+        assert_eq!(shl_slice(&[1], 64), vec!(0, 1));
+        assert_eq!(shl_slice(&[NINE, 1], 4), vec!(NINE << 4, 0x1f));
+        assert_eq!(shl_slice(&[3], 127), vec!(0, 1 << 63, 1));
+        assert!(shl_slice(&[], 5).is_empty());
+    }
+
+    #[test]
+    fn test_shr_slice() {
+        assert_eq!(shr_slice(&[0, 1], 64), vec!(1));
+        assert_eq!(shr_slice(&[NINE << 4, 0x1f], 4), vec!(NINE, 1));
+        assert_eq!(shr_slice(&[0, 1 << 63, 1], 127), vec!(3));
+        assert!(shr_slice(&[5], 3).is_empty());
+        assert!(shr_slice(&[5, 6], 200).is_empty());
+    }
+}
@@ -11,16 +11,18 @@ pub fn sub_signed(first: &Natural, second: &Natural) -> (Sign, Natural) {
     }
 }
 
+/// Subtract second from first in-place, returning whether a borrow propagated out of first
 #[inline]
-pub (in crate::algorithms) fn sub_slice_assign(first: &mut [Limb], second: &[Limb]) {
+pub (in crate::algorithms) fn sub_slice_assign(first: &mut [Limb], second: &[Limb]) -> bool {
     let mut carry: bool = false;
     let mut other_iter = second.iter();
     for digit in first.iter_mut() {
-        let other_digit = other_iter.next().or(Some(&0)).unwrap();
+        let other_digit = other_iter.next().unwrap_or(&0);
         let (a, b) = sub_with_carry(*digit, *other_digit, carry);
         carry = b;
         *digit = a;
     }
+    carry
 }
 
 #[inline]
@@ -30,7 +32,7 @@ pub (in crate::algorithms) fn sub_slice(first: &[Limb], second: &[Limb]) -> Vec<
 
     let mut other_iter = second.iter();
     for digit in first.iter() {
-        let other_digit = other_iter.next().or(Some(&0)).unwrap();
+        let other_digit = other_iter.next().unwrap_or(&0);
         let (a, b) = sub_with_carry(*digit, *other_digit, carry);
         carry = b;
         result.push(a);
@@ -114,14 +116,14 @@ mod tests {
     fn test_sub_bigger_number() {
         let a = Natural::from(vec!(1, 2));
         let b = Natural::from(vec!(3, 2));
-        assert_eq!(sub_signed(&a, &b), (Sign::Negative, Natural::from(2)));
+        assert_eq!(sub_signed(&a, &b), (Sign::Negative, Natural::from(2u64)));
     }
 
     #[test]
     fn test_sub_smaller_number() {
         let a = Natural::from(vec!(3, 2));
         let b = Natural::from(vec!(1, 2));
-        assert_eq!(sub_signed(&a, &b), (Sign::Positive, Natural::from(2)));
+        assert_eq!(sub_signed(&a, &b), (Sign::Positive, Natural::from(2u64)));
     }
 
     /*
@@ -0,0 +1,129 @@
+// Barrett reduction modulo any non-zero Natural
+//
+// For a k-digit modulus m and B = 2^64, the value mu = floor(B^(2k) / m) is computed
+// once. The quotient of any x < B^(2k) by m is then estimated from the top digits of
+// x times mu, which is never too large and at most two too small, so reduction needs
+// two multiplications and a couple of subtractions but no division.
+
+use crate::natural::Natural;
+use crate::algorithms::{div, window_pow};
+
+///
+/// Precomputed values for repeated reduction modulo a fixed number
+///
+/// Unlike `MontgomeryContext`, any non-zero modulus is allowed and numbers are used
+/// as they are, with no conversion into a special form.
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, BarrettContext};
+///
+/// let context = BarrettContext::new(&Natural::from(1000u64));
+/// assert_eq!(context.mul_mod(&Natural::from(123u64), &Natural::from(456u64)), Natural::from(88u64));
+/// assert_eq!(context.pow_mod(&Natural::from(7u64), &Natural::from(4u64)), Natural::from(401u64));
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct BarrettContext {
+    modulus: Natural,
+    /// floor(B^(2k) / m)
+    mu: Natural,
+    /// The number of digits of the modulus
+    k: usize,
+}
+
+/// Returns floor(n / B^shift)
+fn shift_right_limbs(n: &Natural, shift: usize) -> Natural {
+    Natural::from(n.digits.get(shift..).unwrap_or(&[]).to_vec())
+}
+
+impl BarrettContext {
+    ///
+    /// Creates a context for reduction modulo m
+    ///
+    /// # Panics
+    /// Panics if m is zero
+    pub fn new(m: &Natural) -> Self {
+        assert!(!m.is_zero(), "Cannot reduce modulo zero");
+        let k = m.digits.len();
+        let mut power = vec!(0; 2 * k);
+        power.push(1);
+        Self {
+            modulus: m.clone(),
+            mu: div(&Natural::from(power), m).0,
+            k,
+        }
+    }
+
+    /// The modulus of this context
+    pub fn modulus(&self) -> &Natural {
+        &self.modulus
+    }
+
+    ///
+    /// Returns x modulo m
+    ///
+    /// This is fastest for x below m^2, such as a product of two reduced numbers.
+    /// Larger numbers fall back to a full division.
+    pub fn reduce(&self, x: &Natural) -> Natural {
+        if x.digits.len() > 2 * self.k {
+            return div(x, &self.modulus).1;
+        }
+        let q = shift_right_limbs(&(&shift_right_limbs(x, self.k - 1) * &self.mu), self.k + 1);
+        // The estimate q is at most two less than the true quotient
+        let mut r = x - &(&q * &self.modulus);
+        while r >= self.modulus {
+            r = &r - &self.modulus;
+        }
+        r
+    }
+
+    ///
+    /// Returns a * b modulo m
+    pub fn mul_mod(&self, a: &Natural, b: &Natural) -> Natural {
+        let a = self.reduce(a);
+        let b = self.reduce(b);
+        self.reduce(&(&a * &b))
+    }
+
+    ///
+    /// Returns base raised to the power exp, modulo m
+    pub fn pow_mod(&self, base: &Natural, exp: &Natural) -> Natural {
+        let one = self.reduce(&Natural::from(1u64));
+        window_pow(&self.reduce(base), exp, one, |x, y| self.reduce(&(x * y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduce_matches_division() {
+        for m in [Natural::from(10u64).pow(30), Natural::from(vec!(0, 0, 1 << 63)), Natural::from(vec!(7, 3))] {
+            let context = BarrettContext::new(&m);
+            let mut x = &(&m * &m) - &Natural::from(1u64);
+            for _ in 0..40 {
+                assert_eq!(context.reduce(&x), &x % &m);
+                x = x.div(&Natural::from(3u64)).0;
+            }
+            let huge = m.pow(5);
+            assert_eq!(context.reduce(&(&huge + &Natural::from(9u64))), &Natural::from(9u64) % &m);
+        }
+    }
+
+    #[test]
+    fn test_pow_mod_even_modulus() {
+        let m = Natural::from(vec!(0, 1)); // 2^64
+        let context = BarrettContext::new(&m);
+        assert_eq!(context.pow_mod(&Natural::from(3u64), &Natural::from(1u64 << 30)),
+                   Natural::from(3u64.wrapping_pow(1u32 << 30)));
+        assert_eq!(context.pow_mod(&Natural::from(3u64), &Natural::ZERO), Natural::from(1u64));
+        assert_eq!(BarrettContext::new(&Natural::from(1u64)).pow_mod(&Natural::from(3u64), &Natural::ZERO), Natural::ZERO);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_modulus() {
+        BarrettContext::new(&Natural::ZERO);
+    }
+}
@@ -0,0 +1,107 @@
+// Prints the prime factors of numbers, in the format of GNU coreutils factor
+//
+// usage: factor [-h|--exponents] [number]...
+//
+// Each number is printed followed by a colon and its prime factors in increasing
+// order, repeated according to multiplicity, e.g. "12: 2 2 3". With -h repeated
+// factors are instead printed once as p^e. Without arguments, the numbers are read
+// from standard input, separated by whitespace.
+
+use std::io::{self, BufRead, BufWriter, Write};
+use std::process;
+
+use skewes::Natural;
+
+/// Parses a number as GNU factor does: decimal digits only, after an optional '+'
+fn parse(word: &str) -> Option<Natural> {
+    let digits = word.strip_prefix('+').unwrap_or(word);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Writes the factorisation of one number, returning false if it is not a valid number
+fn factor(word: &str, exponents: bool, out: &mut impl Write) -> io::Result<bool> {
+    let n = match parse(word) {
+        Some(n) => n,
+        None => {
+            out.flush()?;
+            eprintln!("factor: '{}' is not a valid positive integer", word);
+            return Ok(false);
+        }
+    };
+    write!(out, "{}:", n)?;
+    for (p, e) in n.factor() {
+        if exponents && e > 1 {
+            write!(out, " {}^{}", p, e)?;
+        }
+        else {
+            for _ in 0..e {
+                write!(out, " {}", p)?;
+            }
+        }
+    }
+    writeln!(out)?;
+    Ok(true)
+}
+
+fn run(args: &[String], exponents: bool) -> io::Result<bool> {
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut valid = true;
+    if args.is_empty() {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+            for word in line?.split_whitespace() {
+                valid &= factor(word, exponents, &mut out)?;
+            }
+            // Answer each line as it arrives, for use as a coprocess
+            out.flush()?;
+        }
+    }
+    else {
+        for word in args {
+            valid &= factor(word, exponents, &mut out)?;
+        }
+    }
+    out.flush()?;
+    Ok(valid)
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let exponents = args.first().is_some_and(|arg| arg == "-h" || arg == "--exponents");
+    if exponents {
+        args.remove(0);
+    }
+    match run(&args, exponents) {
+        Ok(true) => {}
+        // A closed pipe ends the output quietly, as for any other failed write
+        Ok(false) | Err(_) => process::exit(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("360"), Some(Natural::from(360u64)));
+        assert_eq!(parse("+17"), Some(Natural::from(17u64)));
+        assert_eq!(parse("0"), Some(Natural::ZERO));
+        for word in ["0x10", "0b101", "0o17", "1_000", " 12", "12 ", "", "+", "++1", "-5", "1e3", "１２"] {
+            assert_eq!(parse(word), None, "{:?}", word);
+        }
+    }
+
+    #[test]
+    fn test_factor_output() {
+        let mut out = Vec::new();
+        assert!(factor("360", false, &mut out).unwrap());
+        assert!(factor("360", true, &mut out).unwrap());
+        assert!(!factor("0x10", false, &mut out).unwrap());
+        assert_eq!(String::from_utf8(out).unwrap(), "360: 2 2 2 3 3 5\n360: 2^3 3^2 5\n");
+    }
+}
@@ -0,0 +1,103 @@
+// Prints the primes in a range, in the manner of BSD primes(6)
+//
+// usage: primes [-h] [start [stop]]
+//
+// Prints the primes at or above start and below stop, one per line. Without a start
+// value it is read from standard input, and without a stop value the primes go on
+// until the output is closed. With -h they are printed in hexadecimal.
+
+use std::io::{self, BufRead, BufWriter, Write};
+use std::process;
+
+use skewes::{Natural, SegmentedSieve};
+
+/// The width of each window sieved when there is no stop value
+const WINDOW: u64 = 1 << 32;
+
+fn fail(message: &str) -> ! {
+    eprintln!("primes: {}", message);
+    process::exit(1);
+}
+
+/// Parses decimal digits after an optional plus sign, as factor does
+fn parse_number(s: &str) -> Option<Natural> {
+    let digits = s.strip_prefix('+').unwrap_or(s);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+fn parse(s: &str) -> Natural {
+    if s.starts_with('-') {
+        fail("negative numbers aren't permitted.");
+    }
+    parse_number(s).unwrap_or_else(|| fail(&format!("{}: illegal numeric format.", s)))
+}
+
+fn read_start() -> Natural {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match io::stdin().lock().read_line(&mut line) {
+            Ok(0) | Err(_) => process::exit(0),
+            Ok(_) => if let Some(word) = line.split_whitespace().next() {
+                return parse(word);
+            },
+        }
+    }
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let hex = args.first().is_some_and(|arg| arg == "-h");
+    if hex {
+        args.remove(0);
+    }
+    if args.len() > 2 {
+        eprintln!("usage: primes [-h] [start [stop]]");
+        process::exit(1);
+    }
+    let start = args.first().map_or_else(read_start, |arg| parse(arg));
+    let stop = args.get(1).map(|arg| parse(arg));
+    if stop.as_ref().is_some_and(|stop| start > *stop) {
+        fail("start value must be less than stop value.");
+    }
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut a = start;
+    loop {
+        let b = match &stop {
+            Some(stop) => stop.clone(),
+            None => &a + &Natural::from(WINDOW),
+        };
+        for p in SegmentedSieve::new(&a, &b) {
+            let written = if hex { writeln!(out, "{:#x}", p) } else { writeln!(out, "{}", p) };
+            if written.is_err() {
+                // The reader has gone away
+                return;
+            }
+        }
+        if stop.is_some() {
+            break;
+        }
+        a = b;
+    }
+    let _ = out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("1000"), Some(Natural::from(1000u64)));
+        assert_eq!(parse_number("+17"), Some(Natural::from(17u64)));
+        assert_eq!(parse_number("0"), Some(Natural::ZERO));
+        for s in ["0x10", "0b101", "0o17", "1_000", " 12", "12 ", "", "+", "++1", "-5", "1e3", "１２"] {
+            assert_eq!(parse_number(s), None, "{:?}", s);
+        }
+    }
+}
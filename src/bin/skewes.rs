@@ -0,0 +1,85 @@
+// Compares pi(x) with li(x) and Riemann's R(x)
+//
+// usage: skewes [x ...]
+//
+// Prints a table of pi(x), the floors of li(x) and R(x), and their differences from
+// pi(x), for each x given or else for the powers of ten up to 10^12. Where x is 2^64 or
+// more pi(x) is out of reach and only li(x) and R(x) are printed. Since pi(x) is an
+// integer, li(x) >= pi(x) exactly when the difference of floors is at least zero.
+
+use std::process;
+
+use skewes::{Natural, Integer, RealInterval, li, prime_count, riemann_r};
+
+fn fail(message: &str) -> ! {
+    eprintln!("skewes: {}", message);
+    process::exit(1);
+}
+
+fn parse(s: &str) -> Natural {
+    if s.starts_with('-') {
+        fail("negative numbers aren't permitted.");
+    }
+    let digits = s.strip_prefix('+').unwrap_or(s);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        fail(&format!("{}: illegal numeric format.", s));
+    }
+    digits.parse().unwrap()
+}
+
+/// The most fractional bits tried when the bounds on li(x) or R(x) straddle an integer
+const MAX_PRECISION: u64 = 4096;
+
+/// The floor of a function at x, raising the precision until it is certain
+fn floor(name: &str, f: fn(&Natural, u64) -> RealInterval, x: &Natural) -> Integer {
+    let mut precision = 32;
+    loop {
+        let value = f(x, precision);
+        if let Some(floor) = value.floor() {
+            return floor;
+        }
+        if precision >= MAX_PRECISION {
+            fail(&format!("{}({}) lies in {:.3}, whose floor is still uncertain.", name, x, value));
+        }
+        precision *= 2;
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let xs: Vec<Natural> = if args.is_empty() {
+        (1..=12).map(|k| Natural::from(10u64).pow(k)).collect()
+    }
+    else {
+        args.iter().map(|arg| parse(arg)).collect()
+    };
+    if xs.iter().any(|x| *x < Natural::from(2u64)) {
+        fail("x must be at least 2.");
+    }
+
+    let limit = Natural::from(vec!(0, 1));
+    let mut rows = vec!(["x", "pi(x)", "li(x)", "R(x)", "li(x) - pi(x)", "R(x) - pi(x)"].map(String::from));
+    for x in &xs {
+        let li = floor("li", li, x);
+        let r = floor("R", riemann_r, x);
+        let row = if *x < limit {
+            let pi = Integer::from(prime_count(x));
+            [x.to_string(), pi.to_string(), li.to_string(), r.to_string(),
+             (&li - &pi).to_string(), (&r - &pi).to_string()]
+        }
+        else {
+            [x.to_string(), "-".into(), li.to_string(), r.to_string(), "-".into(), "-".into()]
+        };
+        rows.push(row);
+    }
+    let mut widths = [0; 6];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    for row in &rows {
+        let cells: Vec<String> = row.iter().zip(&widths).map(|(cell, &width)| format!("{:>width$}", cell, width = width)).collect();
+        println!("{}", cells.join("  "));
+    }
+}
@@ -0,0 +1,299 @@
+// Conversion of Natural and Integer to and from raw bytes
+//
+// Naturals are written as unsigned magnitudes, while Integers use two's complement
+// with the minimum number of bytes needed to carry the sign.
+
+use core::fmt;
+
+use crate::natural::{Natural, Limb};
+use crate::integer::{Integer, Sign};
+
+///
+/// The order in which bytes are written to a fixed-width buffer
+///
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Endianness {
+    /// Least significant byte first
+    Little,
+    /// Most significant byte first
+    Big,
+}
+
+///
+/// The error returned when a number is too large to be written into a buffer
+///
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct BufferTooSmallError {
+    required: usize,
+}
+
+impl BufferTooSmallError {
+    /// The number of bytes which would have been needed to write the number
+    pub fn required(&self) -> usize {
+        self.required
+    }
+}
+
+impl fmt::Display for BufferTooSmallError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "buffer too small, {} bytes are required", self.required)
+    }
+}
+
+impl std::error::Error for BufferTooSmallError {}
+
+/// Returns the little-endian bytes of a slice of limbs, with no trailing zero bytes
+fn limbs_to_bytes_le(digits: &[Limb]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = digits.iter().flat_map(|d| d.to_le_bytes()).collect();
+    while let Some(&0) = bytes.last() {
+        bytes.pop();
+    }
+    bytes
+}
+
+/// Builds the limbs of a number from its little-endian bytes
+fn limbs_from_bytes_le(bytes: &[u8]) -> Vec<Limb> {
+    let mut digits: Vec<Limb> = bytes.chunks(8)
+        .map(|chunk| {
+            let mut limb = [0u8; 8];
+            limb[..chunk.len()].copy_from_slice(chunk);
+            Limb::from_le_bytes(limb)
+        })
+        .collect();
+    while let Some(&0) = digits.last() {
+        digits.pop();
+    }
+    digits
+}
+
+/// Copies little-endian bytes into a buffer, padding with the given byte
+fn write_padded(bytes: &[u8], pad: u8, buf: &mut [u8], endianness: Endianness) -> Result<(), BufferTooSmallError> {
+    if bytes.len() > buf.len() {
+        return Err(BufferTooSmallError { required: bytes.len() });
+    }
+    let (value, padding) = buf.split_at_mut(bytes.len());
+    value.copy_from_slice(bytes);
+    padding.iter_mut().for_each(|b| *b = pad);
+    if endianness == Endianness::Big {
+        buf.reverse();
+    }
+    Ok(())
+}
+
+impl Natural {
+    ///
+    /// Returns the bytes of the number, least significant first
+    ///
+    /// The result is as short as possible, except that zero is written as a single zero byte.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(0x1234u64).to_bytes_le(), vec!(0x34, 0x12));
+    /// ```
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        let bytes = limbs_to_bytes_le(&self.digits);
+        if bytes.is_empty() { vec!(0) } else { bytes }
+    }
+
+    ///
+    /// Returns the bytes of the number, most significant first
+    ///
+    /// The result is as short as possible, except that zero is written as a single zero byte.
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let mut bytes = self.to_bytes_le();
+        bytes.reverse();
+        bytes
+    }
+
+    ///
+    /// Builds a number from its bytes, least significant first
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from_bytes_le(&[0x34, 0x12, 0]), Natural::from(0x1234u64));
+    /// ```
+    pub fn from_bytes_le(bytes: &[u8]) -> Self {
+        Natural::from(limbs_from_bytes_le(bytes))
+    }
+
+    ///
+    /// Builds a number from its bytes, most significant first
+    pub fn from_bytes_be(bytes: &[u8]) -> Self {
+        let reversed: Vec<u8> = bytes.iter().rev().copied().collect();
+        Natural::from_bytes_le(&reversed)
+    }
+
+    ///
+    /// Writes the number into a fixed-width buffer, padding with zero bytes
+    ///
+    /// Returns an error, leaving the buffer untouched, if the number does not fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::{Natural, Endianness};
+    ///
+    /// let mut buf = [0xffu8; 4];
+    /// Natural::from(0x0102u64).write_to_slice(&mut buf, Endianness::Big).unwrap();
+    /// assert_eq!(buf, [0, 0, 1, 2]);
+    /// assert!(Natural::from(1u64 << 32).write_to_slice(&mut buf, Endianness::Big).is_err());
+    /// ```
+    pub fn write_to_slice(&self, buf: &mut [u8], endianness: Endianness) -> Result<(), BufferTooSmallError> {
+        write_padded(&limbs_to_bytes_le(&self.digits), 0, buf, endianness)
+    }
+}
+
+impl Integer {
+    /// Returns the minimal two's complement bytes of the number, least significant first,
+    /// along with the byte used to sign-extend them
+    fn twos_complement_le(&self) -> (Vec<u8>, u8) {
+        match self.sign {
+            Sign::Positive => {
+                let mut bytes = limbs_to_bytes_le(&self.size.digits);
+                if bytes.last().is_some_and(|b| b & 0x80 != 0) {
+                    bytes.push(0);
+                }
+                (bytes, 0)
+            },
+            Sign::Negative => {
+                // -x is the bitwise complement of x - 1
+                let mut magnitude = self.size.clone();
+                magnitude.dec();
+                let mut bytes: Vec<u8> = limbs_to_bytes_le(&magnitude.digits)
+                    .iter()
+                    .map(|b| !b)
+                    .collect();
+                if bytes.last().is_none_or(|b| b & 0x80 == 0) {
+                    bytes.push(0xff);
+                }
+                (bytes, 0xff)
+            },
+        }
+    }
+
+    ///
+    /// Returns the two's complement bytes of the number, least significant first
+    ///
+    /// The result is the shortest which preserves the sign of the number.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Integer;
+    ///
+    /// assert_eq!(Integer::from(-129).to_signed_bytes_le(), vec!(0x7f, 0xff));
+    /// assert_eq!(Integer::from(128).to_signed_bytes_le(), vec!(0x80, 0));
+    /// ```
+    pub fn to_signed_bytes_le(&self) -> Vec<u8> {
+        let (bytes, _) = self.twos_complement_le();
+        if bytes.is_empty() { vec!(0) } else { bytes }
+    }
+
+    ///
+    /// Returns the two's complement bytes of the number, most significant first
+    ///
+    /// The result is the shortest which preserves the sign of the number.
+    pub fn to_signed_bytes_be(&self) -> Vec<u8> {
+        let mut bytes = self.to_signed_bytes_le();
+        bytes.reverse();
+        bytes
+    }
+
+    ///
+    /// Builds a number from its two's complement bytes, least significant first
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Integer;
+    ///
+    /// assert_eq!(Integer::from_signed_bytes_le(&[0x7f, 0xff]), Integer::from(-129));
+    /// ```
+    pub fn from_signed_bytes_le(bytes: &[u8]) -> Self {
+        match bytes.last() {
+            Some(b) if b & 0x80 != 0 => {
+                let complement: Vec<u8> = bytes.iter().map(|b| !b).collect();
+                let mut size = Natural::from_bytes_le(&complement);
+                size.inc();
+                Integer::from_sign_and_size(Sign::Negative, size)
+            },
+            _ => Integer::from(Natural::from_bytes_le(bytes)),
+        }
+    }
+
+    ///
+    /// Builds a number from its two's complement bytes, most significant first
+    pub fn from_signed_bytes_be(bytes: &[u8]) -> Self {
+        let reversed: Vec<u8> = bytes.iter().rev().copied().collect();
+        Integer::from_signed_bytes_le(&reversed)
+    }
+
+    ///
+    /// Writes the number into a fixed-width buffer in two's complement, sign-extending
+    /// to fill the buffer
+    ///
+    /// Returns an error, leaving the buffer untouched, if the number does not fit.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::{Integer, Endianness};
+    ///
+    /// let mut buf = [0u8; 4];
+    /// Integer::from(-2).write_to_slice(&mut buf, Endianness::Little).unwrap();
+    /// assert_eq!(buf, [0xfe, 0xff, 0xff, 0xff]);
+    /// ```
+    pub fn write_to_slice(&self, buf: &mut [u8], endianness: Endianness) -> Result<(), BufferTooSmallError> {
+        let (bytes, pad) = self.twos_complement_le();
+        write_padded(&bytes, pad, buf, endianness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_natural_bytes_round_trip() {
+        let n = Natural::from(vec!(0x0123_4567_89ab_cdef, 0x42));
+        let le = n.to_bytes_le();
+        assert_eq!(le, vec!(0xef, 0xcd, 0xab, 0x89, 0x67, 0x45, 0x23, 0x01, 0x42));
+        assert_eq!(Natural::from_bytes_le(&le), n);
+        assert_eq!(Natural::from_bytes_be(&n.to_bytes_be()), n);
+        assert_eq!(Natural::ZERO.to_bytes_be(), vec!(0));
+        assert_eq!(Natural::from_bytes_be(&[]), Natural::ZERO);
+        assert_eq!(Natural::from_bytes_be(&[0, 0, 0]), Natural::ZERO);
+    }
+
+    #[test]
+    fn test_signed_bytes_match_primitives() {
+        for &v in &[0i64, 1, -1, 127, 128, -128, -129, 255, -256, i64::MAX, i64::MIN] {
+            let z = Integer::from(v);
+            let bytes = z.to_signed_bytes_le();
+            let full = v.to_le_bytes();
+            assert_eq!(&bytes[..], &full[..bytes.len()]);
+            assert_eq!(Integer::from_signed_bytes_le(&bytes), z);
+            assert_eq!(Integer::from_signed_bytes_be(&z.to_signed_bytes_be()), z);
+        }
+        assert_eq!(Integer::from(i128::MIN).to_signed_bytes_le(), i128::MIN.to_le_bytes().to_vec());
+        assert_eq!(Integer::from_signed_bytes_le(&[]), Integer::from(0));
+    }
+
+    #[test]
+    fn test_write_to_slice() {
+        let mut buf = [0xaa; 3];
+        assert_eq!(Natural::from(0x01020304u64).write_to_slice(&mut buf, Endianness::Little),
+                   Err(BufferTooSmallError { required: 4 }));
+        assert_eq!(buf, [0xaa; 3]);
+        Natural::ZERO.write_to_slice(&mut buf, Endianness::Big).unwrap();
+        assert_eq!(buf, [0; 3]);
+
+        let mut buf = [0u8; 2];
+        assert!(Integer::from(128).write_to_slice(&mut buf[..1], Endianness::Big).is_err());
+        Integer::from(-32768).write_to_slice(&mut buf, Endianness::Big).unwrap();
+        assert_eq!(buf, [0x80, 0]);
+        assert!(Integer::from(-32769).write_to_slice(&mut buf, Endianness::Big).is_err());
+        let mut empty: [u8; 0] = [];
+        assert!(Integer::from(0).write_to_slice(&mut empty, Endianness::Big).is_ok());
+    }
+}
@@ -0,0 +1,415 @@
+// Primality certificates based on the factorisation of n - 1
+//
+// If every prime q dividing a factor F of n - 1 has a witness a with a^(n-1) = 1 and
+// gcd(a^((n-1)/q) - 1, n) = 1 (mod n), then every prime factor of n is 1 modulo F
+// (Pocklington). So n is prime when F^2 > n, and by the theorem of Brillhart, Lehmer
+// and Selfridge also when F^3 > n, writing n = c_2 F^2 + c_1 F + 1, provided
+// c_1^2 - 4c_2 is not a square. Each q is proved prime by a certificate of its own,
+// down to primes small enough to check by trial division.
+//
+// Certificates are written one line per prime, each after the primes it depends on:
+//
+//     small <p>
+//     pocklington <n> <q>^<e>:<a> <q>^<e>:<a> ...
+//
+// where the certificate for the last line is the one being presented.
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::natural::Natural;
+use crate::primality::Primality;
+
+/// Primes below this are certified by trial division alone
+const SMALL_LIMIT: u64 = 1 << 32;
+
+/// n - 1 is trial divided by primes up to this bound when building a certificate
+const TRIAL_DIVISION_LIMIT: u64 = 1 << 16;
+
+/// The largest witness tried for each prime factor of n - 1
+const MAX_WITNESS: u64 = 1000;
+
+///
+/// A proof that a number is prime, which can be checked with `verify_certificate`
+///
+/// Certificates can be written out with `Display` and read back with `str::parse`.
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, PrimeCertificate, verify_certificate};
+///
+/// let p = Natural::from(u128::MAX >> 1); // 2^127 - 1
+/// let certificate = p.prime_certificate().unwrap();
+/// let text = certificate.to_string();
+/// let parsed: PrimeCertificate = text.parse().unwrap();
+/// assert!(verify_certificate(&parsed));
+/// assert_eq!(parsed.prime(), &p);
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum PrimeCertificate {
+    /// A prime below 2^32, which is checked by trial division
+    Small(Natural),
+    /// A prime n with a partial factorisation of n - 1
+    Pocklington {
+        /// The prime
+        n: Natural,
+        /// The prime factors of the factored part of n - 1, with their witnesses
+        factors: Vec<FactorWitness>,
+    },
+}
+
+///
+/// A prime factor q^e of n - 1 in a Pocklington certificate, together with a
+/// witness a such that a^(n-1) = 1 (mod n) and gcd(a^((n-1)/q) - 1, n) = 1
+///
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct FactorWitness {
+    /// A certificate that q is prime
+    pub certificate: PrimeCertificate,
+    /// The power of q dividing n - 1
+    pub exponent: u64,
+    /// The witness a
+    pub witness: Natural,
+}
+
+impl PrimeCertificate {
+    /// The number which this certificate proves prime
+    pub fn prime(&self) -> &Natural {
+        match self {
+            PrimeCertificate::Small(p) => p,
+            PrimeCertificate::Pocklington { n, .. } => n,
+        }
+    }
+}
+
+/// Whether n is prime, by trial division
+fn is_prime_by_trial_division(n: &Natural) -> bool {
+    let two = Natural::from(2u64);
+    if *n < two {
+        return false;
+    }
+    let mut d = two;
+    while &d * &d <= *n {
+        if (n % &d).is_zero() {
+            return false;
+        }
+        d.inc();
+    }
+    true
+}
+
+///
+/// Checks a primality certificate, returning true if it proves its number prime
+///
+/// This checks every step of the proof, including the certificates of the prime
+/// factors, using only exact arithmetic.
+pub fn verify_certificate(certificate: &PrimeCertificate) -> bool {
+    match certificate {
+        PrimeCertificate::Small(p) => *p < Natural::from(SMALL_LIMIT) && is_prime_by_trial_division(p),
+        PrimeCertificate::Pocklington { n, factors } => verify_pocklington(n, factors),
+    }
+}
+
+fn verify_pocklington(n: &Natural, factors: &[FactorWitness]) -> bool {
+    let one = Natural::from(1u64);
+    if *n <= Natural::from(2u64) {
+        return false;
+    }
+    let n_minus_1 = n - &one;
+    let mut f = one.clone();
+    // The part of n - 1 not yet accounted for, which q^e must divide before F is
+    // multiplied by it, so that a huge exponent is rejected before it is used
+    let mut rest = n_minus_1.clone();
+    for (i, factor) in factors.iter().enumerate() {
+        let q = factor.certificate.prime();
+        if factor.exponent == 0 || factors[..i].iter().any(|other| other.certificate.prime() == q) {
+            return false;
+        }
+        if !verify_certificate(&factor.certificate) {
+            return false;
+        }
+        for _ in 0..factor.exponent {
+            let (quotient, remainder) = rest.div(q);
+            if !remainder.is_zero() {
+                return false;
+            }
+            rest = quotient;
+        }
+        f = &f * &q.pow(factor.exponent);
+        let a = &factor.witness;
+        if a.pow_mod(&n_minus_1, n) != one {
+            return false;
+        }
+        let partial = a.pow_mod(&n_minus_1.div(q).0, n);
+        if partial.is_zero() || partial.sub(&one).unwrap().gcd(n) != one {
+            return false;
+        }
+    }
+    // Every prime factor of n is at least F + 1
+    if &f * &f > *n {
+        return true;
+    }
+    if &(&f * &f) * &f <= *n {
+        return false;
+    }
+    // n = c_2 F^2 + c_1 F + 1 with c_1 < F, and n is prime exactly when c_1^2 - 4c_2
+    // is not a square
+    let (c_2, c_1) = n_minus_1.div(&f).0.div(&f);
+    let c_1_squared = &c_1 * &c_1;
+    let four_c_2 = &Natural::from(4u64) * &c_2;
+    match c_1_squared.sub(&four_c_2) {
+        Some(discriminant) => !discriminant.is_perfect_square(),
+        None => true,
+    }
+}
+
+/// Returns the primes up to a limit
+fn primes_up_to(limit: u64) -> Vec<u64> {
+    let mut composite = vec!(false; limit as usize + 1);
+    let mut primes = Vec::new();
+    for i in 2..=limit as usize {
+        if !composite[i] {
+            primes.push(i as u64);
+            for j in (i * i..=limit as usize).step_by(i) {
+                composite[j] = true;
+            }
+        }
+    }
+    primes
+}
+
+impl Natural {
+    ///
+    /// Builds a certificate proving this number prime
+    ///
+    /// This needs n - 1 to be factored far enough that the factored part exceeds the
+    /// cube root of n. Small factors are found by trial division, and a remaining
+    /// cofactor is used if it is itself prime. Returns None if the number is not
+    /// prime or if n - 1 could not be factored far enough.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::{Natural, verify_certificate};
+    ///
+    /// let certificate = Natural::from(1_000_000_007u64).prime_certificate().unwrap();
+    /// assert!(verify_certificate(&certificate));
+    /// assert!(Natural::from(1_000_000_005u64).prime_certificate().is_none());
+    /// ```
+    pub fn prime_certificate(&self) -> Option<PrimeCertificate> {
+        if *self < Natural::from(SMALL_LIMIT) {
+            return if is_prime_by_trial_division(self) { Some(PrimeCertificate::Small(self.clone())) } else { None };
+        }
+        if self.is_probable_prime(0) == Primality::Composite {
+            return None;
+        }
+        let one = Natural::from(1u64);
+        let n_minus_1 = self - &one;
+
+        // Factor n - 1 into small primes and a cofactor
+        let mut factors: Vec<(Natural, u64)> = Vec::new();
+        let mut cofactor = n_minus_1.clone();
+        for p in primes_up_to(TRIAL_DIVISION_LIMIT) {
+            let p = Natural::from(p);
+            let mut exponent = 0;
+            loop {
+                let (q, r) = cofactor.div(&p);
+                if !r.is_zero() {
+                    break;
+                }
+                cofactor = q;
+                exponent += 1;
+            }
+            if exponent > 0 {
+                factors.push((p, exponent));
+            }
+        }
+        let mut certificates: Vec<PrimeCertificate> = Vec::new();
+        for (p, _) in &factors {
+            certificates.push(p.prime_certificate()?);
+        }
+        if cofactor > one && cofactor.is_probable_prime(0) != Primality::Composite {
+            if let Some(certificate) = cofactor.prime_certificate() {
+                factors.push((cofactor, 1));
+                certificates.push(certificate);
+            }
+        }
+
+        let n_minus_1_over = |q: &Natural| n_minus_1.div(q).0;
+        let mut witnesses = Vec::with_capacity(factors.len());
+        for ((q, exponent), certificate) in factors.iter().zip(certificates) {
+            let exponent_q = n_minus_1_over(q);
+            let witness = (2..MAX_WITNESS).map(Natural::from).find(|a| {
+                let partial = a.pow_mod(&exponent_q, self);
+                !partial.is_zero()
+                    && partial.pow_mod(q, self) == one
+                    && (&partial - &one).gcd(self) == one
+            })?;
+            witnesses.push(FactorWitness { certificate, exponent: *exponent, witness });
+        }
+        let certificate = PrimeCertificate::Pocklington { n: self.clone(), factors: witnesses };
+        if verify_certificate(&certificate) { Some(certificate) } else { None }
+    }
+}
+
+/// Writes the lines of a certificate after those of the certificates it depends on
+fn write_lines(certificate: &PrimeCertificate, f: &mut fmt::Formatter, written: &mut Vec<Natural>) -> fmt::Result {
+    if written.contains(certificate.prime()) {
+        return Ok(());
+    }
+    match certificate {
+        PrimeCertificate::Small(p) => writeln!(f, "small {}", p)?,
+        PrimeCertificate::Pocklington { n, factors } => {
+            for factor in factors {
+                write_lines(&factor.certificate, f, written)?;
+            }
+            write!(f, "pocklington {}", n)?;
+            for factor in factors {
+                write!(f, " {}^{}:{}", factor.certificate.prime(), factor.exponent, factor.witness)?;
+            }
+            writeln!(f)?;
+        },
+    }
+    written.push(certificate.prime().clone());
+    Ok(())
+}
+
+impl fmt::Display for PrimeCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_lines(self, f, &mut Vec::new())
+    }
+}
+
+///
+/// The error returned when a certificate cannot be parsed
+///
+/// This only reports malformed text. A well-formed certificate may still fail to
+/// verify.
+///
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ParseCertificateError {
+    line: usize,
+}
+
+impl ParseCertificateError {
+    /// The line, counting from one, on which the error was found
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for ParseCertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid primality certificate on line {}", self.line)
+    }
+}
+
+impl std::error::Error for ParseCertificateError {}
+
+impl FromStr for PrimeCertificate {
+    type Err = ParseCertificateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Certificates from earlier lines, which later ones may refer to
+        let mut known: Vec<PrimeCertificate> = Vec::new();
+        let mut last = None;
+        for (i, line) in s.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let error = ParseCertificateError { line: i + 1 };
+            let mut words = line.split_whitespace();
+            let kind = words.next().ok_or_else(|| error.clone())?;
+            let n: Natural = words.next().ok_or_else(|| error.clone())?.parse().map_err(|_| error.clone())?;
+            let certificate = match kind {
+                "small" => PrimeCertificate::Small(n),
+                "pocklington" => {
+                    let factors = words.map(|word| {
+                        let (q, rest) = word.split_once('^')?;
+                        let (exponent, witness) = rest.split_once(':')?;
+                        let q: Natural = q.parse().ok()?;
+                        Some(FactorWitness {
+                            certificate: known.iter().find(|c| *c.prime() == q)?.clone(),
+                            exponent: exponent.parse().ok()?,
+                            witness: witness.parse().ok()?,
+                        })
+                    }).collect::<Option<Vec<_>>>().ok_or_else(|| error.clone())?;
+                    PrimeCertificate::Pocklington { n, factors }
+                },
+                _ => return Err(error),
+            };
+            known.push(certificate.clone());
+            last = Some(certificate);
+        }
+        last.ok_or(ParseCertificateError { line: 1 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_certify_primes() {
+        let one = Natural::from(1u64);
+        let primes = [
+            Natural::from(4_294_967_311u64),
+            Natural::from(u64::MAX - 58),
+            &Natural::from(2u64).pow(89) - &one,
+            &Natural::from(2u64).pow(127) - &one,
+            // The first prime of the form k 2^400 + 1, for which n - 1 is easy to factor
+            (1u64..).map(|k| &(&Natural::from(k) * &Natural::from(2u64).pow(400)) + &one)
+                .find(|n| n.is_probable_prime(0) != Primality::Composite)
+                .unwrap(),
+        ];
+        for p in &primes {
+            let certificate = p.prime_certificate().expect("prime should be certified");
+            assert!(verify_certificate(&certificate));
+            assert_eq!(certificate.prime(), p);
+            assert_eq!(certificate.to_string().parse::<PrimeCertificate>(), Ok(certificate));
+        }
+    }
+
+    #[test]
+    fn test_composites_are_not_certified() {
+        assert!(Natural::from(4_294_967_297u64).prime_certificate().is_none());
+        let m = &Natural::from(2u64).pow(67) - &Natural::from(1u64);
+        assert!(m.prime_certificate().is_none());
+        assert!(Natural::from(1u64).prime_certificate().is_none());
+    }
+
+    #[test]
+    fn test_forged_certificates_fail() {
+        let p = Natural::from(u128::MAX >> 1);
+        let certificate = p.prime_certificate().unwrap();
+        let PrimeCertificate::Pocklington { factors, .. } = &certificate else { panic!("expected a Pocklington certificate") };
+
+        // The same proof cannot be reused for a different number
+        let forged = PrimeCertificate::Pocklington { n: &p + &Natural::from(2u64), factors: factors.clone() };
+        assert!(!verify_certificate(&forged));
+
+        // Dropping factors leaves too little of n - 1 factored
+        let forged = PrimeCertificate::Pocklington { n: p.clone(), factors: factors[..1].to_vec() };
+        assert!(!verify_certificate(&forged));
+
+        // A witness must not be one
+        let mut bad = factors.clone();
+        bad[0].witness = Natural::from(1u64);
+        assert!(!verify_certificate(&PrimeCertificate::Pocklington { n: p.clone(), factors: bad }));
+
+        // An exponent too large for q^e to divide n - 1 is rejected without computing q^e
+        let huge: PrimeCertificate = "small 3\npocklington 7 3^100000000000:2".parse().unwrap();
+        assert!(!verify_certificate(&huge));
+        let mut huge = factors.clone();
+        huge[0].exponent = u64::MAX;
+        assert!(!verify_certificate(&PrimeCertificate::Pocklington { n: p.clone(), factors: huge }));
+
+        assert!(!verify_certificate(&PrimeCertificate::Small(Natural::from(91u64))));
+        assert!(!verify_certificate(&PrimeCertificate::Small(Natural::from(u64::MAX - 58))));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<PrimeCertificate>(), Err(ParseCertificateError { line: 1 }));
+        assert_eq!("small 7\nbogus 5".parse::<PrimeCertificate>(), Err(ParseCertificateError { line: 2 }));
+        // Factors must be certified on an earlier line
+        assert_eq!("pocklington 7 3^1:2".parse::<PrimeCertificate>(), Err(ParseCertificateError { line: 1 }));
+        assert!("small 3\npocklington 7 3^1:3".parse::<PrimeCertificate>().is_ok());
+    }
+}
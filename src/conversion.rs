@@ -0,0 +1,653 @@
+// Conversions between the big number types and Rust's primitive integer and float types
+//
+// Every primitive integer converts losslessly into an Integer, and the unsigned primitives
+// convert losslessly into a Natural. Conversions in the other direction can fail,
+// so they are provided through TryFrom, along with truncating `to_*_wrapping` accessors
+// which behave like an `as` cast on the primitive types.
+//
+// Floats convert into the big number types only when they hold an exact integer, while
+// conversion to floats rounds to nearest, ties to even, as the IEEE 754 operations do.
+
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::natural::{Natural, Limb};
+use crate::integer::{Integer, Sign};
+use crate::algorithms::shl_slice;
+
+///
+/// The error returned when a checked conversion to or from a primitive integer fails
+/// because the value is out of range for the target type
+///
+/// # Example
+/// ```rust
+/// use core::convert::TryFrom;
+/// use skewes::{Natural, TryFromIntError};
+///
+/// let n = Natural::from(300u64);
+/// assert_eq!(u8::try_from(&n), Err(TryFromIntError));
+/// assert_eq!(u16::try_from(&n), Ok(300));
+/// ```
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct TryFromIntError;
+
+impl fmt::Display for TryFromIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "out of range integral type conversion attempted")
+    }
+}
+
+impl std::error::Error for TryFromIntError {}
+
+///
+/// The error returned when converting a float which is not an exact integer,
+/// or is out of range for the target type
+///
+/// # Example
+/// ```rust
+/// use core::convert::TryFrom;
+/// use skewes::{Natural, TryFromFloatError};
+///
+/// assert_eq!(Natural::try_from(1.5f64), Err(TryFromFloatError));
+/// assert_eq!(Natural::try_from(-1.0f64), Err(TryFromFloatError));
+/// assert_eq!(Natural::try_from(1e20f64), Ok(Natural::from(100000000000000000000u128)));
+/// ```
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct TryFromFloatError;
+
+impl fmt::Display for TryFromFloatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "float is not an integer representable by the target type")
+    }
+}
+
+impl std::error::Error for TryFromFloatError {}
+
+impl From<u128> for Natural {
+    fn from(n: u128) -> Self {
+        let mut digits = vec!(n as Limb, (n >> 64) as Limb);
+        while let Some(&0) = digits.last() {
+            digits.pop();
+        }
+        Natural::from(digits)
+    }
+}
+
+impl Natural {
+    /// Returns the number modulo 2^128
+    #[inline]
+    fn low_u128(&self) -> u128 {
+        let low = self.digits.first().copied().unwrap_or(0) as u128;
+        let high = self.digits.get(1).copied().unwrap_or(0) as u128;
+        (high << 64) | low
+    }
+
+    /// Returns the number as a u128 if it fits
+    #[inline]
+    fn to_u128_checked(&self) -> Option<u128> {
+        if self.digits.len() > 2 {
+            None
+        }
+        else {
+            Some(self.low_u128())
+        }
+    }
+}
+
+impl Integer {
+    /// Returns the number modulo 2^128, using two's complement for negative numbers
+    #[inline]
+    fn low_u128(&self) -> u128 {
+        match self.sign {
+            Sign::Positive => self.size.low_u128(),
+            Sign::Negative => self.size.low_u128().wrapping_neg(),
+        }
+    }
+}
+
+// Unsigned primitives which can be widened losslessly into a Limb
+macro_rules! natural_from_small_unsigned {
+    ($($t:ty)*) => ($(
+        impl From<$t> for Natural {
+            #[inline]
+            fn from(n: $t) -> Self {
+                Natural::from(n as Limb)
+            }
+        }
+    )*)
+}
+
+natural_from_small_unsigned! { u8 u16 u32 }
+
+impl From<usize> for Natural {
+    #[inline]
+    fn from(n: usize) -> Self {
+        Natural::from(n as u128)
+    }
+}
+
+macro_rules! natural_try_from_signed {
+    ($($t:ty)*) => ($(
+        impl TryFrom<$t> for Natural {
+            type Error = TryFromIntError;
+
+            #[inline]
+            fn try_from(n: $t) -> Result<Self, Self::Error> {
+                if n < 0 {
+                    Err(TryFromIntError)
+                }
+                else {
+                    Ok(Natural::from(n as u128))
+                }
+            }
+        }
+    )*)
+}
+
+natural_try_from_signed! { i8 i16 i32 i64 i128 isize }
+
+macro_rules! integer_from_unsigned {
+    ($($t:ty)*) => ($(
+        impl From<$t> for Integer {
+            #[inline]
+            fn from(n: $t) -> Self {
+                Integer::from(Natural::from(n))
+            }
+        }
+    )*)
+}
+
+integer_from_unsigned! { u8 u16 u32 u64 u128 usize }
+
+macro_rules! integer_from_signed {
+    ($($t:ty)*) => ($(
+        impl From<$t> for Integer {
+            #[inline]
+            fn from(n: $t) -> Self {
+                let sign = if n < 0 { Sign::Negative } else { Sign::Positive };
+                Integer::from_sign_and_size(sign, Natural::from(n.unsigned_abs() as u128))
+            }
+        }
+    )*)
+}
+
+integer_from_signed! { i8 i16 i32 i64 i128 isize }
+
+impl TryFrom<Integer> for Natural {
+    type Error = TryFromIntError;
+
+    #[inline]
+    fn try_from(z: Integer) -> Result<Self, Self::Error> {
+        match z.sign {
+            Sign::Positive => Ok(z.size),
+            Sign::Negative => Err(TryFromIntError),
+        }
+    }
+}
+
+impl TryFrom<&Integer> for Natural {
+    type Error = TryFromIntError;
+
+    #[inline]
+    fn try_from(z: &Integer) -> Result<Self, Self::Error> {
+        match z.sign {
+            Sign::Positive => Ok(z.size.clone()),
+            Sign::Negative => Err(TryFromIntError),
+        }
+    }
+}
+
+macro_rules! primitive_try_from_natural {
+    ($($t:ty)*) => ($(
+        impl TryFrom<&Natural> for $t {
+            type Error = TryFromIntError;
+
+            #[inline]
+            fn try_from(n: &Natural) -> Result<$t, Self::Error> {
+                n.to_u128_checked()
+                    .and_then(|v| <$t>::try_from(v).ok())
+                    .ok_or(TryFromIntError)
+            }
+        }
+
+        impl TryFrom<Natural> for $t {
+            type Error = TryFromIntError;
+
+            #[inline]
+            fn try_from(n: Natural) -> Result<$t, Self::Error> {
+                <$t>::try_from(&n)
+            }
+        }
+    )*)
+}
+
+primitive_try_from_natural! { u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize }
+
+macro_rules! unsigned_try_from_integer {
+    ($($t:ty)*) => ($(
+        impl TryFrom<&Integer> for $t {
+            type Error = TryFromIntError;
+
+            #[inline]
+            fn try_from(z: &Integer) -> Result<$t, Self::Error> {
+                match z.sign {
+                    Sign::Positive => <$t>::try_from(&z.size),
+                    Sign::Negative => Err(TryFromIntError),
+                }
+            }
+        }
+
+        impl TryFrom<Integer> for $t {
+            type Error = TryFromIntError;
+
+            #[inline]
+            fn try_from(z: Integer) -> Result<$t, Self::Error> {
+                <$t>::try_from(&z)
+            }
+        }
+    )*)
+}
+
+unsigned_try_from_integer! { u8 u16 u32 u64 u128 usize }
+
+macro_rules! signed_try_from_integer {
+    ($($t:ty)*) => ($(
+        impl TryFrom<&Integer> for $t {
+            type Error = TryFromIntError;
+
+            #[inline]
+            fn try_from(z: &Integer) -> Result<$t, Self::Error> {
+                match z.sign {
+                    Sign::Positive => <$t>::try_from(&z.size),
+                    Sign::Negative => {
+                        // The most negative value has a magnitude one larger than the maximum
+                        let limit = (<$t>::MAX as u128) + 1;
+                        match z.size.to_u128_checked() {
+                            Some(m) if m <= limit => Ok(m.wrapping_neg() as $t),
+                            _ => Err(TryFromIntError),
+                        }
+                    },
+                }
+            }
+        }
+
+        impl TryFrom<Integer> for $t {
+            type Error = TryFromIntError;
+
+            #[inline]
+            fn try_from(z: Integer) -> Result<$t, Self::Error> {
+                <$t>::try_from(&z)
+            }
+        }
+    )*)
+}
+
+signed_try_from_integer! { i8 i16 i32 i64 i128 isize }
+
+macro_rules! wrapping_accessors {
+    ($($name:ident -> $t:ty),*) => (
+        impl Natural {
+            $(
+            #[doc = concat!("Returns the number truncated to a `", stringify!($t), "`, as if by an `as` cast")]
+            #[inline]
+            pub fn $name(&self) -> $t {
+                self.low_u128() as $t
+            }
+            )*
+        }
+
+        impl Integer {
+            $(
+            #[doc = concat!("Returns the number truncated to a `", stringify!($t), "` using two's complement, as if by an `as` cast")]
+            #[inline]
+            pub fn $name(&self) -> $t {
+                self.low_u128() as $t
+            }
+            )*
+        }
+    )
+}
+
+wrapping_accessors! {
+    to_u8_wrapping -> u8,
+    to_u16_wrapping -> u16,
+    to_u32_wrapping -> u32,
+    to_u64_wrapping -> u64,
+    to_u128_wrapping -> u128,
+    to_usize_wrapping -> usize,
+    to_i8_wrapping -> i8,
+    to_i16_wrapping -> i16,
+    to_i32_wrapping -> i32,
+    to_i64_wrapping -> i64,
+    to_i128_wrapping -> i128,
+    to_isize_wrapping -> isize
+}
+
+/// Describes the layout of an IEEE 754 binary floating-point type
+struct FloatFormat {
+    /// Number of mantissa bits, including the implicit leading bit
+    precision: u32,
+    /// Largest unbiased exponent of a finite value
+    max_exponent: u64,
+}
+
+const F64_FORMAT: FloatFormat = FloatFormat { precision: 53, max_exponent: 1023 };
+const F32_FORMAT: FloatFormat = FloatFormat { precision: 24, max_exponent: 127 };
+
+impl Natural {
+    /// Returns the 64 most significant bits of the number, left-aligned, along with
+    /// a flag indicating whether any of the remaining lower bits are set
+    fn leading_bits(&self) -> (u64, bool) {
+        let bits = self.bit_length();
+        let n = self.digits.len();
+        let shift = self.digits[n-1].leading_zeros();
+        let high = self.digits[n-1] as u128;
+        let low = if n >= 2 { self.digits[n-2] as u128 } else { 0 };
+        let window = ((high << 64) | low) << shift;
+        let top = (window >> 64) as u64;
+        let mut sticky = window as u64 != 0;
+        if bits > 128 {
+            sticky |= self.digits[..n-2].iter().any(|&d| d != 0);
+        }
+        (top, sticky)
+    }
+
+    /// Rounds the number to the given precision, returning the mantissa and the binary
+    /// exponent e such that the rounded value is mantissa * 2^(e - precision + 1), with
+    /// the mantissa having exactly `precision` bits
+    fn round_to_precision(&self, precision: u32) -> (u64, u64) {
+        let (top, sticky) = self.leading_bits();
+        let mut exponent = self.bit_length() - 1;
+        let dropped = 64 - precision;
+        let mut mantissa = top >> dropped;
+        let round_bits = top & ((1 << dropped) - 1);
+        let half = 1 << (dropped - 1);
+        if round_bits > half || (round_bits == half && (sticky || mantissa & 1 == 1)) {
+            mantissa += 1;
+            if mantissa == 1 << precision {
+                mantissa >>= 1;
+                exponent += 1;
+            }
+        }
+        (mantissa, exponent)
+    }
+
+    /// Rounds to the nearest float with the given format, returning the raw bits of the
+    /// exponent and fraction fields
+    fn to_float_bits(&self, format: &FloatFormat) -> u64 {
+        if self.is_zero() {
+            return 0;
+        }
+        let (mantissa, exponent) = self.round_to_precision(format.precision);
+        let fraction_bits = format.precision - 1;
+        if exponent > format.max_exponent {
+            // Infinity has a saturated exponent field and a zero fraction
+            (2 * format.max_exponent + 1) << fraction_bits
+        }
+        else {
+            let biased = exponent + format.max_exponent;
+            (biased << fraction_bits) | (mantissa & ((1 << fraction_bits) - 1))
+        }
+    }
+
+    ///
+    /// Returns the nearest f64 to this number, rounding ties to even
+    /// 
+    /// Numbers too large to be represented return infinity.
+    /// 
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    /// 
+    /// assert_eq!(Natural::from(12345u64).to_f64(), 12345.0);
+    /// assert_eq!(Natural::from((1u64 << 53) + 1).to_f64(), 9007199254740992.0);
+    /// ```
+    pub fn to_f64(&self) -> f64 {
+        f64::from_bits(self.to_float_bits(&F64_FORMAT))
+    }
+
+    ///
+    /// Returns the nearest f32 to this number, rounding ties to even
+    /// 
+    /// Numbers too large to be represented return infinity.
+    pub fn to_f32(&self) -> f32 {
+        f32::from_bits(self.to_float_bits(&F32_FORMAT) as u32)
+    }
+
+    ///
+    /// Returns a mantissa m and exponent e such that this number is approximately m * 2^e
+    /// 
+    /// The mantissa is rounded to nearest, ties to even, and lies in the range [0.5, 1),
+    /// so that e is the bit length of the number. This works for numbers far outside the
+    /// range of an f64. Zero returns (0.0, 0).
+    /// 
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    /// 
+    /// let n = Natural::from(vec!(0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3));
+    /// assert_eq!(n.to_f64_with_exponent(), (0.75, 1026));
+    /// ```
+    pub fn to_f64_with_exponent(&self) -> (f64, u64) {
+        if self.is_zero() {
+            return (0.0, 0);
+        }
+        let (mantissa, exponent) = self.round_to_precision(F64_FORMAT.precision);
+        let m = mantissa as f64 / (1u64 << F64_FORMAT.precision) as f64;
+        (m, exponent + 1)
+    }
+
+    /// Builds a number from the raw parts of a non-negative float
+    fn try_from_float_parts(bits: u64, format: &FloatFormat) -> Result<Self, TryFromFloatError> {
+        let fraction_bits = format.precision - 1;
+        let biased = bits >> fraction_bits;
+        let fraction = bits & ((1 << fraction_bits) - 1);
+        if biased == 2 * format.max_exponent + 1 {
+            // Infinity or NaN
+            return Err(TryFromFloatError);
+        }
+        if biased == 0 {
+            // Zero or a subnormal, of which only zero is an integer
+            return if fraction == 0 { Ok(Natural::ZERO) } else { Err(TryFromFloatError) };
+        }
+        let mantissa = fraction | (1 << fraction_bits);
+        let exponent = biased as i64 - format.max_exponent as i64 - fraction_bits as i64;
+        if exponent >= 0 {
+            Ok(Natural::from(shl_slice(&[mantissa], exponent as usize)))
+        }
+        else if -exponent > fraction_bits as i64 || mantissa & ((1 << -exponent) - 1) != 0 {
+            Err(TryFromFloatError)
+        }
+        else {
+            Ok(Natural::from(mantissa >> -exponent))
+        }
+    }
+}
+
+impl Integer {
+    ///
+    /// Returns the nearest f64 to this number, rounding ties to even
+    /// 
+    /// Numbers too large in magnitude to be represented return an infinity.
+    pub fn to_f64(&self) -> f64 {
+        match self.sign {
+            Sign::Positive => self.size.to_f64(),
+            Sign::Negative => -self.size.to_f64(),
+        }
+    }
+
+    ///
+    /// Returns the nearest f32 to this number, rounding ties to even
+    /// 
+    /// Numbers too large in magnitude to be represented return an infinity.
+    pub fn to_f32(&self) -> f32 {
+        match self.sign {
+            Sign::Positive => self.size.to_f32(),
+            Sign::Negative => -self.size.to_f32(),
+        }
+    }
+
+    ///
+    /// Returns a mantissa m and exponent e such that this number is approximately m * 2^e
+    /// 
+    /// The magnitude of the mantissa lies in the range [0.5, 1) and it carries the sign
+    /// of the number. See `Natural::to_f64_with_exponent`.
+    pub fn to_f64_with_exponent(&self) -> (f64, u64) {
+        let (m, e) = self.size.to_f64_with_exponent();
+        match self.sign {
+            Sign::Positive => (m, e),
+            Sign::Negative => (-m, e),
+        }
+    }
+}
+
+macro_rules! try_from_float {
+    ($t:ty, $format:expr) => (
+        impl TryFrom<$t> for Natural {
+            type Error = TryFromFloatError;
+
+            fn try_from(x: $t) -> Result<Self, Self::Error> {
+                if x.is_sign_negative() && x != 0.0 {
+                    Err(TryFromFloatError)
+                }
+                else {
+                    Natural::try_from_float_parts(x.abs().to_bits() as u64, &$format)
+                }
+            }
+        }
+
+        impl TryFrom<$t> for Integer {
+            type Error = TryFromFloatError;
+
+            fn try_from(x: $t) -> Result<Self, Self::Error> {
+                let size = Natural::try_from_float_parts(x.abs().to_bits() as u64, &$format)?;
+                let sign = if x < 0.0 { Sign::Negative } else { Sign::Positive };
+                Ok(Integer::from_sign_and_size(sign, size))
+            }
+        }
+    )
+}
+
+try_from_float!(f64, F64_FORMAT);
+try_from_float!(f32, F32_FORMAT);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NINE: u64 = u64::MAX;
+
+    #[test]
+    fn test_natural_from_unsigned() {
+        assert_eq!(Natural::from(7u8), Natural::from(vec!(7)));
+        assert_eq!(Natural::from(0u32), Natural::ZERO);
+        assert_eq!(Natural::from(u128::MAX), Natural::from(vec!(NINE, NINE)));
+        assert_eq!(Natural::from(1u128 << 64), Natural::from(vec!(0, 1)));
+        assert_eq!(Natural::from(usize::MAX), Natural::from(vec!(usize::MAX as u64)));
+    }
+
+    #[test]
+    fn test_natural_try_from_signed() {
+        assert_eq!(Natural::try_from(-1i32), Err(TryFromIntError));
+        assert_eq!(Natural::try_from(5i8), Ok(Natural::from(5u64)));
+        assert_eq!(Natural::try_from(i128::MAX), Ok(Natural::from(vec!(NINE, NINE >> 1))));
+    }
+
+    #[test]
+    fn test_primitive_try_from_natural() {
+        let n = Natural::from(vec!(1 << 32));
+        assert_eq!(u32::try_from(&n), Err(TryFromIntError));
+        assert_eq!(u64::try_from(&n), Ok(1 << 32));
+        assert_eq!(i64::try_from(Natural::from(NINE)), Err(TryFromIntError));
+        assert_eq!(u128::try_from(Natural::from(vec!(1, 2))), Ok((2 << 64) | 1));
+        assert_eq!(u128::try_from(Natural::from(vec!(1, 2, 3))), Err(TryFromIntError));
+        assert_eq!(u8::try_from(Natural::ZERO), Ok(0));
+    }
+
+    #[test]
+    fn test_integer_round_trips() {
+        for &v in &[0i64, 1, -1, i64::MAX, i64::MIN, -123456789] {
+            assert_eq!(i64::try_from(Integer::from(v)), Ok(v));
+        }
+        for &v in &[0i128, i128::MAX, i128::MIN, -(1 << 64)] {
+            assert_eq!(i128::try_from(Integer::from(v)), Ok(v));
+        }
+        assert_eq!(i8::try_from(Integer::from(-129)), Err(TryFromIntError));
+        assert_eq!(i8::try_from(Integer::from(-128)), Ok(-128));
+        assert_eq!(i8::try_from(Integer::from(128)), Err(TryFromIntError));
+        assert_eq!(u64::try_from(Integer::from(-1)), Err(TryFromIntError));
+        assert_eq!(Natural::try_from(Integer::from(-1)), Err(TryFromIntError));
+        assert_eq!(Natural::try_from(Integer::from(3)), Ok(Natural::from(3u64)));
+    }
+
+    #[test]
+    fn test_wrapping_accessors() {
+        let n = Natural::from(vec!(0x1_0000_0102, 5, 6));
+        assert_eq!(n.to_u8_wrapping(), 2);
+        assert_eq!(n.to_u32_wrapping(), 0x102);
+        assert_eq!(n.to_u64_wrapping(), 0x1_0000_0102);
+        assert_eq!(n.to_u128_wrapping(), (5 << 64) | 0x1_0000_0102);
+        assert_eq!(Natural::from(255u64).to_i8_wrapping(), -1);
+
+        let z = Integer::from(-2);
+        assert_eq!(z.to_u8_wrapping(), 254);
+        assert_eq!(z.to_i64_wrapping(), -2);
+        assert_eq!(z.to_u64_wrapping(), NINE - 1);
+        assert_eq!(Integer::from(i128::MIN).to_i128_wrapping(), i128::MIN);
+    }
+
+    #[test]
+    fn test_to_f64_exact_and_rounded() {
+        assert_eq!(Natural::ZERO.to_f64(), 0.0);
+        assert_eq!(Natural::from(NINE).to_f64(), 18446744073709551616.0);
+        assert_eq!(Natural::from(vec!(0, 1)).to_f64(), 18446744073709551616.0);
+        // Ties round to even
+        assert_eq!(Natural::from((1u64 << 53) + 1).to_f64(), (1u64 << 53) as f64);
+        assert_eq!(Natural::from((1u64 << 53) + 3).to_f64(), ((1u64 << 53) + 4) as f64);
+        // Anything below the tie rounds down, but a sticky bit far below pushes a tie up
+        let tie = Natural::from(vec!(0, 0, (1 << 53) + 1));
+        let above_tie = Natural::from(vec!(1, 0, (1 << 53) + 1));
+        assert_eq!(tie.to_f64(), ((1u64 << 53) as f64) * 2f64.powi(128));
+        assert_eq!(above_tie.to_f64(), (((1u64 << 53) + 2) as f64) * 2f64.powi(128));
+    }
+
+    #[test]
+    fn test_to_f64_overflow() {
+        let max = Natural::try_from(f64::MAX).unwrap();
+        assert_eq!(max.to_f64(), f64::MAX);
+        let mut big = vec!(0; 16);
+        big.push(1);
+        assert_eq!(Natural::from(big).to_f64(), f64::INFINITY);
+        assert_eq!(Integer::from(-1).to_f64(), -1.0);
+        assert_eq!(Natural::from(u128::MAX).to_f32(), f32::INFINITY);
+        assert_eq!(Natural::from(16777217u64).to_f32(), 16777216.0);
+    }
+
+    #[test]
+    fn test_to_f64_with_exponent() {
+        assert_eq!(Natural::from(1u64).to_f64_with_exponent(), (0.5, 1));
+        assert_eq!(Natural::from(NINE).to_f64_with_exponent(), (0.5, 65));
+        assert_eq!(Integer::from(-12).to_f64_with_exponent(), (-0.75, 4));
+    }
+
+    #[test]
+    fn test_try_from_float() {
+        assert_eq!(Natural::try_from(0.0f64), Ok(Natural::ZERO));
+        assert_eq!(Natural::try_from(-0.0f64), Ok(Natural::ZERO));
+        assert_eq!(Natural::try_from(2f64.powi(64)), Ok(Natural::from(vec!(0, 1))));
+        assert_eq!(Natural::try_from(f64::NAN), Err(TryFromFloatError));
+        assert_eq!(Natural::try_from(f64::INFINITY), Err(TryFromFloatError));
+        assert_eq!(Natural::try_from(0.5f64), Err(TryFromFloatError));
+        assert_eq!(Natural::try_from(f64::MIN_POSITIVE), Err(TryFromFloatError));
+        assert_eq!(Integer::try_from(-3.0f32), Ok(Integer::from(-3)));
+        assert_eq!(Integer::try_from(-3.5f32), Err(TryFromFloatError));
+        assert_eq!(Integer::try_from(-0.0f64), Ok(Integer::from(0)));
+    }
+
+    #[test]
+    fn test_float_round_trip() {
+        for &x in &[1.0f64, 3.0, 1e15, 1e300, 2f64.powi(100) * 3.0, f64::MAX] {
+            assert_eq!(Natural::try_from(x).unwrap().to_f64(), x);
+        }
+    }
+}
@@ -0,0 +1,343 @@
+// Lenstra's elliptic curve method of factorisation
+//
+// Each curve is a Montgomery curve By^2 = x^3 + Ax^2 + x modulo n, chosen with
+// Suyama's parametrisation so that its group order modulo any prime is divisible by 12.
+// Only the x coordinate is tracked, projectively as (X : Z), so no inverses are needed.
+// A factor p of n is found when the order of the starting point modulo p divides the
+// scalar it has been multiplied by, since then Z is 0 modulo p.
+//
+// Stage 1 multiplies the point by every prime power up to B1 with the Montgomery
+// ladder. Stage 2 then catches a single further prime q in (B1, B2]. Writing
+// q = mD +- j for a fixed D, with j < D/2 coprime to D, qQ = 0 exactly when the x
+// coordinates of mDQ and jQ agree. The baby steps jQ are computed once, the giant
+// steps mDQ one after another, and X_m Z_j - X_j Z_m is accumulated over all q in a
+// product whose gcd with n is taken at the end.
+
+use core::convert::TryFrom;
+
+use crate::natural::Natural;
+use crate::montgomery::MontgomeryContext;
+use crate::sieve::SegmentedSieve;
+
+///
+/// The parameters of a run of the elliptic curve method
+///
+/// The bounds suited to factors of a given size are given by `for_digits`.
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, EcmParameters};
+///
+/// // 2^64 + 1 = 274177 * 67280421310721
+/// let n = &Natural::from(vec!(0, 1)) + &Natural::from(1u64);
+/// let parameters = EcmParameters { b1: 1000, b2: 50_000, curves: 20, sigma: 6 };
+/// let factor = n.ecm(&parameters).unwrap();
+/// assert!(factor == Natural::from(274177u64) || factor == Natural::from(67280421310721u64));
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct EcmParameters {
+    /// The bound on the prime powers in stage 1
+    pub b1: u64,
+    /// The bound on the prime in stage 2, which is skipped if this is at most b1
+    pub b2: u64,
+    /// The number of curves to try
+    pub curves: u32,
+    /// The parameter of the first curve, from which the curve and its starting point
+    /// are derived. The following curves take sigma + 1, sigma + 2 and so on.
+    pub sigma: u64,
+}
+
+impl EcmParameters {
+    ///
+    /// Returns parameters expected to find a factor of up to the given number of
+    /// decimal digits, if there is one, with B2 = 100 B1
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::EcmParameters;
+    ///
+    /// assert_eq!(EcmParameters::for_digits(20).b1, 11_000);
+    /// ```
+    pub fn for_digits(digits: u32) -> Self {
+        // The usual bounds from the GMP-ECM documentation
+        const TABLE: [(u32, u64, u32); 8] = [
+            (15, 2_000, 25),
+            (20, 11_000, 90),
+            (25, 50_000, 300),
+            (30, 250_000, 700),
+            (35, 1_000_000, 1_800),
+            (40, 3_000_000, 5_100),
+            (45, 11_000_000, 10_600),
+            (50, 43_000_000, 19_300),
+        ];
+        let &(_, b1, curves) = TABLE.iter().find(|&&(d, _, _)| digits <= d).unwrap_or(&TABLE[7]);
+        Self {
+            b1,
+            b2: 100 * b1,
+            curves,
+            sigma: 6,
+        }
+    }
+}
+
+impl Default for EcmParameters {
+    fn default() -> Self {
+        Self::for_digits(20)
+    }
+}
+
+/// A point given by the projective x coordinate (X : Z)
+#[derive(Debug,Clone)]
+struct Point {
+    x: Natural,
+    z: Natural,
+}
+
+/// A Montgomery curve modulo n, with values in Montgomery form
+struct Curve<'a> {
+    context: &'a MontgomeryContext,
+    /// (A + 2) / 4
+    a24: Natural,
+}
+
+/// The outcome of setting up a curve
+enum Setup<'a> {
+    Curve(Curve<'a>, Point),
+    /// A factor turned up while inverting the denominator of (A + 2) / 4
+    Factor(Natural),
+    /// The parameter gives a degenerate curve
+    Degenerate,
+}
+
+impl<'a> Curve<'a> {
+    /// Sets up the curve with Suyama's parameter sigma, which has u = sigma^2 - 5 and
+    /// v = 4 sigma, starting point (u^3 : v^3) and (A + 2) / 4 = (v - u)^3 (3u + v) / 16u^3v
+    fn suyama(context: &'a MontgomeryContext, sigma: u64) -> Setup<'a> {
+        let n = context.modulus();
+        let sigma = &Natural::from(sigma) % n;
+        let u = sub_mod(&(&(&sigma * &sigma) % n), &(&Natural::from(5u64) % n), n);
+        let v = &(&sigma * &Natural::from(4u64)) % n;
+        let u3 = &u.pow(3) % n;
+        let v3 = &v.pow(3) % n;
+        let numerator = &(&sub_mod(&v, &u, n).pow(3) * &(&(&u * &Natural::from(3u64)) + &v)) % n;
+        let denominator = &(&(&u3 * &v) * &Natural::from(16u64)) % n;
+        let inverse = match denominator.inv_mod(n) {
+            Some(inverse) => inverse,
+            None => {
+                let g = denominator.gcd(n);
+                return if g == *n { Setup::Degenerate } else { Setup::Factor(g) };
+            }
+        };
+        let a24 = &(&numerator * &inverse) % n;
+        let curve = Curve {
+            context,
+            a24: context.to_montgomery(&a24),
+        };
+        let point = Point {
+            x: context.to_montgomery(&u3),
+            z: context.to_montgomery(&v3),
+        };
+        Setup::Curve(curve, point)
+    }
+
+    fn add(&self, a: &Natural, b: &Natural) -> Natural {
+        let s = a + b;
+        if s >= *self.context.modulus() { &s - self.context.modulus() } else { s }
+    }
+
+    fn sub(&self, a: &Natural, b: &Natural) -> Natural {
+        sub_mod(a, b, self.context.modulus())
+    }
+
+    fn mul(&self, a: &Natural, b: &Natural) -> Natural {
+        self.context.mul(a, b)
+    }
+
+    /// Returns 2P
+    fn double(&self, p: &Point) -> Point {
+        let sum = self.context.square(&self.add(&p.x, &p.z));
+        let difference = self.context.square(&self.sub(&p.x, &p.z));
+        let t = self.sub(&sum, &difference);
+        Point {
+            x: self.mul(&sum, &difference),
+            z: self.mul(&t, &self.add(&difference, &self.mul(&self.a24, &t))),
+        }
+    }
+
+    /// Returns P + Q, given P - Q
+    fn add_points(&self, p: &Point, q: &Point, difference: &Point) -> Point {
+        let u = self.mul(&self.sub(&p.x, &p.z), &self.add(&q.x, &q.z));
+        let v = self.mul(&self.add(&p.x, &p.z), &self.sub(&q.x, &q.z));
+        Point {
+            x: self.mul(&difference.z, &self.context.square(&self.add(&u, &v))),
+            z: self.mul(&difference.x, &self.context.square(&self.sub(&u, &v))),
+        }
+    }
+
+    /// Returns kP for k at least 1, with the Montgomery ladder
+    fn multiply(&self, p: &Point, k: u64) -> Point {
+        // Invariant: r1 = r0 + P
+        let mut r0 = p.clone();
+        let mut r1 = self.double(p);
+        for i in (0..63 - k.leading_zeros()).rev() {
+            if (k >> i) & 1 == 1 {
+                r0 = self.add_points(&r1, &r0, p);
+                r1 = self.double(&r1);
+            }
+            else {
+                r1 = self.add_points(&r1, &r0, p);
+                r0 = self.double(&r0);
+            }
+        }
+        r0
+    }
+
+    /// Multiplies the point by every prime power up to b1
+    fn stage_1(&self, mut p: Point, b1: u64) -> Point {
+        for q in SegmentedSieve::new(&Natural::from(2u64), &Natural::from(b1 + 1)) {
+            let q = u64::try_from(&q).unwrap();
+            let mut power = q;
+            while power <= b1 / q {
+                power *= q;
+            }
+            p = self.multiply(&p, power);
+        }
+        p
+    }
+
+    /// Returns the product of X_m Z_j - X_j Z_m over the primes mD +- j in (b1, b2]
+    fn stage_2(&self, q: &Point, b1: u64, b2: u64) -> Natural {
+        // Taking D at most b1 keeps m at least 1 for every prime
+        let d = [2310, 210, 30, 6].iter().copied().find(|&d| d <= b1).unwrap_or(6);
+        let half = (d / 2) as usize;
+        // Baby steps jQ for odd j < D/2
+        let double = self.double(q);
+        let mut babies = vec!(q.clone(), self.add_points(&double, q, q));
+        for j in (5..half).step_by(2) {
+            let i = j / 2;
+            let next = self.add_points(&babies[i - 1], &double, &babies[i - 2]);
+            babies.push(next);
+        }
+        let step = self.multiply(q, d);
+        let mut product = self.context.one();
+        // The current m with (m - 1)DQ, or None for m = 1, and mDQ
+        let mut giant: Option<(u64, Option<Point>, Point)> = None;
+        let start = Natural::from(b1.max(d) + 1);
+        for p in SegmentedSieve::new(&start, &Natural::from(b2 + 1)) {
+            let p = u64::try_from(&p).unwrap();
+            let m = (p + d / 2) / d;
+            // Giant steps mDQ, each from the previous two
+            giant = Some(match giant {
+                Some((k, previous, current)) if k == m => (k, previous, current),
+                Some((k, previous, current)) if k + 1 == m => {
+                    let next = match &previous {
+                        Some(previous) => self.add_points(&current, &step, previous),
+                        None => self.double(&current),
+                    };
+                    (m, Some(current), next)
+                }
+                _ if m == 1 => (m, None, step.clone()),
+                _ => (m, Some(self.multiply(q, (m - 1) * d)), self.multiply(q, m * d)),
+            });
+            let r = &giant.as_ref().unwrap().2;
+            let j = p.abs_diff(m * d) as usize;
+            let baby = &babies[j / 2];
+            product = self.mul(&product, &self.sub(&self.mul(&r.x, &baby.z), &self.mul(&baby.x, &r.z)));
+        }
+        product
+    }
+}
+
+fn sub_mod(a: &Natural, b: &Natural, n: &Natural) -> Natural {
+    if a >= b { a - b } else { &(a + n) - b }
+}
+
+/// Looks for a proper factor of an odd n > 1 on each curve in turn
+pub(crate) fn ecm(context: &MontgomeryContext, parameters: &EcmParameters) -> Option<Natural> {
+    let n = context.modulus();
+    let one = Natural::from(1u64);
+    for i in 0..parameters.curves {
+        let (curve, point) = match Curve::suyama(context, parameters.sigma + i as u64) {
+            Setup::Curve(curve, point) => (curve, point),
+            Setup::Factor(g) => return Some(g),
+            Setup::Degenerate => continue,
+        };
+        let q = curve.stage_1(point, parameters.b1);
+        let g = q.z.gcd(n);
+        if g == *n {
+            // Every factor was found at once, so this curve is no use
+            continue;
+        }
+        if g != one {
+            return Some(g);
+        }
+        if parameters.b2 > parameters.b1 {
+            let g = curve.stage_2(&q, parameters.b1, parameters.b2).gcd(n);
+            if g != one && g != *n {
+                return Some(g);
+            }
+        }
+    }
+    None
+}
+
+impl Natural {
+    ///
+    /// Looks for a proper factor of this number with the elliptic curve method,
+    /// returning None if no curve finds one
+    ///
+    /// The factor found is not necessarily prime, nor the smallest. Numbers with a
+    /// factor of 2 give 2 immediately, and primes, 0 and 1 give None.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::{Natural, EcmParameters};
+    ///
+    /// let n = &Natural::from(1_000_003u64) * &Natural::from(u64::MAX - 58);
+    /// assert_eq!(n.ecm(&EcmParameters::for_digits(15)), Some(Natural::from(1_000_003u64)));
+    /// ```
+    pub fn ecm(&self, parameters: &EcmParameters) -> Option<Natural> {
+        if self.digits.first().is_some_and(|d| d & 1 == 0) {
+            return Some(Natural::from(2u64)).filter(|two| self != two);
+        }
+        if *self <= Natural::from(1u64) {
+            return None;
+        }
+        ecm(&MontgomeryContext::new(self), parameters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_2() {
+        // On the curve with sigma = 6 modulo p, the starting point has an order which is
+        // 2000-smooth apart from one prime below 100000
+        let p = Natural::from(2_636_631_855_999_262_956_979u128);
+        let n = &p * &Natural::from(u128::MAX >> 1);
+        let mut parameters = EcmParameters { b1: 2000, b2: 100_000, curves: 1, sigma: 6 };
+        assert_eq!(n.ecm(&parameters), Some(p));
+        parameters.b2 = 0;
+        assert_eq!(n.ecm(&parameters), None);
+    }
+
+    #[test]
+    fn test_small_bounds() {
+        // Stage 2 with D = 30, and a product of two primes near 2^32
+        let n = Natural::from(4_294_967_291u64 * 4_294_967_279);
+        let factor = n.ecm(&EcmParameters { b1: 100, b2: 20_000, curves: 100, sigma: 6 }).unwrap();
+        assert!(factor == Natural::from(4_294_967_291u64) || factor == Natural::from(4_294_967_279u64));
+    }
+
+    #[test]
+    fn test_trivial() {
+        let parameters = EcmParameters::for_digits(15);
+        assert_eq!(Natural::ZERO.ecm(&parameters), None);
+        assert_eq!(Natural::from(1u64).ecm(&parameters), None);
+        assert_eq!(Natural::from(2u64).ecm(&parameters), None);
+        assert_eq!(Natural::from(6u64).ecm(&parameters), Some(Natural::from(2u64)));
+        assert_eq!(Natural::from(101u64).ecm(&parameters), None);
+    }
+}
@@ -0,0 +1,320 @@
+// Factorisation of Natural numbers into primes
+//
+// Factors below 1000 are removed by trial division. What remains is split recursively,
+// stopping at numbers which pass a primality test. Perfect powers are split by taking
+// roots. Other numbers below 2^64 are split with Pollard's rho method in native
+// arithmetic. Larger ones first try Pollard's p-1 method, which quickly finds any
+// factor p for which p-1 has only small prime factors, then a limited run of rho in
+// Montgomery form, which finds factors up to about 10 digits, and then the elliptic
+// curve method with bounds for 15 digit factors. Numbers of up to 80 digits then go
+// to the quadratic sieve, whose running time depends only on the size of the number,
+// while larger ones carry on with ECM for ever larger factors, since the sieve's dense
+// matrix step takes minutes beyond 80 digits. Rho uses Brent's cycle detection,
+// and takes the gcd with n only once per batch of steps, accumulating the differences
+// in a product modulo n.
+
+use core::convert::TryFrom;
+
+use crate::natural::Natural;
+use crate::montgomery::MontgomeryContext;
+use crate::primality::{Primality, SMALL_PRIMES, mul_mod_u64};
+use crate::sieve::SegmentedSieve;
+use crate::ecm::{EcmParameters, ecm};
+use crate::siqs::siqs;
+
+/// The bound on the prime powers in the p-1 method
+const P_MINUS_1_BOUND: u64 = 10_000;
+
+/// The number of rho steps between gcds
+const BATCH: u64 = 128;
+
+/// The number of rho steps, roughly, before giving way to the elliptic curve method
+const RHO_LIMIT: u64 = 1 << 17;
+
+/// The largest number, in bits, handed to the quadratic sieve rather than to further
+/// ECM, which is about 80 digits
+const SIEVE_BITS: u64 = 266;
+
+/// Divides out the primes below 1000, pushing each one found
+fn trial_divide(n: &mut Natural, factors: &mut Vec<Natural>) {
+    for &p in SMALL_PRIMES {
+        if *n < Natural::from(p * p) {
+            break;
+        }
+        while n.rem_digit(p) == 0 {
+            *n = n.div(&Natural::from(p)).0;
+            factors.push(Natural::from(p));
+        }
+    }
+}
+
+fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Looks for a proper factor of an odd composite n with Pollard's rho method, iterating
+/// x -> x^2 + c (mod n)
+fn pollard_brent_u64(n: u64, c: u64) -> Option<u64> {
+    let f = |x: u64| ((mul_mod_u64(x, x, n) as u128 + c as u128) % n as u128) as u64;
+    let (mut x, mut y, mut saved) = (2, 2, 2);
+    let mut q = 1;
+    let mut g = 1;
+    let mut r = 1;
+    while g == 1 {
+        x = y;
+        for _ in 0..r {
+            y = f(y);
+        }
+        let mut k = 0;
+        while k < r && g == 1 {
+            saved = y;
+            for _ in 0..BATCH.min(r - k) {
+                y = f(y);
+                q = mul_mod_u64(q, x.abs_diff(y), n);
+            }
+            g = gcd_u64(q, n);
+            k += BATCH;
+        }
+        r *= 2;
+    }
+    if g == n {
+        // The batch overshot, so retrace it one step at a time
+        loop {
+            saved = f(saved);
+            g = gcd_u64(x.abs_diff(saved), n);
+            if g != 1 {
+                break;
+            }
+        }
+    }
+    Some(g).filter(|&g| g != n)
+}
+
+/// Looks for a proper factor of an odd composite n with Pollard's rho method, as for
+/// `pollard_brent_u64` but on numbers in Montgomery form, and giving up after about
+/// RHO_LIMIT steps
+fn pollard_brent(n: &Natural, context: &MontgomeryContext, c: u64) -> Option<Natural> {
+    let c = context.to_montgomery(&Natural::from(c));
+    let f = |x: &Natural| {
+        let y = &context.square(x) + &c;
+        if y >= *n { &y - n } else { y }
+    };
+    let diff = |x: &Natural, y: &Natural| if x >= y { x - y } else { y - x };
+    let two = context.to_montgomery(&Natural::from(2u64));
+    let (mut x, mut y, mut saved) = (two.clone(), two.clone(), two);
+    let mut q = context.one();
+    let mut g = Natural::from(1u64);
+    let one = Natural::from(1u64);
+    let mut r = 1;
+    while g == one {
+        if r > RHO_LIMIT {
+            return None;
+        }
+        x = y.clone();
+        for _ in 0..r {
+            y = f(&y);
+        }
+        let mut k = 0;
+        while k < r && g == one {
+            saved = y.clone();
+            for _ in 0..BATCH.min(r - k) {
+                y = f(&y);
+                q = context.mul(&q, &diff(&x, &y));
+            }
+            // Montgomery form only scales q by a power of 2, which n does not share
+            g = q.gcd(n);
+            k += BATCH;
+        }
+        r *= 2;
+    }
+    if g == *n {
+        loop {
+            saved = f(&saved);
+            g = diff(&x, &saved).gcd(n);
+            if g != one {
+                break;
+            }
+        }
+    }
+    Some(g).filter(|g| g != n)
+}
+
+/// Looks for a proper factor of an odd n with Pollard's p-1 method, by raising 2 to
+/// every prime power below P_MINUS_1_BOUND
+fn pollard_p_minus_1(n: &Natural, context: &MontgomeryContext) -> Option<Natural> {
+    let mut a = context.to_montgomery(&Natural::from(2u64));
+    for p in SegmentedSieve::new(&Natural::from(2u64), &Natural::from(P_MINUS_1_BOUND)) {
+        let p = u64::try_from(&p).unwrap();
+        let mut power = p;
+        while power <= P_MINUS_1_BOUND / p {
+            power *= p;
+        }
+        a = context.pow(&a, &Natural::from(power));
+    }
+    let g = (&context.from_montgomery(&a) - &Natural::from(1u64)).gcd(n);
+    Some(g).filter(|g| *g != Natural::from(1u64) && g != n)
+}
+
+/// Finds a proper factor of an odd composite n, which is not a perfect power, with the
+/// elliptic curve method, switching to the quadratic sieve after the first run if n is
+/// small enough, and otherwise moving on to the bounds for larger factors, and to fresh
+/// curves, each time a run fails
+fn ecm_escalating(context: &MontgomeryContext) -> Natural {
+    let mut sigma = 6;
+    let mut digits = 15;
+    loop {
+        let parameters = EcmParameters { sigma, ..EcmParameters::for_digits(digits) };
+        if let Some(g) = ecm(context, &parameters) {
+            return g;
+        }
+        if context.modulus().bit_length() <= SIEVE_BITS {
+            return siqs(context.modulus());
+        }
+        sigma += parameters.curves as u64;
+        digits += 5;
+    }
+}
+
+/// Pushes the prime factors of n, which has no prime factors below 1000
+fn split(n: Natural, factors: &mut Vec<Natural>) {
+    if n == Natural::from(1u64) {
+        return;
+    }
+    if n.is_probable_prime(0) != Primality::Composite {
+        factors.push(n);
+        return;
+    }
+    if let Some((root, e)) = n.is_perfect_power() {
+        let mut root_factors = Vec::new();
+        split(root, &mut root_factors);
+        for _ in 0..e {
+            factors.extend(root_factors.iter().cloned());
+        }
+        return;
+    }
+    let d = match u64::try_from(&n) {
+        Ok(m) => Natural::from((1..).find_map(|c| pollard_brent_u64(m, c)).unwrap()),
+        Err(_) => {
+            let context = MontgomeryContext::new(&n);
+            pollard_p_minus_1(&n, &context)
+                .or_else(|| pollard_brent(&n, &context, 1))
+                .unwrap_or_else(|| ecm_escalating(&context))
+        }
+    };
+    let q = n.div(&d).0;
+    split(d, factors);
+    split(q, factors);
+}
+
+impl Natural {
+    ///
+    /// Returns the prime factorisation of this number, as pairs of a prime and its
+    /// exponent in increasing order of the primes
+    ///
+    /// Zero and one have no prime factors and give an empty list. Factors above 2^64
+    /// are reported as prime when they pass the Baillie-PSW test.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// let factors = Natural::from(360u64).factor();
+    /// assert_eq!(factors, vec!((Natural::from(2u64), 3), (Natural::from(3u64), 2), (Natural::from(5u64), 1)));
+    /// assert_eq!(Natural::from(1u64).factor(), vec!());
+    /// ```
+    pub fn factor(&self) -> Vec<(Natural, u64)> {
+        if self.is_zero() {
+            return Vec::new();
+        }
+        let mut n = self.clone();
+        let mut primes = Vec::new();
+        trial_divide(&mut n, &mut primes);
+        split(n, &mut primes);
+        primes.sort();
+        let mut factors: Vec<(Natural, u64)> = Vec::new();
+        for p in primes {
+            match factors.last_mut() {
+                Some((q, e)) if *q == p => *e += 1,
+                _ => factors.push((p, 1)),
+            }
+        }
+        factors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(factors: &[(Natural, u64)]) -> Natural {
+        factors.iter().fold(Natural::from(1u64), |acc, (p, e)| &acc * &p.pow(*e))
+    }
+
+    fn check(n: &Natural) {
+        let factors = n.factor();
+        assert_eq!(product(&factors), *n);
+        assert!(factors.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(factors.iter().all(|(p, _)| p.is_probable_prime(0) != Primality::Composite));
+    }
+
+    #[test]
+    fn test_small() {
+        assert_eq!(Natural::ZERO.factor(), vec!());
+        for n in 1u64..5000 {
+            check(&Natural::from(n));
+        }
+        assert_eq!(Natural::from(1024u64).factor(), vec!((Natural::from(2u64), 10)));
+    }
+
+    #[test]
+    fn test_u64() {
+        // A semiprime with two 32-bit factors, a prime square and a prime
+        let (p, q) = (4_294_967_291u64, 4_294_967_279u64);
+        assert_eq!(Natural::from(p * q).factor(), vec!((Natural::from(q), 1), (Natural::from(p), 1)));
+        assert_eq!(Natural::from(p as u128 * p as u128).factor(), vec!((Natural::from(p), 2)));
+        assert_eq!(Natural::from(u64::MAX - 58).factor(), vec!((Natural::from(u64::MAX - 58), 1)));
+        for n in [u64::MAX, 600_851_475_143, 1_000_000_016_000_000_063, 999_999_999_999_999_989] {
+            check(&Natural::from(n));
+        }
+    }
+
+    #[test]
+    fn test_large() {
+        // A 128-bit prime p with p - 1 smooth, found by p-1, times a 90-bit prime
+        let p: Natural = "240390913140459677349243227848453663321".parse().unwrap();
+        let q: Natural = "618970019642690137449562141".parse().unwrap();
+        assert_eq!((&p * &q).factor(), vec!((q, 1), (p, 1)));
+        // F7 = 2^128 + 1, whose smaller factor is beyond rho and p-1 and needs ECM
+        let f7 = &Natural::from(vec!(0, 0, 1)) + &Natural::from(1u64);
+        assert_eq!(f7.factor(), vec!((Natural::from(59_649_589_127_497_217u64), 1),
+                                     (Natural::from(5_704_689_200_685_129_054_721u128), 1)));
+        // 2^64 - 59 is prime, so this is the cube of a large prime
+        let p = Natural::from(u64::MAX - 58);
+        assert_eq!(p.pow(3).factor(), vec!((p, 3)));
+        // 100! has 25 distinct prime factors
+        let factorial = (1..=100u64).fold(Natural::from(1u64), |acc, k| &acc * &Natural::from(k));
+        let factors = factorial.factor();
+        assert_eq!(factors.len(), 25);
+        assert_eq!(factors[0], (Natural::from(2u64), 97));
+        // Two 40-bit primes and a 64-bit prime, too large for the native path
+        let n = &(&Natural::from(1_099_511_627_791u64) * &Natural::from(1_099_511_628_401u64)) * &Natural::from(u64::MAX - 58);
+        check(&n);
+        assert_eq!(n.factor().len(), 3);
+        // Two 20 digit primes, which the first round of ECM leaves to the quadratic sieve
+        let (p, q) = (Natural::from(10_000_000_000_000_000_051u64), Natural::from(10_000_000_000_000_000_087u64));
+        assert_eq!((&p * &q).factor(), vec!((p, 1), (q, 1)));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_beyond_the_sieve() {
+        // 85 digits, too large for the quadratic sieve, with a 20 digit factor for ECM
+        let p: Natural = "30000000000000000041".parse().unwrap();
+        let q: Natural = "70000000000000000000000000000000000000000000000000000000000000079".parse().unwrap();
+        assert!((&p * &q).bit_length() > SIEVE_BITS);
+        assert_eq!((&p * &q).factor(), vec!((p, 1), (q, 1)));
+    }
+}
@@ -0,0 +1,117 @@
+// Human-readable formatting of large numbers with grouped digits
+
+use core::fmt;
+
+use crate::natural::Natural;
+use crate::integer::{Integer, Sign};
+use crate::radix::to_radix_digits;
+
+///
+/// Displays a number in decimal with its digits split into groups by a separator
+///
+/// This is created by `Natural::grouped` or `Integer::grouped`, and honours the
+/// width, fill, alignment and sign flags of the formatter.
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, Integer};
+///
+/// let n = Natural::from(1234567u64);
+/// assert_eq!(n.grouped(',', 3).to_string(), "1,234,567");
+/// assert_eq!(format!("{:>12}", Integer::from(-1234).grouped('_', 2)), "      -12_34");
+/// ```
+pub struct Grouped<'a> {
+    size: &'a Natural,
+    is_nonnegative: bool,
+    separator: char,
+    group_size: usize,
+}
+
+impl<'a> Grouped<'a> {
+    fn new(size: &'a Natural, is_nonnegative: bool, separator: char, group_size: usize) -> Self {
+        assert!(group_size > 0, "Digit groups must contain at least one digit");
+        Self {
+            size,
+            is_nonnegative,
+            separator,
+            group_size,
+        }
+    }
+}
+
+impl fmt::Display for Grouped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = to_radix_digits(self.size, 10, false);
+        let mut s = String::with_capacity(digits.len() + digits.len() / self.group_size);
+        for (i, &digit) in digits.iter().enumerate() {
+            // Groups are counted from the least significant digit
+            if i > 0 && (digits.len() - i).is_multiple_of(self.group_size) {
+                s.push(self.separator);
+            }
+            s.push(digit as char);
+        }
+        f.pad_integral(self.is_nonnegative, "", &s)
+    }
+}
+
+impl Natural {
+    ///
+    /// Returns a value which displays this number in decimal with the digits
+    /// split into groups of `group_size`, counted from the right, by `separator`
+    ///
+    /// # Panics
+    /// Panics if `group_size` is zero
+    pub fn grouped(&self, separator: char, group_size: usize) -> Grouped<'_> {
+        Grouped::new(self, true, separator, group_size)
+    }
+}
+
+impl Integer {
+    ///
+    /// Returns a value which displays this number in decimal with the digits
+    /// split into groups of `group_size`, counted from the right, by `separator`
+    ///
+    /// # Panics
+    /// Panics if `group_size` is zero
+    pub fn grouped(&self, separator: char, group_size: usize) -> Grouped<'_> {
+        Grouped::new(&self.size, self.sign == Sign::Positive, separator, group_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_flags() {
+        let n = Natural::from(12345u64);
+        assert_eq!(format!("{:>8}", n), "   12345");
+        assert_eq!(format!("{:<8}|", n), "12345   |");
+        assert_eq!(format!("{:*^9}", n), "**12345**");
+        assert_eq!(format!("{:+}", n), "+12345");
+        assert_eq!(format!("{:08}", n), "00012345");
+
+        let z = Integer::from(-12345);
+        assert_eq!(format!("{:08}", z), "-0012345");
+        assert_eq!(format!("{:+}", Integer::from(5)), "+5");
+        assert_eq!(format!("{:+}", Integer::from(0)), "+0");
+        assert_eq!(format!("{:>7}", z), " -12345");
+        assert_eq!(format!("{:x>3}", Natural::ZERO), "xx0");
+    }
+
+    #[test]
+    fn test_grouped() {
+        assert_eq!(Natural::ZERO.grouped(',', 3).to_string(), "0");
+        assert_eq!(Natural::from(123u64).grouped(',', 3).to_string(), "123");
+        assert_eq!(Natural::from(1234u64).grouped(',', 3).to_string(), "1,234");
+        assert_eq!(Natural::from(vec!(0, 1)).grouped(' ', 4).to_string(), "1844 6744 0737 0955 1616");
+        assert_eq!(Integer::from(-123456).grouped(',', 3).to_string(), "-123,456");
+        assert_eq!(format!("{:+}", Natural::from(1000u64).grouped('.', 3)), "+1.000");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_grouped_empty_group() {
+        Natural::from(1u64).grouped(',', 0);
+    }
+}
@@ -0,0 +1,101 @@
+// Greatest common divisors and least common multiples of Natural and Integer
+
+use crate::natural::Natural;
+use crate::integer::Integer;
+use crate::algorithms::{gcd, extended_gcd};
+
+impl Natural {
+    ///
+    /// Returns the greatest common divisor of this number and another
+    ///
+    /// The gcd of zero and zero is zero.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(48u64).gcd(&Natural::from(18u64)), Natural::from(6u64));
+    /// ```
+    pub fn gcd(&self, other: &Natural) -> Natural {
+        gcd(self, other)
+    }
+
+    ///
+    /// Returns the least common multiple of this number and another
+    ///
+    /// The lcm of zero and any number is zero.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(4u64).lcm(&Natural::from(6u64)), Natural::from(12u64));
+    /// ```
+    pub fn lcm(&self, other: &Natural) -> Natural {
+        if self.is_zero() || other.is_zero() {
+            return Natural::ZERO;
+        }
+        // Dividing before multiplying keeps the intermediate product small
+        &self.div(&gcd(self, other)).0 * other
+    }
+}
+
+impl Integer {
+    ///
+    /// Returns the greatest common divisor g of this number and another, together with
+    /// Bézout coefficients x and y such that self * x + other * y = g
+    ///
+    /// The gcd is always non-negative.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::{Integer, Natural};
+    ///
+    /// let (a, b) = (Integer::from(240), Integer::from(-46));
+    /// let (g, x, y) = a.extended_gcd(&b);
+    /// assert_eq!(g, Natural::from(2u64));
+    /// assert_eq!(&(&a * &x) + &(&b * &y), Integer::from(2));
+    /// ```
+    pub fn extended_gcd(&self, other: &Integer) -> (Natural, Integer, Integer) {
+        let (g, x, y) = extended_gcd(&self.size, &other.size);
+        // Coefficients of the magnitudes become coefficients of the signed numbers
+        // by absorbing the signs
+        let x = Integer::from_sign_and_size(x.sign * self.sign, x.size);
+        let y = Integer::from_sign_and_size(y.sign * other.sign, y.size);
+        (g, x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lcm() {
+        assert_eq!(Natural::ZERO.lcm(&Natural::from(5u64)), Natural::ZERO);
+        assert_eq!(Natural::from(21u64).lcm(&Natural::from(6u64)), Natural::from(42u64));
+        let a = Natural::from(vec!(0, 3));
+        let b = Natural::from(vec!(0, 0, 2));
+        assert_eq!(a.lcm(&b), Natural::from(vec!(0, 0, 6)));
+    }
+
+    #[test]
+    fn test_extended_gcd_signs() {
+        let values = [0i64, 1, -1, 12, -12, 18, -18, 35, -64, 1 << 40, -(3 << 50)];
+        for &a in &values {
+            for &b in &values {
+                let (za, zb) = (Integer::from(a), Integer::from(b));
+                let (g, x, y) = za.extended_gcd(&zb);
+                assert_eq!(g, Natural::from(gcd_u64(a.unsigned_abs(), b.unsigned_abs())));
+                assert_eq!(&(&za * &x) + &(&zb * &y), Integer::from(g), "{} {}", a, b);
+            }
+        }
+        assert_eq!(Integer::from(-7).extended_gcd(&Integer::from(0)).1, Integer::from(-1));
+        assert_eq!(Integer::from(0).extended_gcd(&Integer::from(0)).0, Natural::ZERO);
+        assert_eq!(Integer::from(0).extended_gcd(&Integer::from(-3)).2, Integer::from(-1));
+    }
+
+    fn gcd_u64(a: u64, b: u64) -> u64 {
+        if b == 0 { a } else { gcd_u64(b, a % b) }
+    }
+}
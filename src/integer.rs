@@ -1,13 +1,13 @@
 use core::ops::{Add, Mul, Sub, Div};
 use core::convert::From;
+use core::cmp::Ordering;
 
 use crate::natural::Natural;
 use crate::algorithms::{sub_signed, div};
+use crate::radix::to_radix_digits;
 
 use core::fmt;
 
-//pub struct ParseError(String);
-
 ///
 /// Represents the sign of an integer (Positive/Negative)
 /// 
@@ -49,38 +49,34 @@ impl Sign {
 /// println!("z = {}", z);
 /// ```
 /// 
-#[derive(Debug,Eq,PartialEq)]
+#[derive(Debug,Eq,PartialEq,Clone)]
 pub struct Integer {
-    sign: Sign,
-    size: Natural,
+    pub(crate) sign: Sign,
+    pub(crate) size: Natural,
 }
 
 impl Integer {
+    /// Builds an integer from a sign and a magnitude, ensuring zero is always positive
+    pub(crate) fn from_sign_and_size(sign: Sign, size: Natural) -> Self {
+        let sign = if size.is_zero() { Sign::Positive } else { sign };
+        Self {
+            sign,
+            size,
+        }
+    }
+
     /// 
     /// Parses a string and returns an integer
     /// 
-    /// The API for this function is likely to change in future,
-    /// with the addition of an error type for failed parsing
+    /// This is a convenience wrapper around `str::parse`, which should be used instead
+    /// when the input may not be a valid number.
+    /// 
+    /// # Panics
+    /// Panics if the string is not a valid integer
     pub fn from_string<S: Into<String>>(s: S) -> Self {
-        let mut sign = Sign::Positive;
-        let chars = s.into();
-        for (count, ch) in chars.chars().enumerate() {
-            if ch == '-' {
-                sign = sign.negate();
-            }
-            else if ch == ' ' {
-                // Do nothing
-            }
-            else if ch.is_digit(10) {
-                return Self {
-                    sign, 
-                    size: Natural::from_string(&chars[count..])
-                }
-            }
-        }
-        Self {
-            sign, 
-            size: Natural::from(0),
+        match s.into().parse() {
+            Ok(z) => z,
+            Err(e) => panic!("Failed to parse an Integer: {}", e),
         }
     }
 }
@@ -94,6 +90,24 @@ impl From<Natural> for Integer {
     }
 }
 
+impl Ord for Integer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.sign, other.sign) {
+            (Sign::Positive, Sign::Positive) => self.size.cmp(&other.size),
+            (Sign::Negative, Sign::Negative) => other.size.cmp(&self.size),
+            (Sign::Positive, Sign::Negative) => Ordering::Greater,
+            (Sign::Negative, Sign::Positive) => Ordering::Less,
+        }
+    }
+}
+
+impl PartialOrd for Integer {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Mul<Sign> for Sign {
     type Output = Self;
 
@@ -111,30 +125,13 @@ impl Add<&Integer> for &Integer {
     type Output = Integer;
 
     fn add(self, other: &Integer) -> Integer {
-        match (self.sign, other.sign) {
-            (Sign::Positive, Sign::Positive) => Integer {
-                sign: Sign::Positive, 
-                size: &self.size + &other.size
-            },
-            (Sign::Negative, Sign::Negative) => Integer {
-                sign: Sign::Negative,
-                size: &self.size + &other.size
-            },
-            (Sign::Negative, Sign::Positive) => {
-                let (sign, size) = sub_signed(&other.size, &self.size);
-                Integer {
-                    sign,
-                    size,
-                }
-            },
-            (Sign::Positive, Sign::Negative) => {
-                let (sign, size) = sub_signed(&self.size, &other.size);
-                Integer {
-                    sign,
-                    size,
-                }
-            },
-        }
+        let (sign, size) = match (self.sign, other.sign) {
+            (Sign::Positive, Sign::Positive) => (Sign::Positive, &self.size + &other.size),
+            (Sign::Negative, Sign::Negative) => (Sign::Negative, &self.size + &other.size),
+            (Sign::Negative, Sign::Positive) => sub_signed(&other.size, &self.size),
+            (Sign::Positive, Sign::Negative) => sub_signed(&self.size, &other.size),
+        };
+        Integer::from_sign_and_size(sign, size)
     }
 }
 
@@ -148,10 +145,7 @@ impl Sub<&Integer> for &Integer {
             (Sign::Positive, Sign::Negative) => (Sign::Positive, &self.size + &other.size),
             (Sign::Negative, Sign::Positive) => (Sign::Negative, &self.size + &other.size),
         };
-        Integer {
-            sign,
-            size: result,
-        }
+        Integer::from_sign_and_size(sign, result)
     }
 }
 
@@ -159,10 +153,7 @@ impl Mul<&Integer> for &Integer {
     type Output = Integer;
 
     fn mul(self, other: &Integer) -> Integer {
-        Integer {
-            sign: self.sign * other.sign,
-            size: &self.size * &other.size,
-        }
+        Integer::from_sign_and_size(self.sign * other.sign, &self.size * &other.size)
     }
 }
 
@@ -175,20 +166,16 @@ impl Div<&Integer> for &Integer {
     #[allow(clippy::suspicious_arithmetic_impl)]
     fn div(self, other: &Integer) -> Integer {
         let (d, _r) = div(&self.size, &other.size);
-        Integer {
-            sign: self.sign * other.sign,
-            size: d,
-        }
+        Integer::from_sign_and_size(self.sign * other.sign, d)
     }
 }
 
 impl fmt::Display for Integer {
+    /// Writes the number in decimal, honouring the width, fill, alignment,
+    /// sign and zero-padding flags of the formatter
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let sign_text = match self.sign {
-            Sign::Positive => "",
-            Sign::Negative => "-",
-        };
-        write!(f, "{}{}", sign_text, self.size)
+        let digits = to_radix_digits(&self.size, 10, false);
+        f.pad_integral(self.sign == Sign::Positive, "", core::str::from_utf8(&digits).unwrap())
     }
 }
 
@@ -198,102 +185,124 @@ mod tests {
 
     #[test]
     fn test_add_positive_positive() {
-        let a = Integer{sign: Sign::Positive, size: Natural::from(1234)};
-        let b = Integer{sign: Sign::Positive, size: Natural::from(123)};
-        assert_eq!(&a + &b, Integer{sign: Sign::Positive, size: Natural::from(1357)});
+        let a = Integer{sign: Sign::Positive, size: Natural::from(1234u64)};
+        let b = Integer{sign: Sign::Positive, size: Natural::from(123u64)};
+        assert_eq!(&a + &b, Integer{sign: Sign::Positive, size: Natural::from(1357u64)});
     }
 
     #[test]
     fn test_add_positive_negative() {
-        let a = Integer{sign: Sign::Positive, size: Natural::from(1234)};
-        let b = Integer{sign: Sign::Negative, size: Natural::from(123)};
-        assert_eq!(&a + &b, Integer{sign: Sign::Positive, size: Natural::from(1111)});
+        let a = Integer{sign: Sign::Positive, size: Natural::from(1234u64)};
+        let b = Integer{sign: Sign::Negative, size: Natural::from(123u64)};
+        assert_eq!(&a + &b, Integer{sign: Sign::Positive, size: Natural::from(1111u64)});
     }
 
     #[test]
     fn test_add_negative_positive() {
-        let a = Integer{sign: Sign::Negative, size: Natural::from(1234)};
-        let b = Integer{sign: Sign::Positive, size: Natural::from(123)};
-        assert_eq!(&a + &b, Integer{sign: Sign::Negative, size: Natural::from(1111)});
+        let a = Integer{sign: Sign::Negative, size: Natural::from(1234u64)};
+        let b = Integer{sign: Sign::Positive, size: Natural::from(123u64)};
+        assert_eq!(&a + &b, Integer{sign: Sign::Negative, size: Natural::from(1111u64)});
     }
 
     #[test]
     fn test_add_negative_negative() {
-        let a = Integer{sign: Sign::Negative, size: Natural::from(1234)};
-        let b = Integer{sign: Sign::Negative, size: Natural::from(123)};
-        assert_eq!(&a + &b, Integer{sign: Sign::Negative, size: Natural::from(1357)});
+        let a = Integer{sign: Sign::Negative, size: Natural::from(1234u64)};
+        let b = Integer{sign: Sign::Negative, size: Natural::from(123u64)};
+        assert_eq!(&a + &b, Integer{sign: Sign::Negative, size: Natural::from(1357u64)});
     }
 
     #[test]
     fn test_sub_positive_positive_eq_positive() {
-        let a = Integer{sign: Sign::Positive, size: Natural::from(400)};
-        let b = Integer{sign: Sign::Positive, size: Natural::from(100)};
-        assert_eq!(&a - &b, Integer{sign: Sign::Positive, size: Natural::from(300)});
+        let a = Integer{sign: Sign::Positive, size: Natural::from(400u64)};
+        let b = Integer{sign: Sign::Positive, size: Natural::from(100u64)};
+        assert_eq!(&a - &b, Integer{sign: Sign::Positive, size: Natural::from(300u64)});
     }
 
     #[test]
     fn test_sub_positive_positive_eq_negative() {
-        let a = Integer{sign: Sign::Positive, size: Natural::from(100)};
-        let b = Integer{sign: Sign::Positive, size: Natural::from(400)};
-        assert_eq!(&a - &b, Integer{sign: Sign::Negative, size: Natural::from(300)});
+        let a = Integer{sign: Sign::Positive, size: Natural::from(100u64)};
+        let b = Integer{sign: Sign::Positive, size: Natural::from(400u64)};
+        assert_eq!(&a - &b, Integer{sign: Sign::Negative, size: Natural::from(300u64)});
     }
 
     #[test]
     fn test_sub_positive_negative() {
-        let a = Integer{sign: Sign::Positive, size: Natural::from(400)};
-        let b = Integer{sign: Sign::Negative, size: Natural::from(100)};
-        assert_eq!(&a - &b, Integer{sign: Sign::Positive, size: Natural::from(500)});
+        let a = Integer{sign: Sign::Positive, size: Natural::from(400u64)};
+        let b = Integer{sign: Sign::Negative, size: Natural::from(100u64)};
+        assert_eq!(&a - &b, Integer{sign: Sign::Positive, size: Natural::from(500u64)});
         
-        let a = Integer{sign: Sign::Positive, size: Natural::from(100)};
-        let b = Integer{sign: Sign::Negative, size: Natural::from(400)};
-        assert_eq!(&a - &b, Integer{sign: Sign::Positive, size: Natural::from(500)});
+        let a = Integer{sign: Sign::Positive, size: Natural::from(100u64)};
+        let b = Integer{sign: Sign::Negative, size: Natural::from(400u64)};
+        assert_eq!(&a - &b, Integer{sign: Sign::Positive, size: Natural::from(500u64)});
     }
 
     #[test]
     fn test_sub_negative_positive() {
-        let a = Integer{sign: Sign::Negative, size: Natural::from(400)};
-        let b = Integer{sign: Sign::Positive, size: Natural::from(100)};
-        assert_eq!(&a - &b, Integer{sign: Sign::Negative, size: Natural::from(500)});
+        let a = Integer{sign: Sign::Negative, size: Natural::from(400u64)};
+        let b = Integer{sign: Sign::Positive, size: Natural::from(100u64)};
+        assert_eq!(&a - &b, Integer{sign: Sign::Negative, size: Natural::from(500u64)});
 
-        let a = Integer{sign: Sign::Negative, size: Natural::from(100)};
-        let b = Integer{sign: Sign::Positive, size: Natural::from(400)};
-        assert_eq!(&a - &b, Integer{sign: Sign::Negative, size: Natural::from(500)});
+        let a = Integer{sign: Sign::Negative, size: Natural::from(100u64)};
+        let b = Integer{sign: Sign::Positive, size: Natural::from(400u64)};
+        assert_eq!(&a - &b, Integer{sign: Sign::Negative, size: Natural::from(500u64)});
     }
 
     #[test]
     fn test_sub_negative_negative_eq_positive() {
-        let a = Integer{sign: Sign::Negative, size: Natural::from(100)};
-        let b = Integer{sign: Sign::Negative, size: Natural::from(400)};
-        assert_eq!(&a - &b, Integer{sign: Sign::Positive, size: Natural::from(300)});
+        let a = Integer{sign: Sign::Negative, size: Natural::from(100u64)};
+        let b = Integer{sign: Sign::Negative, size: Natural::from(400u64)};
+        assert_eq!(&a - &b, Integer{sign: Sign::Positive, size: Natural::from(300u64)});
     }
 
     #[test]
     fn test_sub_negative_negative_eq_negative() {
-        let a = Integer{sign: Sign::Negative, size: Natural::from(400)};
-        let b = Integer{sign: Sign::Negative, size: Natural::from(100)};
-        assert_eq!(&a - &b, Integer{sign: Sign::Negative, size: Natural::from(300)});
+        let a = Integer{sign: Sign::Negative, size: Natural::from(400u64)};
+        let b = Integer{sign: Sign::Negative, size: Natural::from(100u64)};
+        assert_eq!(&a - &b, Integer{sign: Sign::Negative, size: Natural::from(300u64)});
     }
 
     #[test]
     fn test_mul_positive_positive() {
-        let a = Integer{sign: Sign::Positive, size: Natural::from(20)};
-        let b = Integer{sign: Sign::Positive, size: Natural::from(10)};
-        assert_eq!(&a * &b, Integer{sign: Sign::Positive, size: Natural::from(200)});
+        let a = Integer{sign: Sign::Positive, size: Natural::from(20u64)};
+        let b = Integer{sign: Sign::Positive, size: Natural::from(10u64)};
+        assert_eq!(&a * &b, Integer{sign: Sign::Positive, size: Natural::from(200u64)});
     }
 
     #[test]
     fn test_mul_positive_negative() {
-        let a = Integer{sign: Sign::Positive, size: Natural::from(20)};
-        let b = Integer{sign: Sign::Negative, size: Natural::from(10)};
-        assert_eq!(&a * &b, Integer{sign: Sign::Negative, size: Natural::from(200)});
+        let a = Integer{sign: Sign::Positive, size: Natural::from(20u64)};
+        let b = Integer{sign: Sign::Negative, size: Natural::from(10u64)};
+        assert_eq!(&a * &b, Integer{sign: Sign::Negative, size: Natural::from(200u64)});
+    }
+
+    #[test]
+    fn test_zero_results_are_positive() {
+        let zero = Integer::from(0);
+        let product = &Integer::from(-3) * &zero;
+        let quotient = &Integer::from(-1) / &Integer::from(5);
+        for z in [&product, &quotient, &(&Integer::from(-4) + &Integer::from(4)), &(&Integer::from(-4) - &Integer::from(-4))] {
+            assert_eq!(*z, zero);
+            assert_eq!(z.cmp(&zero), Ordering::Equal);
+            assert_eq!(z.to_string(), "0");
+        }
+        assert!(product < Integer::from(1) && product > Integer::from(-1));
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut values = vec!(Integer::from(3), Integer::from(-7), Integer::from(0),
+                              Integer::from(-2), Integer::from(10));
+        values.sort();
+        assert_eq!(values, vec!(Integer::from(-7), Integer::from(-2), Integer::from(0),
+                                Integer::from(3), Integer::from(10)));
     }
 
     #[test]
     fn test_can_parse_string() {
         let one_hundred = Integer::from_string("100");
         let minus_five = Integer::from_string("-5");
-        assert_eq!(one_hundred, Integer{sign: Sign::Positive, size: Natural::from(100)});
-        assert_eq!(minus_five, Integer{sign: Sign::Negative, size: Natural::from(5)});
+        assert_eq!(one_hundred, Integer{sign: Sign::Positive, size: Natural::from(100u64)});
+        assert_eq!(minus_five, Integer{sign: Sign::Negative, size: Natural::from(5u64)});
     }
 
     #[test]
@@ -0,0 +1,217 @@
+// The Jacobi, Legendre and Kronecker symbols
+//
+// Moduli below 2^64 are handled in native arithmetic. Larger ones use the binary
+// algorithm, or for the very largest a half-gcd which follows the sign of the symbol
+// through its quotients. The Kronecker symbol extends the Jacobi symbol to any
+// modulus, with (a/2) = 0 for even a and otherwise (2/a), (a/-1) = -1 for negative a
+// and otherwise 1, and (a/0) = 1 when a = ±1 and otherwise 0.
+
+use crate::natural::Natural;
+use crate::integer::{Integer, Sign};
+use crate::algorithms;
+
+/// Returns the Jacobi symbol (a/n) for odd n
+pub(crate) fn jacobi_u64(mut a: u64, mut n: u64) -> i32 {
+    debug_assert!(n & 1 == 1);
+    a %= n;
+    let mut result = 1;
+    while a != 0 {
+        let twos = a.trailing_zeros();
+        a >>= twos;
+        // (2/n) = -1 exactly when n = 3 or 5 (mod 8)
+        if twos & 1 == 1 && (n % 8 == 3 || n % 8 == 5) {
+            result = -result;
+        }
+        // Quadratic reciprocity flips the sign when both are 3 (mod 4)
+        if a % 4 == 3 && n % 4 == 3 {
+            result = -result;
+        }
+        let t = n % a;
+        n = a;
+        a = t;
+    }
+    if n == 1 { result } else { 0 }
+}
+
+/// Returns the Jacobi symbol (a/n) for odd n
+pub(crate) fn jacobi_i64(a: i64, n: &Natural) -> i32 {
+    let magnitude = a.unsigned_abs();
+    // (a/n) = (a mod n / n), and for a = 2^t b with b odd reciprocity gives
+    // (b/n) = (n mod b / b), up to sign
+    let twos = magnitude.trailing_zeros();
+    let odd = magnitude >> twos;
+    let n_mod_8 = n.rem_digit(8);
+    let mut result = jacobi_u64(n.rem_digit(odd), odd);
+    if twos & 1 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+        result = -result;
+    }
+    if odd % 4 == 3 && n_mod_8 % 4 == 3 {
+        result = -result;
+    }
+    // (-1/n) = -1 exactly when n = 3 (mod 4)
+    if a < 0 && n_mod_8 % 4 == 3 {
+        result = -result;
+    }
+    result
+}
+
+///
+/// Returns the Jacobi symbol (a/n), which is 1, -1 or 0
+///
+/// For a prime n this is the Legendre symbol, and in general it is the product of the
+/// Legendre symbols for the prime factors of n. It is 0 exactly when a and n have a
+/// common factor.
+///
+/// # Panics
+/// If n is even
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, jacobi};
+///
+/// assert_eq!(jacobi(&Natural::from(2u64), &Natural::from(15u64)), 1);
+/// assert_eq!(jacobi(&Natural::from(7u64), &Natural::from(15u64)), -1);
+/// assert_eq!(jacobi(&Natural::from(10u64), &Natural::from(15u64)), 0);
+/// ```
+pub fn jacobi(a: &Natural, n: &Natural) -> i32 {
+    assert!(n.rem_digit(2) == 1, "Jacobi symbol with an even modulus");
+    match n.digits[..] {
+        [n] => jacobi_u64(a.rem_digit(n), n),
+        _ => algorithms::jacobi(a, n),
+    }
+}
+
+///
+/// Returns the Legendre symbol (a/p) for an odd prime p, which is 1 when a is a
+/// non-zero square modulo p, -1 when it is not a square and 0 when p divides a
+///
+/// p is not tested for primality, and for composite p this is the Jacobi symbol.
+///
+/// # Panics
+/// If p is even
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, legendre};
+///
+/// let p = Natural::from(1_000_000_007u64);
+/// assert_eq!(legendre(&Natural::from(4u64), &p), 1);
+/// assert_eq!(legendre(&Natural::from(5u64), &p), -1);
+/// ```
+pub fn legendre(a: &Natural, p: &Natural) -> i32 {
+    jacobi(a, p)
+}
+
+///
+/// Returns the Kronecker symbol (a/n), which extends the Jacobi symbol to any
+/// Integers a and n
+///
+/// # Example
+/// ```rust
+/// use skewes::{Integer, kronecker};
+///
+/// assert_eq!(kronecker(&Integer::from(-1), &Integer::from(7)), -1);
+/// assert_eq!(kronecker(&Integer::from(3), &Integer::from(-8)), -1);
+/// assert_eq!(kronecker(&Integer::from(5), &Integer::from(0)), 0);
+/// ```
+pub fn kronecker(a: &Integer, n: &Integer) -> i32 {
+    let Some(twos) = n.size.trailing_zeros() else {
+        return if a.size == Natural::from(1u64) { 1 } else { 0 };
+    };
+    let a_mod_8 = a.size.rem_digit(8);
+    let a_mod_8 = if a.sign == Sign::Negative { (8 - a_mod_8) % 8 } else { a_mod_8 };
+    let mut result = 1;
+    if twos > 0 {
+        if a_mod_8 % 2 == 0 {
+            return 0;
+        }
+        if twos % 2 == 1 && (a_mod_8 == 3 || a_mod_8 == 5) {
+            result = -result;
+        }
+    }
+    if n.sign == Sign::Negative && a.sign == Sign::Negative {
+        result = -result;
+    }
+    let odd = Natural::from(algorithms::shr_slice(&n.size.digits, twos as usize));
+    // (-a/m) = (-1/m) (a/m), where (-1/m) = -1 exactly when m = 3 (mod 4)
+    if a.sign == Sign::Negative && odd.rem_digit(4) == 3 {
+        result = -result;
+    }
+    result * jacobi(&a.size, &odd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jacobi_native() {
+        assert_eq!(jacobi_u64(2, 7), 1);
+        assert_eq!(jacobi_u64(3, 7), -1);
+        assert_eq!(jacobi_u64(21, 7), 0);
+        assert_eq!(jacobi_u64(1001, 9907), -1);
+        let p = Natural::from(1_000_000_007u64);
+        for a in [-11i64, -7, 5, 9, 13] {
+            let residue = if a < 0 { &p - &Natural::from(a.unsigned_abs()) } else { Natural::from(a as u64) };
+            let euler = residue.pow_mod(&Natural::from(500_000_003u64), &p);
+            let expected = if euler == Natural::from(1u64) { 1 } else { -1 };
+            assert_eq!(jacobi_i64(a, &p), expected, "{}", a);
+        }
+    }
+
+    #[test]
+    fn test_jacobi() {
+        // (a/p) = a^((p-1)/2) for the prime p = 2^521 - 1
+        let p = &Natural::from(2u64).pow(521) - &Natural::from(1u64);
+        let exponent = p.div(&Natural::from(2u64)).0;
+        for a in [2u64, 3, 5, 7, 11, 1_000_003] {
+            let a = &Natural::from(a).pow(60) + &Natural::from(1u64);
+            let euler = a.pow_mod(&exponent, &p);
+            let expected = if euler == Natural::from(1u64) { 1 } else { -1 };
+            assert_eq!(legendre(&a, &p), expected);
+        }
+        // The symbol is multiplicative in the modulus
+        let q = &Natural::from(2u64).pow(127) - &Natural::from(1u64);
+        let a = &Natural::from(3u64).pow(200) + &Natural::from(2u64);
+        assert_eq!(jacobi(&a, &(&p * &q)), jacobi(&a, &p) * jacobi(&a, &q));
+        assert_eq!(jacobi(&(&a * &q), &(&p * &q)), 0);
+        assert_eq!(jacobi(&Natural::ZERO, &Natural::from(1u64)), 1);
+        // 2^4423 - 1 is prime and long enough for the half-gcd
+        let p = &Natural::from(2u64).pow(4423) - &Natural::from(1u64);
+        let exponent = p.div(&Natural::from(2u64)).0;
+        for a in [&a, &q] {
+            let euler = a.pow_mod(&exponent, &p);
+            let expected = if euler == Natural::from(1u64) { 1 } else { -1 };
+            assert_eq!(jacobi(&a.pow(40), &p), 1);
+            assert_eq!(jacobi(&a.pow(41), &p), expected);
+        }
+    }
+
+    #[test]
+    fn test_kronecker() {
+        let (a_values, n_values) = ([-9i64, -8, -3, -2, -1, 0, 1, 2, 3, 6, 7], [-12i64, -7, -2, -1, 0, 1, 2, 4, 5, 8, 15]);
+        // From the definition, by factoring n
+        let two = |a: i64| match a.rem_euclid(8) {
+            1 | 7 => 1,
+            3 | 5 => -1,
+            _ => 0,
+        };
+        for &a in &a_values {
+            for &n in &n_values {
+                let expected = if n == 0 {
+                    (a.abs() == 1) as i32
+                }
+                else {
+                    let mut m = n.abs();
+                    let mut result = if n < 0 && a < 0 { -1 } else { 1 };
+                    while m % 2 == 0 {
+                        result *= two(a);
+                        m /= 2;
+                    }
+                    result * jacobi_u64(a.rem_euclid(m) as u64, m as u64)
+                };
+                assert_eq!(kronecker(&Integer::from(a), &Integer::from(n)), expected, "({}/{})", a, n);
+            }
+        }
+    }
+}
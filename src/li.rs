@@ -0,0 +1,201 @@
+// The logarithmic integral and Riemann's prime counting function
+//
+// With L = ln x, both come from series of positive terms in the powers of L:
+//
+//     li(x) = gamma + ln L + sum(L^k / (k k!))
+//     R(x)  = 1 + sum(L^k / (k k! zeta(k + 1)))
+//
+// the second being Gram's series. Once k passes 2L each L^k / k! is at most half the
+// one before, so the rest of either series is below the last L^k / k!. Since li(x) and
+// R(x) are close to x / L, an error of 2^-p in L becomes one of about 2^-p x in the
+// result, and the work is done with as many more bits as x has.
+//
+// zeta(k + 1) is wanted for each k in turn. The powers m^-s are kept for m up to n and
+// divided by m at each step. Once some N^-s is below 2^-p, zeta(s) is summed directly,
+// the rest of the sum being below N^-s (1 + N / (s - 1)). Before that it comes from
+// the alternating series of Borwein,
+//
+//     eta(s) = sum((-1)^k (d_n - d_k) / (k + 1)^s) / d_n,   k < n
+//
+// with d_k = sum(n (n + i - 1)! 4^i / ((n - i)! (2i)!)) over i <= k, and
+// zeta(s) = eta(s) / (1 - 2^(1 - s)), whose error for real s >= 2 is below
+// 6 / (3 + sqrt(8))^n.
+
+use crate::natural::Natural;
+use crate::integer::Integer;
+use crate::real::{RealInterval, euler_gamma, ln_natural};
+
+/// The bits of working precision for a result to the given precision at x
+fn working_precision(x: &Natural, precision: u64) -> u64 {
+    let bits = precision + x.bit_length();
+    bits + 2 * (64 - bits.leading_zeros()) as u64 + 32
+}
+
+/// Values of the zeta function at 2, 3, 4, ... in turn
+struct Zeta {
+    /// The argument of the last value
+    s: u64,
+    /// Bounds on m^-s for m from 1, at index m - 1
+    powers: Vec<RealInterval>,
+    /// d_n - d_k for k < n, while the powers are still needed for Borwein's series
+    coefficients: Vec<Natural>,
+    d_n: Natural,
+}
+
+impl Zeta {
+    fn new(precision: u64) -> Self {
+        // log2(3 + sqrt(8)) > 2.54
+        let n = (precision + 3) * 100 / 254 + 1;
+        let mut e = Natural::from(1u64);
+        let mut d = vec!(e.clone());
+        for i in 1..=n {
+            let (q, r) = (&e * &Natural::from(4 * (n + i - 1) * (n - i + 1))).div(&Natural::from(2 * i * (2 * i - 1)));
+            debug_assert!(r.is_zero());
+            e = q;
+            d.push(&d[i as usize - 1] + &e);
+        }
+        let d_n = d.pop().unwrap();
+        let coefficients = d.iter().map(|d_k| &d_n - d_k).collect();
+        let one = RealInterval::from_natural(&Natural::from(1u64), precision);
+        let powers = (1..=n).map(|m| one.div_natural(&Natural::from(m))).collect();
+        Self { s: 1, powers, coefficients, d_n }
+    }
+
+    /// Bounds on zeta at one more than the last argument
+    fn next(&mut self) -> RealInterval {
+        self.s += 1;
+        let s = self.s;
+        for (m, power) in (1u64..).zip(self.powers.iter_mut()) {
+            *power = power.div_natural(&Natural::from(m));
+        }
+        match self.powers.iter().position(RealInterval::is_negligible) {
+            Some(i) => {
+                // Later powers are only needed to find the next cut-off, which is no later
+                self.powers.truncate(i + 1);
+                self.coefficients.clear();
+                let n = i as u64 + 1;
+                let sum = self.powers[1..i].iter().fold(self.powers[0].clone(), |sum, power| sum.add(power));
+                let tail = 1 + (n + s - 2) / (s - 1);
+                sum.raise_upper(tail)
+            }
+            None => {
+                let precision = self.powers[0].precision();
+                let mut positive = RealInterval::from_natural(&Natural::ZERO, precision);
+                let mut negative = positive.clone();
+                for (k, (c, power)) in self.coefficients.iter().zip(&self.powers).enumerate() {
+                    let term = power.scale(c);
+                    if k.is_multiple_of(2) {
+                        positive = positive.add(&term);
+                    }
+                    else {
+                        negative = negative.add(&term);
+                    }
+                }
+                let eta = positive.sub(&negative).div_natural(&self.d_n);
+                let half = Natural::from(2u64).pow(s - 1);
+                eta.scale(&half).div_natural(&(&half - &Natural::from(1u64))).widen(1)
+            }
+        }
+    }
+}
+
+/// Sums L^k / (k k! zeta(k + 1)) over k >= 1, or L^k / (k k!) without zeta
+fn gram_sum(l: &RealInterval, mut zeta: Option<&mut Zeta>) -> RealInterval {
+    let mut power = RealInterval::from_natural(&Natural::from(1u64), l.precision());
+    let mut sum = RealInterval::from_natural(&Natural::ZERO, l.precision());
+    let limit = &l.ceil_upper() * &Integer::from(2i64);
+    for k in 1u64.. {
+        let k_natural = Natural::from(k);
+        power = power.mul(l).div_natural(&k_natural);
+        let mut term = power.div_natural(&k_natural);
+        if let Some(zeta) = zeta.as_mut() {
+            term = term.div(&zeta.next());
+        }
+        sum = sum.add(&term);
+        if Integer::from(k + 1) >= limit && power.is_negligible() {
+            break;
+        }
+    }
+    // The rest of the series is below the last L^k / k!, which is below a unit
+    sum.raise_upper(1)
+}
+
+///
+/// Returns bounds on the logarithmic integral li(x), the principal value of the
+/// integral of 1 / ln t from 0 to x, with the given number of fractional bits
+///
+/// # Panics
+/// If x is less than 2
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, Integer, li};
+///
+/// let value = li(&Natural::from(10u64).pow(12), 32);
+/// assert_eq!(value.floor(), Some(Integer::from(37_607_950_280i64)));
+/// ```
+pub fn li(x: &Natural, precision: u64) -> RealInterval {
+    assert!(*x >= Natural::from(2u64), "li(x) for x below 2");
+    let working = working_precision(x, precision);
+    let l = ln_natural(x, working);
+    euler_gamma(working).add(&l.ln()).add(&gram_sum(&l, None)).with_precision(precision)
+}
+
+///
+/// Returns bounds on Riemann's prime counting function R(x), the sum of
+/// mu(n) li(x^(1/n)) / n over n >= 1, with the given number of fractional bits
+///
+/// # Panics
+/// If x is zero
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, Integer, riemann_r};
+///
+/// let value = riemann_r(&Natural::from(10u64).pow(12), 32);
+/// assert_eq!(value.floor(), Some(Integer::from(37_607_910_542i64)));
+/// ```
+pub fn riemann_r(x: &Natural, precision: u64) -> RealInterval {
+    assert!(!x.is_zero(), "R(x) for x of zero");
+    let working = working_precision(x, precision);
+    let l = ln_natural(x, working);
+    let one = RealInterval::from_natural(&Natural::from(1u64), working);
+    one.add(&gram_sum(&l, Some(&mut Zeta::new(working)))).with_precision(precision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zeta() {
+        let mut zeta = Zeta::new(100);
+        assert_eq!(format!("{:.25}", zeta.next()), "[1.6449340668482264364724151, 1.6449340668482264364724152]");
+        assert_eq!(format!("{:.25}", zeta.next()), "[1.2020569031595942853997381, 1.2020569031595942853997382]");
+        for _ in 4..200 {
+            zeta.next();
+        }
+        // zeta(200) is 1 + 2^-200 + ..., which is 1 at this precision
+        assert_eq!(format!("{:.25}", zeta.next()), "[1.0000000000000000000000000, 1.0000000000000000000000001]");
+    }
+
+    #[test]
+    fn test_li() {
+        assert_eq!(format!("{:.30}", li(&Natural::from(2u64), 128)), "[1.045163780117492784844588889194, 1.045163780117492784844588889195]");
+        assert_eq!(format!("{:.30}", li(&Natural::from(10u64), 128)), "[6.165599504787297937522981752669, 6.165599504787297937522981752670]");
+        assert_eq!(format!("{:.20}", li(&Natural::from(10u64).pow(15), 80)), "[29844571475287.58106490589058598594, 29844571475287.58106490589058598595]");
+        let value = li(&Natural::from(10u64).pow(100), 32);
+        assert_eq!(value.floor().unwrap().to_string(), "43619719871407031590995091132291646115387572117171703030140224030853500384584993091500059589703719");
+        assert!(li(&Natural::from(10u64).pow(1000), 8).floor().unwrap().to_string().starts_with("434483257640119745541093005164817784217811590701875"));
+    }
+
+    #[test]
+    fn test_riemann_r() {
+        assert_eq!(format!("{:.30}", riemann_r(&Natural::from(1u64), 128)), "[0.999999999999999999999999999999, 1.000000000000000000000000000001]");
+        assert_eq!(format!("{:.30}", riemann_r(&Natural::from(2u64), 128)), "[1.541009016187131883288503786627, 1.541009016187131883288503786628]");
+        assert_eq!(format!("{:.20}", riemann_r(&Natural::from(1000u64), 80)), "[168.35944628116734806491, 168.35944628116734806492]");
+        assert_eq!(format!("{:.20}", riemann_r(&Natural::from(10u64).pow(15), 80)), "[29844570495886.92737822228672779202, 29844570495886.92737822228672779203]");
+        let value = riemann_r(&Natural::from(10u64).pow(100), 8);
+        assert_eq!(value.floor().unwrap().to_string(), "43619719871407031590995091132291646115387572117171264896124348638759579492041603135851786844754145");
+    }
+}
@@ -14,7 +14,7 @@
 //! let n = Natural::from_string("1234567890000000000");
 //! 
 //! // Create a number from a native type
-//! let m = Natural::from(123);
+//! let m = Natural::from(123u64);
 //! 
 //! // Do arithmetic with these numbers
 //! let x = &m + &n;
@@ -34,10 +34,47 @@ mod integer;
 mod algorithms;
 mod division_result;
 mod range;
+mod conversion;
+mod parse;
+mod radix;
+mod format;
+mod bytes;
+mod power;
+mod roots;
+mod gcd;
+mod modular;
+mod montgomery;
+mod barrett;
+mod primality;
+mod jacobi;
+mod certificate;
+mod primes;
+mod sieve;
+mod factor;
+mod ecm;
+mod siqs;
+mod prime_count;
+mod real;
+mod li;
 
 pub use natural::Natural;
 pub use integer::Sign;
 pub use integer::Integer;
-pub use range::Range;
+pub use range::{Range, IntegerRange};
+pub use conversion::{TryFromIntError, TryFromFloatError};
+pub use parse::{ParseError, ParseErrorKind};
+pub use format::Grouped;
+pub use bytes::{Endianness, BufferTooSmallError};
+pub use montgomery::MontgomeryContext;
+pub use barrett::BarrettContext;
+pub use primality::Primality;
+pub use jacobi::{jacobi, legendre, kronecker};
+pub use primes::Primes;
+pub use sieve::SegmentedSieve;
+pub use ecm::EcmParameters;
+pub use prime_count::prime_count;
+pub use real::RealInterval;
+pub use li::{li, riemann_r};
+pub use certificate::{PrimeCertificate, FactorWitness, ParseCertificateError, verify_certificate};
 
 use natural::Limb;
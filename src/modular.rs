@@ -0,0 +1,221 @@
+// Modular exponentiation and inverses of Natural and Integer
+
+use crate::natural::Natural;
+use crate::integer::{Integer, Sign};
+use crate::montgomery::MontgomeryContext;
+use crate::barrett::BarrettContext;
+use crate::algorithms::{pow_mod, extended_gcd};
+
+impl Natural {
+    ///
+    /// Returns this number raised to the power exp, modulo m
+    ///
+    /// Intermediate results are reduced after every multiplication, so they never
+    /// grow beyond twice the length of m. Odd moduli use Montgomery multiplication and
+    /// others Barrett reduction, both of which avoid dividing by m at each step.
+    ///
+    /// # Panics
+    /// Panics if m is zero
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// let m = Natural::from(497u64);
+    /// assert_eq!(Natural::from(4u64).pow_mod(&Natural::from(13u64), &m), Natural::from(445u64));
+    /// ```
+    pub fn pow_mod(&self, exp: &Natural, m: &Natural) -> Natural {
+        // Division by a single-digit modulus is already cheap
+        if m.digits.first().is_some_and(|d| d & 1 == 1) && m.digits.len() > 1 {
+            let context = MontgomeryContext::new(m);
+            context.from_montgomery(&context.pow(&context.to_montgomery(self), exp))
+        }
+        else if m.digits.len() > 1 {
+            BarrettContext::new(m).pow_mod(self, exp)
+        }
+        else {
+            pow_mod(self, exp, m)
+        }
+    }
+
+    ///
+    /// Returns the product of this number and another, modulo m
+    ///
+    /// This takes a single division of the product by m. Setting up Barrett or
+    /// Montgomery reduction costs about as much, so for many products modulo the
+    /// same m a `BarrettContext` or `MontgomeryContext` should be made once instead.
+    ///
+    /// # Panics
+    /// Panics if m is zero
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(123u64).mul_mod(&Natural::from(456u64), &Natural::from(1000u64)), Natural::from(88u64));
+    /// ```
+    pub fn mul_mod(&self, other: &Natural, m: &Natural) -> Natural {
+        &(self * other) % m
+    }
+
+    ///
+    /// Returns the inverse of this number modulo m, i.e. the x in [0, m) with
+    /// self * x = 1 (mod m), or None if the number and m are not coprime
+    ///
+    /// # Panics
+    /// Panics if m is zero
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(3u64).inv_mod(&Natural::from(11u64)), Some(Natural::from(4u64)));
+    /// assert_eq!(Natural::from(6u64).inv_mod(&Natural::from(9u64)), None);
+    /// ```
+    pub fn inv_mod(&self, m: &Natural) -> Option<Natural> {
+        let a = self % m;
+        let (g, x, _) = extended_gcd(&a, m);
+        if g != Natural::from(1u64) {
+            // Everything is congruent to one modulo one, and zero is its own inverse
+            return if *m == Natural::from(1u64) { Some(Natural::ZERO) } else { None };
+        }
+        Some(x.rem_natural(m))
+    }
+}
+
+impl Integer {
+    /// Returns the residue of this number modulo m in the range [0, m)
+    fn rem_natural(&self, m: &Natural) -> Natural {
+        let r = &self.size % m;
+        if self.sign == Sign::Negative && !r.is_zero() {
+            m - &r
+        }
+        else {
+            r
+        }
+    }
+
+    ///
+    /// Returns the product of this number and another modulo m, as a number in [0, m)
+    ///
+    /// Like `Natural::mul_mod`, this takes a single division of the product by m.
+    ///
+    /// # Panics
+    /// Panics if m is zero
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::{Integer, Natural};
+    ///
+    /// assert_eq!(Integer::from(-3).mul_mod(&Integer::from(4), &Natural::from(10u64)), Natural::from(8u64));
+    /// ```
+    pub fn mul_mod(&self, other: &Integer, m: &Natural) -> Natural {
+        (self * other).rem_natural(m)
+    }
+
+    ///
+    /// Returns this number raised to the power exp, modulo m, as a number in [0, m)
+    ///
+    /// A negative exponent raises the inverse of the number modulo m to the power
+    /// -exp, so None is returned if the exponent is negative and the number has no
+    /// inverse.
+    ///
+    /// # Panics
+    /// Panics if m is zero
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::{Integer, Natural};
+    ///
+    /// let m = Natural::from(11u64);
+    /// assert_eq!(Integer::from(-2).pow_mod(&Integer::from(3), &m), Some(Natural::from(3u64)));
+    /// assert_eq!(Integer::from(3).pow_mod(&Integer::from(-1), &m), Some(Natural::from(4u64)));
+    /// assert_eq!(Integer::from(22).pow_mod(&Integer::from(-1), &m), None);
+    /// ```
+    pub fn pow_mod(&self, exp: &Integer, m: &Natural) -> Option<Natural> {
+        let base = self.rem_natural(m);
+        match exp.sign {
+            Sign::Positive => Some(base.pow_mod(&exp.size, m)),
+            Sign::Negative => base.inv_mod(m).map(|inverse| inverse.pow_mod(&exp.size, m)),
+        }
+    }
+
+    ///
+    /// Returns the inverse of this number modulo m as a number in [0, m), or None if
+    /// the number and m are not coprime
+    ///
+    /// # Panics
+    /// Panics if m is zero
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::{Integer, Natural};
+    ///
+    /// assert_eq!(Integer::from(-3).inv_mod(&Natural::from(11u64)), Some(Natural::from(7u64)));
+    /// ```
+    pub fn inv_mod(&self, m: &Natural) -> Option<Natural> {
+        self.rem_natural(m).inv_mod(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inv_mod() {
+        let m = Natural::from(1_000_000_007u64);
+        for a in 1u64..200 {
+            let x = Natural::from(a).inv_mod(&m).unwrap();
+            assert_eq!(&(&x * &Natural::from(a)) % &m, Natural::from(1u64));
+        }
+        assert_eq!(Natural::ZERO.inv_mod(&m), None);
+        assert_eq!(Natural::from(5u64).inv_mod(&Natural::from(1u64)), Some(Natural::ZERO));
+        // 2^64 + 1 is odd, so it is invertible modulo 2^128
+        let big = Natural::from(vec!(1, 1));
+        let x = big.inv_mod(&Natural::from(vec!(0, 0, 1))).unwrap();
+        assert_eq!(&(&x * &big) % &Natural::from(vec!(0, 0, 1)), Natural::from(1u64));
+    }
+
+    #[test]
+    fn test_mul_mod() {
+        let even = [Natural::from(vec!(0, 1)), Natural::from(vec!(6, 5, 4)), Natural::from(10u64).pow(40)];
+        let odd = [Natural::from(1001u64), Natural::from(vec!(7, 3)), Natural::from(1u64)];
+        let (a, b) = (Natural::from(vec!(u64::MAX, 42, 7, 1)), Natural::from(3u64).pow(100));
+        for m in even.iter().chain(&odd) {
+            assert_eq!(a.mul_mod(&b, m), &(&a * &b) % m);
+            assert_eq!(Natural::ZERO.mul_mod(&b, m), Natural::ZERO);
+            assert_eq!(Integer::from(a.clone()).mul_mod(&Integer::from(b.clone()), m), a.mul_mod(&b, m));
+            assert_eq!(BarrettContext::new(m).mul_mod(&a, &b), a.mul_mod(&b, m));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mul_mod_zero_modulus() {
+        Natural::from(3u64).mul_mod(&Natural::from(4u64), &Natural::ZERO);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_inv_mod_zero_modulus() {
+        Natural::from(3u64).inv_mod(&Natural::ZERO);
+    }
+
+    #[test]
+    fn test_integer_pow_mod_negative_exponent() {
+        let m = Natural::from(101u64);
+        for a in -20i64..20 {
+            let z = Integer::from(a);
+            match z.inv_mod(&m) {
+                Some(inverse) => {
+                    let x = z.pow_mod(&Integer::from(-5), &m).unwrap();
+                    assert_eq!(x, inverse.pow_mod(&Natural::from(5u64), &m));
+                    let y = z.pow_mod(&Integer::from(5), &m).unwrap();
+                    assert_eq!(&(&x * &y) % &m, Natural::from(1u64));
+                },
+                None => assert_eq!(a, 0),
+            }
+        }
+    }
+}
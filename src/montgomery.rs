@@ -0,0 +1,161 @@
+// Montgomery arithmetic modulo an odd Natural
+//
+// A number x is held in Montgomery form as xR mod m, where R = 2^(64n) for an
+// n-digit modulus. The product of two numbers in this form is divided by R with a
+// Montgomery reduction, which needs only single-digit multiplications and shifts
+// in place of a full division by m.
+
+use crate::natural::{Natural, Limb};
+use crate::algorithms::{div, mul_into, neg_limb_inverse, redc, window_pow};
+
+///
+/// Precomputed values for repeated multiplication modulo a fixed odd number
+///
+/// Values are converted into Montgomery form with `to_montgomery`, combined with
+/// `mul`, `square` and `pow`, and converted back with `from_montgomery`. Every
+/// value passed to these methods must be in Montgomery form, i.e. less than the modulus.
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, MontgomeryContext};
+///
+/// let context = MontgomeryContext::new(&Natural::from(101u64));
+/// let a = context.to_montgomery(&Natural::from(20u64));
+/// let b = context.to_montgomery(&Natural::from(30u64));
+/// assert_eq!(context.from_montgomery(&context.mul(&a, &b)), Natural::from(95u64));
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct MontgomeryContext {
+    modulus: Natural,
+    /// -m^-1 mod 2^64
+    m_inv: Limb,
+    /// R^2 mod m, which converts a number into Montgomery form in one multiplication
+    r_squared: Natural,
+    /// R mod m, the Montgomery form of one
+    one: Natural,
+}
+
+impl MontgomeryContext {
+    ///
+    /// Creates a context for arithmetic modulo m
+    ///
+    /// # Panics
+    /// Panics if m is even
+    pub fn new(m: &Natural) -> Self {
+        assert!(m.digits.first().is_some_and(|d| d & 1 == 1), "Montgomery arithmetic needs an odd modulus");
+        let n = m.digits.len();
+        let mut r_squared = vec!(0; 2 * n);
+        r_squared.push(1);
+        let r_squared = div(&Natural::from(r_squared), m).1;
+        let mut r = vec!(0; n);
+        r.push(1);
+        let one = div(&Natural::from(r), m).1;
+        Self {
+            modulus: m.clone(),
+            m_inv: neg_limb_inverse(m.digits[0]),
+            r_squared,
+            one,
+        }
+    }
+
+    /// The modulus of this context
+    pub fn modulus(&self) -> &Natural {
+        &self.modulus
+    }
+
+    /// The Montgomery form of one
+    pub fn one(&self) -> Natural {
+        self.one.clone()
+    }
+
+    ///
+    /// Converts a number into Montgomery form, first reducing it modulo m
+    pub fn to_montgomery(&self, x: &Natural) -> Natural {
+        let x = if *x < self.modulus { x.clone() } else { div(x, &self.modulus).1 };
+        self.mul(&x, &self.r_squared)
+    }
+
+    ///
+    /// Converts a number out of Montgomery form
+    pub fn from_montgomery(&self, x: &Natural) -> Natural {
+        let mut t = x.digits.clone();
+        Natural::from(redc(&mut t, &self.modulus.digits, self.m_inv))
+    }
+
+    ///
+    /// Multiplies two numbers in Montgomery form
+    pub fn mul(&self, a: &Natural, b: &Natural) -> Natural {
+        let mut t = Vec::with_capacity(2 * self.modulus.digits.len() + 1);
+        mul_into(&a.digits, &b.digits, &mut t);
+        Natural::from(redc(&mut t, &self.modulus.digits, self.m_inv))
+    }
+
+    ///
+    /// Squares a number in Montgomery form
+    pub fn square(&self, a: &Natural) -> Natural {
+        self.mul(a, a)
+    }
+
+    ///
+    /// Raises a number in Montgomery form to the power exp, giving the result in
+    /// Montgomery form
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::{Natural, MontgomeryContext};
+    ///
+    /// let context = MontgomeryContext::new(&Natural::from(497u64));
+    /// let x = context.pow(&context.to_montgomery(&Natural::from(4u64)), &Natural::from(13u64));
+    /// assert_eq!(context.from_montgomery(&x), Natural::from(445u64));
+    /// ```
+    pub fn pow(&self, a: &Natural, exp: &Natural) -> Natural {
+        window_pow(a, exp, self.one(), |x, y| self.mul(x, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let m = Natural::from(vec!(0x1234_5678_9abc_def1, 0xfeed, 3));
+        let context = MontgomeryContext::new(&m);
+        for x in [Natural::ZERO, Natural::from(1u64), Natural::from(vec!(5, 6, 2)), &m - &Natural::from(1u64)] {
+            assert_eq!(context.from_montgomery(&context.to_montgomery(&x)), x);
+        }
+        assert_eq!(context.from_montgomery(&context.one()), Natural::from(1u64));
+        assert_eq!(context.from_montgomery(&context.to_montgomery(&(&m + &Natural::from(7u64)))), Natural::from(7u64));
+    }
+
+    #[test]
+    fn test_mul_matches_division() {
+        let m = Natural::from(vec!(u64::MAX, u64::MAX, 1 << 40, 17));
+        let context = MontgomeryContext::new(&m);
+        let mut x = Natural::from(vec!(3, 1, 4, 1));
+        let mut y = Natural::from(vec!(5, 9, 2, 6));
+        for _ in 0..50 {
+            let expected = &(&x * &y) % &m;
+            let product = context.mul(&context.to_montgomery(&x), &context.to_montgomery(&y));
+            assert_eq!(context.from_montgomery(&product), expected);
+            assert_eq!(context.from_montgomery(&context.square(&context.to_montgomery(&x))), &(&x * &x) % &m);
+            y = x;
+            x = expected;
+        }
+    }
+
+    #[test]
+    fn test_pow() {
+        let p = Natural::from(u128::MAX >> 1);
+        let context = MontgomeryContext::new(&p);
+        let three = context.to_montgomery(&Natural::from(3u64));
+        assert_eq!(context.pow(&three, &(&p - &Natural::from(1u64))), context.one());
+        assert_eq!(context.pow(&three, &Natural::ZERO), context.one());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_even_modulus() {
+        MontgomeryContext::new(&Natural::from(10u64));
+    }
+}
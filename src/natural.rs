@@ -1,10 +1,8 @@
 use core::cmp::Ordering;
 use core::fmt;
-use core::convert::TryFrom;
-use core::iter::FromIterator;
 use core::ops::{Add, Sub, Mul, Div, Rem};
-use core::iter::Iterator;
-use crate::integer::Sign;
+use crate::integer::{Sign, Integer};
+use crate::radix::to_radix_digits;
 use crate::algorithms::{
     add, add_mut,
     sub_signed,
@@ -31,7 +29,7 @@ pub type Limb = u64;
 /// let n = Natural::from_string("123456789000000000");
 /// 
 /// // Create a number from a native Rust type
-/// let m = Natural::from(1234);
+/// let m = Natural::from(1234u64);
 /// 
 #[derive(Debug,PartialEq,Eq,Clone)]
 pub struct Natural {
@@ -41,28 +39,16 @@ pub struct Natural {
 
 impl From<Limb> for Natural {
     fn from(digit: Limb) -> Self {
+        // Zero is represented by an empty vector of digits
+        if digit == 0 {
+            return Self::ZERO;
+        }
         Self {
             digits: vec!(digit),
         }
     }
 }
 
-impl TryFrom<Natural> for u32 {
-    type Error = &'static str;
-
-    fn try_from(n: Natural) -> Result<u32, Self::Error> {
-        if n.digits.is_empty() {
-            Ok(0u32)
-        }
-        else if n.digits.len() == 1 {
-            Ok(n.digits[0] as u32) 
-        }
-        else {
-            Err("Error converting a Natural to unsigned integer")
-        }
-    }
-}
-
 impl From<Vec<Limb>> for Natural {
     fn from(digits: Vec<Limb>) -> Self {
         Self {
@@ -101,8 +87,8 @@ impl Sub for &Natural {
     fn sub(self, other: Self) -> Natural {
         match sub_signed(self, other) {
             (Sign::Positive, result) => result,
-            (Sign::Negative, _) => panic!("Tried to subtract larger natural from smaller natural. 
-                                                Maybe you meant to use the Integer type?"),
+            (Sign::Negative, _) => panic!("Tried to subtract larger natural from smaller natural. \
+                                           Maybe you meant to use checked_sub or sub_to_integer?"),
         }
     }
 }
@@ -170,7 +156,7 @@ impl Natural {
     /// 
     #[inline]
     pub fn sub(&self, other: &Self) -> Option<Self> {
-        let (sign, value) = sub_signed(&self, &other);
+        let (sign, value) = sub_signed(self, other);
         match sign {
             Sign::Negative => None,
             _ => Some(value)
@@ -186,10 +172,145 @@ impl Natural {
         Self::from(result)
     }
 
+    ///
+    /// Checked subtraction - returns None if other is larger than self
+    /// 
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    /// 
+    /// assert_eq!(Natural::from(5u64).checked_sub(&Natural::from(3u64)), Some(Natural::from(2u64)));
+    /// assert_eq!(Natural::from(3u64).checked_sub(&Natural::from(5u64)), None);
+    /// ```
+    #[inline]
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.sub(other)
+    }
+
+    ///
+    /// Saturating subtraction - returns zero if other is larger than self
+    /// 
+    #[inline]
+    pub fn saturating_sub(&self, other: &Self) -> Self {
+        self.sub(other).unwrap_or(Self::ZERO)
+    }
+
+    ///
+    /// Overflowing subtraction - returns the size of the difference along with
+    /// a boolean indicating whether the subtraction would have gone below zero
+    /// 
+    /// Unlike the primitive types there is no fixed width to wrap around, so the
+    /// value returned is |self - other| rather than a wrapped result.
+    /// 
+    #[inline]
+    pub fn overflowing_sub(&self, other: &Self) -> (Self, bool) {
+        let (sign, value) = sub_signed(self, other);
+        (value, sign == Sign::Negative)
+    }
+
+    ///
+    /// Absolute difference |self - other|
+    /// 
+    #[inline]
+    pub fn abs_diff(&self, other: &Self) -> Self {
+        sub_signed(self, other).1
+    }
+
+    ///
+    /// Subtraction which never fails, returning the signed result as an Integer
+    /// 
+    /// # Example
+    /// ```rust
+    /// use skewes::{Natural, Integer};
+    /// 
+    /// let z = Natural::from(3u64).sub_to_integer(&Natural::from(5u64));
+    /// assert_eq!(z, Integer::from_string("-2"));
+    /// ```
+    #[inline]
+    pub fn sub_to_integer(&self, other: &Self) -> Integer {
+        let (sign, value) = sub_signed(self, other);
+        Integer::from_sign_and_size(sign, value)
+    }
+
     /// Immutable division - allocates and stores result
+    /// 
+    /// Panics if other is zero
     #[inline]
     pub fn div(&self, other: &Self) -> (Self, Self) {
-        div(&self, &other)
+        div(self, other)
+    }
+
+    ///
+    /// Checked division - returns None if other is zero
+    /// 
+    #[inline]
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(d, _)| d)
+    }
+
+    ///
+    /// Checked remainder - returns None if other is zero
+    /// 
+    #[inline]
+    pub fn checked_rem(&self, other: &Self) -> Option<Self> {
+        self.checked_div_rem(other).map(|(_, r)| r)
+    }
+
+    ///
+    /// Checked division with remainder - returns None if other is zero
+    /// 
+    #[inline]
+    pub fn checked_div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            None
+        }
+        else {
+            Some(div(self, other))
+        }
+    }
+
+    ///
+    /// Returns true if this number is zero
+    /// 
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    ///
+    /// Returns the number of trailing zero bits, i.e. the largest k such that 2^k
+    /// divides this number, or None if the number is zero
+    /// 
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    /// 
+    /// assert_eq!(Natural::from(40u64).trailing_zeros(), Some(3));
+    /// assert_eq!(Natural::ZERO.trailing_zeros(), None);
+    /// ```
+    #[inline]
+    pub fn trailing_zeros(&self) -> Option<u64> {
+        self.digits.iter()
+            .position(|&d| d != 0)
+            .map(|i| 64 * i as u64 + self.digits[i].trailing_zeros() as u64)
+    }
+
+    ///
+    /// Returns the number of bits needed to represent this number, which is zero for zero
+    /// 
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    /// 
+    /// assert_eq!(Natural::from(255u64).bit_length(), 8);
+    /// assert_eq!(Natural::from(vec!(0, 1)).bit_length(), 65);
+    /// ```
+    #[inline]
+    pub fn bit_length(&self) -> u64 {
+        match self.digits.last() {
+            Some(msd) => 64 * self.digits.len() as u64 - msd.leading_zeros() as u64,
+            None => 0,
+        }
     }
 
 
@@ -198,7 +319,8 @@ impl Natural {
     /// 
     #[inline]
     pub fn inc(&mut self) {
-        let mut carry = false;
+        // Starts as true so that incrementing zero pushes a new digit
+        let mut carry = true;
         for digit in &mut self.digits {
             let (a, b) = digit.overflowing_add(1);
             *digit = a;
@@ -212,35 +334,50 @@ impl Natural {
         }
     }
 
-    // TODOs: change to potentially fail, chunk digits
+    ///
+    /// Decrements a number in-place
+    /// 
+    /// # Panics
+    /// Panics if the number is zero
+    /// 
+    #[inline]
+    pub fn dec(&mut self) {
+        assert!(!self.is_zero(), "Tried to decrement a natural which is zero");
+        for digit in &mut self.digits {
+            let (a, borrow) = digit.overflowing_sub(1);
+            *digit = a;
+            if !borrow {
+                break
+            }
+        }
+        if let Some(&0) = self.digits.last() {
+            self.digits.pop();
+        }
+    }
+
     ///
     /// Parse a string into a Natural
     /// 
-    /// The API for this function will likely change in the future,
-    /// allowing for errors while parsing
+    /// This is a convenience wrapper around `str::parse`, which should be used instead
+    /// when the input may not be a valid number.
+    /// 
+    /// # Panics
+    /// Panics if the string is not a valid natural number
     /// 
     pub fn from_string<S: Into<String>>(s: S) -> Self {
-        let mut n = Self::ZERO;
-        s.into().chars()
-            .for_each(|c| {
-                let d: u32 = c.to_digit(10).unwrap();
-                n = &(&n * &Natural::from(10)) + &Natural::from(d as Limb);
-            });
-        n
+        match s.into().parse() {
+            Ok(n) => n,
+            Err(e) => panic!("Failed to parse a Natural: {}", e),
+        }
     }
 }
 
 impl fmt::Display for Natural {
+    /// Writes the number in decimal, honouring the width, fill, alignment,
+    /// sign and zero-padding flags of the formatter
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut n = self.clone();
-        let mut s = Vec::<char>::new();
-        while n != Natural::ZERO {
-            let (d, r) = div(&n, &Natural::from(10));
-            let rem = u32::try_from(r).unwrap(); // Guaranteed to be correct because remainder < 10
-            s.push(std::char::from_digit(rem, 10).unwrap());
-            n = d;
-        }
-        write!(f, "{}", String::from_iter(s.iter().rev()))
+        let digits = to_radix_digits(self, 10, false);
+        f.pad_integral(true, "", core::str::from_utf8(&digits).unwrap())
     }
 }
 
@@ -261,15 +398,15 @@ mod tests {
 
     // Calculate large factorials; good overall sense-check
     fn factorial(n: Natural) -> Natural {
-        if n == Natural::from(1) {
-            Natural::from(1)
+        if n == Natural::from(1u64) {
+            Natural::from(1u64)
         }
         else {
-            let mut acc = Natural::from(1);
+            let mut acc = Natural::from(1u64);
             let mut m = n;
             while m > Natural::ZERO {
                 acc = acc.mul(&m);
-                m = m.sub(&Natural::from(1)).unwrap();
+                m = m.sub(&Natural::from(1u64)).unwrap();
             }
             acc
         }
@@ -277,7 +414,7 @@ mod tests {
 
     #[test]
     fn can_create_from_u64() {
-        let a = Natural::from(42);
+        let a = Natural::from(42u64);
         let mut b = Natural::ZERO;
         b.digits.push(42);
         assert_eq!(a, b);
@@ -285,9 +422,9 @@ mod tests {
 
     #[test]
     fn can_add_two_one_digit_numbers_without_carry() {
-        let a = Natural::from(21);
-        let b = Natural::from(42);
-        let c = Natural::from(63);
+        let a = Natural::from(21u64);
+        let b = Natural::from(42u64);
+        let c = Natural::from(63u64);
         assert_plus_identity!(a, b => c);
     }
 
@@ -368,6 +505,47 @@ mod tests {
         assert_eq!(a.sub(&b), Some(c));
     }
 
+    #[test]
+    fn test_checked_saturating_sub() {
+        let a = Natural::from(vec!(1, 2));
+        let b = Natural::from(vec!(3, 2));
+        assert_eq!(a.checked_sub(&b), None);
+        assert_eq!(b.checked_sub(&a), Some(Natural::from(2u64)));
+        assert_eq!(a.saturating_sub(&b), Natural::ZERO);
+        assert_eq!(b.saturating_sub(&a), Natural::from(2u64));
+        assert_eq!(a.overflowing_sub(&b), (Natural::from(2u64), true));
+        assert_eq!(b.overflowing_sub(&a), (Natural::from(2u64), false));
+    }
+
+    #[test]
+    fn test_abs_diff_and_sub_to_integer() {
+        let a = Natural::from(100u64);
+        let b = Natural::from(400u64);
+        assert_eq!(a.abs_diff(&b), Natural::from(300u64));
+        assert_eq!(b.abs_diff(&a), Natural::from(300u64));
+        assert_eq!(a.sub_to_integer(&b), Integer::from_string("-300"));
+        assert_eq!(b.sub_to_integer(&a), Integer::from_string("300"));
+        assert_eq!(a.sub_to_integer(&a), Integer::from(Natural::ZERO));
+    }
+
+    #[test]
+    fn test_checked_div_rem() {
+        let a = Natural::from(vec!(7, 5, 3));
+        let b = Natural::from(vec!(2, 9));
+        assert_eq!(a.checked_div(&Natural::ZERO), None);
+        assert_eq!(a.checked_rem(&Natural::from(0u64)), None);
+        let d = a.checked_div(&b).unwrap();
+        let r = a.checked_rem(&b).unwrap();
+        assert_eq!(&(&d * &b) + &r, a);
+        assert!(r < b);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sub_operator_panics_below_zero() {
+        let _ = &Natural::from(1u64) - &Natural::from(2u64);
+    }
+
     #[test]
     fn print_numbers () {
         let a = Natural::from(vec!(3));
@@ -380,13 +558,13 @@ mod tests {
     #[test]
     fn can_parse_integers () {
         let n = Natural::from_string("1234");
-        let m = Natural::from(1234);
+        let m = Natural::from(1234u64);
         assert_eq!(n, m);
     }
 
     #[test]
     fn can_calculate_big_factorials() {
-        let n = Natural::from(100);
+        let n = Natural::from(100u64);
         let ans = factorial(n);
         assert_eq!(ans, Natural::from_string(
             "93326215443944152681699238856266700490715968264381621468592963895217599993229915608941463976156518286253697920827223758251185210916864000000000000000000000000"
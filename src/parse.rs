@@ -0,0 +1,276 @@
+// Parsing of Natural and Integer from strings
+//
+// The accepted syntax is, in order:
+//  - optional leading and trailing ASCII whitespace, which is ignored
+//  - an optional sign, '+' or '-' (only '+' for a Natural)
+//  - an optional radix prefix: 0x or 0X (hexadecimal), 0o or 0O (octal), 0b or 0B (binary)
+//  - one or more digits, where single '_' separators may appear between two digits
+//
+// Anything else, including whitespace between the sign and the digits, is an error.
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::natural::{Natural, Limb};
+use crate::integer::{Integer, Sign};
+use crate::algorithms::mul_add_single_digit_mut;
+
+///
+/// The kind of error encountered while parsing a number
+///
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum ParseErrorKind {
+    /// There were no digits to parse
+    Empty,
+    /// A character which is not a digit in the radix being parsed
+    InvalidDigit,
+    /// A '_' separator which is not between two digits
+    MisplacedSeparator,
+    /// A minus sign where a Natural was being parsed
+    Negative,
+}
+
+///
+/// The error returned when parsing a Natural or Integer fails
+///
+/// The syntax accepted is an optional sign ('+' or '-', the latter only for an Integer),
+/// an optional radix prefix (0x, 0o or 0b, case-insensitive), then one or more digits
+/// with single '_' separators allowed between digits. Surrounding ASCII whitespace is
+/// ignored, but whitespace anywhere else is an error.
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, ParseErrorKind};
+///
+/// let err = "12a4".parse::<Natural>().unwrap_err();
+/// assert_eq!(err.position(), 2);
+/// assert_eq!(err.kind(), ParseErrorKind::InvalidDigit);
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ParseError {
+    position: usize,
+    kind: ParseErrorKind,
+}
+
+impl ParseError {
+    pub(crate) fn new(position: usize, kind: ParseErrorKind) -> Self {
+        Self {
+            position,
+            kind,
+        }
+    }
+
+    /// The byte offset in the input at which the error was found
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// What went wrong
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self.kind {
+            ParseErrorKind::Empty => "no digits found",
+            ParseErrorKind::InvalidDigit => "invalid digit found",
+            ParseErrorKind::MisplacedSeparator => "misplaced digit separator found",
+            ParseErrorKind::Negative => "negative sign found when parsing a natural number",
+        };
+        write!(f, "{} at position {}", description, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Returns the sign and the remaining input, with offset tracking the position in the
+/// original string
+fn split_sign(s: &str, offset: usize) -> (Sign, &str, usize) {
+    match s.as_bytes().first() {
+        Some(b'+') => (Sign::Positive, &s[1..], offset + 1),
+        Some(b'-') => (Sign::Negative, &s[1..], offset + 1),
+        _ => (Sign::Positive, s, offset),
+    }
+}
+
+/// Returns the radix indicated by any prefix and the remaining input
+fn split_prefix(s: &str, offset: usize) -> (u32, &str, usize) {
+    let bytes = s.as_bytes();
+    if bytes.len() >= 2 && bytes[0] == b'0' {
+        let radix = match bytes[1] {
+            b'x' | b'X' => 16,
+            b'o' | b'O' => 8,
+            b'b' | b'B' => 2,
+            _ => return (10, s, offset),
+        };
+        (radix, &s[2..], offset + 2)
+    }
+    else {
+        (10, s, offset)
+    }
+}
+
+/// Trims surrounding ASCII whitespace, returning the trimmed string and its offset in the original
+fn trim(s: &str) -> (&str, usize) {
+    let trimmed = s.trim_start_matches(|c: char| c.is_ascii_whitespace());
+    let offset = s.len() - trimmed.len();
+    (trimmed.trim_end_matches(|c: char| c.is_ascii_whitespace()), offset)
+}
+
+/// Parses a string of digits with optional separators in the given radix
+///
+/// Digits are accumulated into chunks which fit into a single limb before being
+/// added into the result, so that the big multiplication is done once per chunk.
+pub(crate) fn parse_digits(s: &str, offset: usize, radix: u32) -> Result<Natural, ParseError> {
+    debug_assert!((2..=36).contains(&radix));
+    if s.is_empty() {
+        return Err(ParseError::new(offset, ParseErrorKind::Empty));
+    }
+
+    // Find the largest number of digits whose value always fits in a limb
+    let mut chunk_size = 0;
+    let mut chunk_multiplier: Limb = 1;
+    while let Some(m) = chunk_multiplier.checked_mul(radix as Limb) {
+        chunk_multiplier = m;
+        chunk_size += 1;
+    }
+
+    let mut digits = Vec::new();
+    let mut chunk: Limb = 0;
+    let mut chunk_len = 0;
+    let mut previous_was_digit = false;
+    let bytes = s.as_bytes();
+    for (i, ch) in s.char_indices() {
+        if ch == '_' {
+            let next_is_digit = bytes.get(i + 1).is_some_and(|&b| (b as char).is_digit(radix));
+            if !previous_was_digit || !next_is_digit {
+                return Err(ParseError::new(offset + i, ParseErrorKind::MisplacedSeparator));
+            }
+            previous_was_digit = false;
+            continue;
+        }
+        let d = ch.to_digit(radix)
+            .ok_or_else(|| ParseError::new(offset + i, ParseErrorKind::InvalidDigit))?;
+        chunk = chunk * radix as Limb + d as Limb;
+        chunk_len += 1;
+        if chunk_len == chunk_size {
+            mul_add_single_digit_mut(&mut digits, chunk_multiplier, chunk);
+            chunk = 0;
+            chunk_len = 0;
+        }
+        previous_was_digit = true;
+    }
+    if chunk_len > 0 {
+        mul_add_single_digit_mut(&mut digits, (radix as Limb).pow(chunk_len), chunk);
+    }
+    Ok(Natural::from(digits))
+}
+
+/// Parses a signed number, detecting the radix from any prefix
+fn parse_signed(s: &str) -> Result<(Sign, Natural, usize), ParseError> {
+    let (s, offset) = trim(s);
+    let (sign, s, sign_end) = split_sign(s, offset);
+    let (radix, s, digits_start) = split_prefix(s, sign_end);
+    let size = parse_digits(s, digits_start, radix)?;
+    Ok((sign, size, offset))
+}
+
+impl FromStr for Natural {
+    type Err = ParseError;
+
+    ///
+    /// Parses a natural number, see `ParseError` for the syntax accepted
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// let n: Natural = "1_000_000".parse().unwrap();
+    /// assert_eq!(n, Natural::from(1000000u64));
+    /// assert_eq!("0xff".parse::<Natural>().unwrap(), Natural::from(255u64));
+    /// assert!("-1".parse::<Natural>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sign, size, offset) = parse_signed(s)?;
+        match sign {
+            Sign::Positive => Ok(size),
+            Sign::Negative => Err(ParseError::new(offset, ParseErrorKind::Negative)),
+        }
+    }
+}
+
+impl FromStr for Integer {
+    type Err = ParseError;
+
+    ///
+    /// Parses an integer, see `ParseError` for the syntax accepted
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Integer;
+    ///
+    /// let z: Integer = " -0b1010 ".parse().unwrap();
+    /// assert_eq!(z, Integer::from(-10));
+    /// assert!("--5".parse::<Integer>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sign, size, _) = parse_signed(s)?;
+        Ok(Integer::from_sign_and_size(sign, size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn err(position: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError::new(position, kind)
+    }
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!("0".parse::<Natural>(), Ok(Natural::ZERO));
+        assert_eq!("18446744073709551616".parse::<Natural>(), Ok(Natural::from(vec!(0, 1))));
+        assert_eq!("340282366920938463463374607431768211455".parse::<Natural>(),
+                   Ok(Natural::from(u128::MAX)));
+        assert_eq!("+42".parse::<Natural>(), Ok(Natural::from(42u64)));
+        assert_eq!("  42\n".parse::<Natural>(), Ok(Natural::from(42u64)));
+    }
+
+    #[test]
+    fn test_parse_prefixes_and_separators() {
+        assert_eq!("0x_ff".parse::<Natural>(), Err(err(2, ParseErrorKind::MisplacedSeparator)));
+        assert_eq!("0xDEAD_beef".parse::<Natural>(), Ok(Natural::from(0xdead_beefu64)));
+        assert_eq!("0o777".parse::<Natural>(), Ok(Natural::from(0o777u64)));
+        assert_eq!("0B1_0_1".parse::<Natural>(), Ok(Natural::from(5u64)));
+        assert_eq!("1__0".parse::<Natural>(), Err(err(1, ParseErrorKind::MisplacedSeparator)));
+        assert_eq!("10_".parse::<Natural>(), Err(err(2, ParseErrorKind::MisplacedSeparator)));
+        assert_eq!("_10".parse::<Natural>(), Err(err(0, ParseErrorKind::MisplacedSeparator)));
+        assert_eq!("0b102".parse::<Natural>(), Err(err(4, ParseErrorKind::InvalidDigit)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<Natural>(), Err(err(0, ParseErrorKind::Empty)));
+        assert_eq!("  ".parse::<Natural>(), Err(err(2, ParseErrorKind::Empty)));
+        assert_eq!("0x".parse::<Natural>(), Err(err(2, ParseErrorKind::Empty)));
+        assert_eq!("abc".parse::<Natural>(), Err(err(0, ParseErrorKind::InvalidDigit)));
+        assert_eq!(" -5".parse::<Natural>(), Err(err(1, ParseErrorKind::Negative)));
+        assert_eq!("1 2".parse::<Natural>(), Err(err(1, ParseErrorKind::InvalidDigit)));
+        assert_eq!("--5".parse::<Integer>(), Err(err(1, ParseErrorKind::InvalidDigit)));
+        assert_eq!("- 5".parse::<Integer>(), Err(err(1, ParseErrorKind::InvalidDigit)));
+        assert_eq!("-".parse::<Integer>(), Err(err(1, ParseErrorKind::Empty)));
+        assert_eq!("1é".parse::<Integer>(), Err(err(1, ParseErrorKind::InvalidDigit)));
+    }
+
+    #[test]
+    fn test_parse_integer() {
+        assert_eq!("-5".parse::<Integer>(), Ok(Integer::from(-5)));
+        assert_eq!("-0".parse::<Integer>(), Ok(Integer::from(0)));
+        assert_eq!("-0x10".parse::<Integer>(), Ok(Integer::from(-16)));
+        assert_eq!("+170141183460469231731687303715884105727".parse::<Integer>(),
+                   Ok(Integer::from(i128::MAX)));
+    }
+}
@@ -0,0 +1,150 @@
+// Exponentiation of Natural and Integer
+
+use core::convert::TryFrom;
+
+use crate::natural::Natural;
+use crate::integer::{Integer, Sign};
+use crate::algorithms::pow;
+
+impl Natural {
+    ///
+    /// Raises this number to the power exp
+    ///
+    /// Zero to the power zero is one.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(3u64).pow(4), Natural::from(81u64));
+    /// assert_eq!(Natural::from(2u64).pow(64), Natural::from(vec!(0, 1)));
+    /// ```
+    pub fn pow(&self, exp: u64) -> Natural {
+        Natural::from(pow(&self.digits, exp))
+    }
+
+    ///
+    /// Raises this number to a power given as a Natural
+    ///
+    /// # Panics
+    /// Panics if the result would be too large to represent, which is the case for any
+    /// base other than zero or one when the exponent does not fit in a u64
+    pub fn pow_natural(&self, exp: &Natural) -> Natural {
+        if exp.is_zero() {
+            return Natural::from(1u64);
+        }
+        if self.is_zero() || *self == Natural::from(1u64) {
+            return self.clone();
+        }
+        let exp = u64::try_from(exp).expect("Result of exponentiation is too large to represent");
+        self.pow(exp)
+    }
+
+    ///
+    /// Raises this number to the power exp, returning None if the result would
+    /// have more than max_bits bits
+    ///
+    /// The size of the result is estimated before any multiplication is done, so
+    /// results which are far too large are refused cheaply.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(255u64).checked_pow(2, 16), Some(Natural::from(65025u64)));
+    /// assert_eq!(Natural::from(256u64).checked_pow(2, 16), None);
+    /// ```
+    pub fn checked_pow(&self, exp: u64, max_bits: u64) -> Option<Natural> {
+        // A b-bit number raised to the power e has between (b - 1)e + 1 and be bits
+        let bits = self.bit_length() as u128;
+        if exp > 0 && bits > 0 && (bits - 1) * exp as u128 + 1 > max_bits as u128 {
+            return None;
+        }
+        let result = self.pow(exp);
+        if result.bit_length() > max_bits {
+            None
+        }
+        else {
+            Some(result)
+        }
+    }
+}
+
+impl Integer {
+    /// Returns the sign of self raised to the power exp
+    fn pow_sign(&self, exp: u64) -> Sign {
+        if exp % 2 == 1 { self.sign } else { Sign::Positive }
+    }
+
+    ///
+    /// Raises this number to the power exp
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Integer;
+    ///
+    /// assert_eq!(Integer::from(-3).pow(3), Integer::from(-27));
+    /// assert_eq!(Integer::from(-3).pow(2), Integer::from(9));
+    /// ```
+    pub fn pow(&self, exp: u64) -> Integer {
+        Integer::from_sign_and_size(self.pow_sign(exp), self.size.pow(exp))
+    }
+
+    ///
+    /// Raises this number to the power exp, returning None if the magnitude of
+    /// the result would have more than max_bits bits
+    pub fn checked_pow(&self, exp: u64, max_bits: u64) -> Option<Integer> {
+        self.size.checked_pow(exp, max_bits)
+            .map(|size| Integer::from_sign_and_size(self.pow_sign(exp), size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pow_matches_repeated_multiplication() {
+        let base = Natural::from(vec!(0x1234_5678_9abc_def1, 3));
+        let mut expected = Natural::from(1u64);
+        for exp in 0..20 {
+            assert_eq!(base.pow(exp), expected);
+            expected = &expected * &base;
+        }
+    }
+
+    #[test]
+    fn test_pow_with_factors_of_two() {
+        let base = Natural::from(96u64); // 3 * 2^5
+        assert_eq!(base.pow(13), &Natural::from(3u64).pow(13) * &Natural::from(2u64).pow(65));
+        assert_eq!(Natural::from(1u64 << 63).pow(3), Natural::from(vec!(0, 0, 1 << 61)));
+    }
+
+    #[test]
+    fn test_pow_natural() {
+        let huge = Natural::from(vec!(0, 1));
+        assert_eq!(Natural::ZERO.pow_natural(&huge), Natural::ZERO);
+        assert_eq!(Natural::from(1u64).pow_natural(&huge), Natural::from(1u64));
+        assert_eq!(Natural::from(5u64).pow_natural(&Natural::ZERO), Natural::from(1u64));
+        assert_eq!(Natural::from(5u64).pow_natural(&Natural::from(3u64)), Natural::from(125u64));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pow_natural_too_large() {
+        Natural::from(2u64).pow_natural(&Natural::from(vec!(0, 1)));
+    }
+
+    #[test]
+    fn test_checked_pow() {
+        let ten = Natural::from(10u64);
+        assert_eq!(ten.checked_pow(19, 64), Some(Natural::from(10_000_000_000_000_000_000u64)));
+        assert_eq!(ten.checked_pow(20, 64), None);
+        assert_eq!(ten.checked_pow(u64::MAX, 1000), None);
+        assert_eq!(Natural::from(1u64).checked_pow(u64::MAX, 1), Some(Natural::from(1u64)));
+        assert_eq!(Natural::ZERO.checked_pow(0, 0), None);
+        assert_eq!(Natural::ZERO.checked_pow(5, 0), Some(Natural::ZERO));
+        assert_eq!(Integer::from(-2).checked_pow(3, 3), None);
+        assert_eq!(Integer::from(-2).checked_pow(3, 4), Some(Integer::from(-8)));
+    }
+}
@@ -0,0 +1,332 @@
+// Probabilistic primality testing of Natural numbers
+//
+// Candidates are first trial divided by the primes below 1000. Numbers below 2^64
+// are then settled by Miller-Rabin with a fixed set of bases known to have no
+// strong pseudoprimes in that range. Larger numbers take the Baillie-PSW test, a
+// strong base-2 test followed by a strong Lucas test, for which no counterexample
+// is known, and optionally further Miller-Rabin rounds.
+
+use crate::natural::Natural;
+use crate::montgomery::MontgomeryContext;
+use crate::algorithms::div_by_single_digit;
+use crate::jacobi::jacobi_i64;
+
+///
+/// The result of a primality test
+///
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Primality {
+    /// The number is certainly prime
+    Prime,
+    /// The number passed every test and is prime with overwhelming probability
+    ProbablyPrime,
+    /// The number is certainly composite
+    Composite,
+}
+
+/// Returns an array of the primes below the length of the array, followed by zeros
+const fn sieve<const N: usize>() -> [u64; N] {
+    let mut composite = [false; N];
+    let mut primes = [0; N];
+    let mut count = 0;
+    let mut i = 2;
+    while i < N {
+        if !composite[i] {
+            primes[count] = i as u64;
+            count += 1;
+            let mut j = i * i;
+            while j < N {
+                composite[j] = true;
+                j += i;
+            }
+        }
+        i += 1;
+    }
+    primes
+}
+
+const SIEVE: [u64; 1000] = sieve();
+
+/// The number of primes below 1000
+const SMALL_PRIME_COUNT: usize = 168;
+
+/// The primes below 1000, used for trial division
+pub(crate) const SMALL_PRIMES: &[u64] = SIEVE.split_at(SMALL_PRIME_COUNT).0;
+
+/// Returns ab mod m
+#[inline]
+pub(crate) fn mul_mod_u64(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+/// Returns b^e mod m
+pub(crate) fn pow_mod_u64(mut b: u64, mut e: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    b %= m;
+    while e > 0 {
+        if e & 1 == 1 {
+            result = mul_mod_u64(result, b, m);
+        }
+        b = mul_mod_u64(b, b, m);
+        e >>= 1;
+    }
+    result
+}
+
+/// Whether an odd n > 2 is a strong probable prime to the base a
+fn strong_probable_prime_u64(n: u64, a: u64) -> bool {
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    let mut x = pow_mod_u64(a, d, n);
+    if x == 1 || x == n - 1 || x == 0 {
+        // A base which is a multiple of n says nothing
+        return true;
+    }
+    for _ in 1..s {
+        x = mul_mod_u64(x, x, n);
+        if x == n - 1 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Whether a u64 is prime, by trial division and deterministic Miller-Rabin
+pub(crate) fn is_prime_u64(n: u64) -> bool {
+    for &p in SMALL_PRIMES {
+        if n == p {
+            return true;
+        }
+        if n.is_multiple_of(p) {
+            return false;
+        }
+    }
+    if n < 1000 * 1000 {
+        return n >= 2;
+    }
+    // No composite below 2^64 is a strong probable prime to all of these bases
+    // (Sinclair, 2011)
+    [2, 325, 9375, 28178, 450775, 9780504, 1795265022]
+        .iter()
+        .all(|&a| strong_probable_prime_u64(n, a))
+}
+
+/// Arithmetic modulo an odd number in Montgomery form
+struct Field<'a> {
+    context: &'a MontgomeryContext,
+    n: &'a Natural,
+}
+
+impl Field<'_> {
+    fn add(&self, a: &Natural, b: &Natural) -> Natural {
+        let sum = a + b;
+        if sum >= *self.n { &sum - self.n } else { sum }
+    }
+
+    fn sub(&self, a: &Natural, b: &Natural) -> Natural {
+        if a >= b { a - b } else { &(a + self.n) - b }
+    }
+
+    fn half(&self, a: &Natural) -> Natural {
+        let a = if a.digits.first().is_some_and(|d| d & 1 == 1) { a + self.n } else { a.clone() };
+        a.div(&Natural::from(2u64)).0
+    }
+
+    fn mul(&self, a: &Natural, b: &Natural) -> Natural {
+        self.context.mul(a, b)
+    }
+}
+
+impl Natural {
+    /// Returns the remainder of this number divided by a single digit
+    pub(crate) fn rem_digit(&self, d: u64) -> u64 {
+        div_by_single_digit(&self.digits, d).1
+    }
+
+    /// Whether an odd number above 2 is a strong probable prime to the base a,
+    /// i.e. with n - 1 = d 2^s for odd d, either a^d = 1 or a^(d 2^r) = -1 (mod n)
+    /// for some r < s
+    fn is_strong_probable_prime(&self, context: &MontgomeryContext, a: &Natural) -> bool {
+        let n_minus_1 = self - &Natural::from(1u64);
+        let s = n_minus_1.trailing_zeros().unwrap();
+        let d = n_minus_1.div(&Natural::from(2u64).pow(s)).0;
+        let one = context.one();
+        let minus_one = context.to_montgomery(&n_minus_1);
+        let mut x = context.pow(&context.to_montgomery(a), &d);
+        if x == one || x == minus_one || x.is_zero() {
+            return true;
+        }
+        for _ in 1..s {
+            x = context.square(&x);
+            if x == minus_one {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether an odd non-square number above 2 with no small factors is a strong
+    /// Lucas probable prime, with parameters chosen by Selfridge's method
+    fn is_strong_lucas_probable_prime(&self, context: &MontgomeryContext) -> bool {
+        // Find the first D in 5, -7, 9, -11, ... with (D/n) = -1, which exists
+        // because n is not a square
+        let mut d = 5i64;
+        while jacobi_i64(d, self) != -1 {
+            d = if d > 0 { -(d + 2) } else { -d + 2 };
+        }
+        let field = Field { context, n: self };
+        let to_field = |x: i64| {
+            let m = context.to_montgomery(&Natural::from(x.unsigned_abs()));
+            if x < 0 { field.sub(&Natural::ZERO, &m) } else { m }
+        };
+        let p = context.one();
+        let q = to_field((1 - d) / 4);
+        let d = to_field(d);
+
+        // n + 1 = k 2^s with k odd
+        let n_plus_1 = self + &Natural::from(1u64);
+        let s = n_plus_1.trailing_zeros().unwrap();
+        let k = n_plus_1.div(&Natural::from(2u64).pow(s)).0;
+
+        // Walk the bits of k from the top, keeping U_j, V_j and Q^j
+        let mut u = p.clone();
+        let mut v = p.clone();
+        let mut q_j = q.clone();
+        for i in (0..k.bit_length() - 1).rev() {
+            // Doubling: U_2j = U_j V_j, V_2j = V_j^2 - 2Q^j
+            u = field.mul(&u, &v);
+            v = field.sub(&field.mul(&v, &v), &field.add(&q_j, &q_j));
+            q_j = field.mul(&q_j, &q_j);
+            if (k.digits[(i / 64) as usize] >> (i % 64)) & 1 == 1 {
+                // Incrementing: U_j+1 = (PU_j + V_j) / 2, V_j+1 = (DU_j + PV_j) / 2
+                let (pu, pv) = (field.mul(&p, &u), field.mul(&p, &v));
+                let next_u = field.half(&field.add(&pu, &v));
+                v = field.half(&field.add(&field.mul(&d, &u), &pv));
+                u = next_u;
+                q_j = field.mul(&q_j, &q);
+            }
+        }
+        if u.is_zero() || v.is_zero() {
+            return true;
+        }
+        for _ in 1..s {
+            v = field.sub(&field.mul(&v, &v), &field.add(&q_j, &q_j));
+            if v.is_zero() {
+                return true;
+            }
+            q_j = field.mul(&q_j, &q_j);
+        }
+        false
+    }
+
+    ///
+    /// Tests whether this number is prime
+    ///
+    /// Numbers below 2^64 are always classified exactly. Larger numbers which pass the
+    /// Baillie-PSW test are then given `rounds` further Miller-Rabin tests, with
+    /// bases drawn from a pseudo-random sequence seeded by the number itself, and
+    /// reported as probably prime if they pass those too.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::{Natural, Primality};
+    ///
+    /// assert_eq!(Natural::from(97u64).is_probable_prime(0), Primality::Prime);
+    /// assert_eq!(Natural::from(561u64).is_probable_prime(0), Primality::Composite);
+    /// let m127 = Natural::from(u128::MAX >> 1);
+    /// assert_eq!(m127.is_probable_prime(5), Primality::ProbablyPrime);
+    /// ```
+    pub fn is_probable_prime(&self, rounds: u32) -> Primality {
+        if self.digits.len() <= 1 {
+            let n = self.digits.first().copied().unwrap_or(0);
+            return if is_prime_u64(n) { Primality::Prime } else { Primality::Composite };
+        }
+        if SMALL_PRIMES.iter().any(|&p| self.rem_digit(p) == 0) {
+            return Primality::Composite;
+        }
+        let context = MontgomeryContext::new(self);
+        if !self.is_strong_probable_prime(&context, &Natural::from(2u64)) {
+            return Primality::Composite;
+        }
+        // The Lucas parameter search never ends for a square
+        if self.is_perfect_square() || !self.is_strong_lucas_probable_prime(&context) {
+            return Primality::Composite;
+        }
+        let mut state = self.digits[0] | 1;
+        let n_minus_3 = self - &Natural::from(3u64);
+        for _ in 0..rounds {
+            // A base in [2, n - 2]
+            let digits = (0..self.digits.len()).map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            }).collect::<Vec<_>>();
+            let base = &(&Natural::from(digits) % &n_minus_3) + &Natural::from(2u64);
+            if !self.is_strong_probable_prime(&context, &base) {
+                return Primality::Composite;
+            }
+        }
+        Primality::ProbablyPrime
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_primes() {
+        assert_eq!(SMALL_PRIMES.len(), 168);
+        assert_eq!(SMALL_PRIMES[..5], [2, 3, 5, 7, 11]);
+        assert_eq!(SMALL_PRIMES[167], 997);
+    }
+
+    #[test]
+    fn test_is_prime_u64_matches_trial_division() {
+        let is_prime = |n: u64| n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d));
+        for n in (0..5000).chain(1_000_000..1_002_000) {
+            assert_eq!(is_prime_u64(n), is_prime(n), "{}", n);
+        }
+        // Strong pseudoprimes to several of the smallest prime bases
+        for &n in &[2047u64, 1373653, 25326001, 3215031751, 2152302898747, 3474749660383, 341550071728321, 3825123056546413051] {
+            assert!(!is_prime_u64(n), "{}", n);
+        }
+        assert!(is_prime_u64(18446744073709551557));
+        assert!(!is_prime_u64(u64::MAX));
+    }
+
+    #[test]
+    fn test_large_primes_and_composites() {
+        let m127 = Natural::from(u128::MAX >> 1);
+        assert_eq!(m127.is_probable_prime(0), Primality::ProbablyPrime);
+        let m61 = Natural::from((1u64 << 61) - 1);
+        assert_eq!((&m127 * &m61).is_probable_prime(10), Primality::Composite);
+        assert_eq!((&m127 * &m127).is_probable_prime(0), Primality::Composite);
+        // 2^521 - 1 is prime, 2^523 - 1 is not
+        let one = Natural::from(1u64);
+        assert_eq!((&Natural::from(2u64).pow(521) - &one).is_probable_prime(2), Primality::ProbablyPrime);
+        assert_eq!((&Natural::from(2u64).pow(523) - &one).is_probable_prime(0), Primality::Composite);
+        assert_eq!(Natural::ZERO.is_probable_prime(0), Primality::Composite);
+        assert_eq!(Natural::from(1u64).is_probable_prime(0), Primality::Composite);
+        assert_eq!(Natural::from(2u64).is_probable_prime(0), Primality::Prime);
+    }
+
+    #[test]
+    fn test_pseudoprimes_fail_the_other_test() {
+        // Strong pseudoprimes to base 2 are not strong Lucas probable primes
+        for &n in &[2047u64, 3277, 4033, 4681, 8321, 15841, 29341, 42799, 49141, 52633, 65281, 74665, 80581] {
+            let n = Natural::from(n);
+            let context = MontgomeryContext::new(&n);
+            assert!(n.is_strong_probable_prime(&context, &Natural::from(2u64)), "{}", n);
+            assert!(!n.is_strong_lucas_probable_prime(&context), "{}", n);
+        }
+        // and strong Lucas pseudoprimes are not strong pseudoprimes to base 2
+        for &n in &[5459u64, 5777, 10877, 16109, 18971, 22499, 24569, 25199, 40309, 58519] {
+            let n = Natural::from(n);
+            let context = MontgomeryContext::new(&n);
+            assert!(n.is_strong_lucas_probable_prime(&context), "{}", n);
+            assert!(!n.is_strong_probable_prime(&context, &Natural::from(2u64)), "{}", n);
+        }
+    }
+}
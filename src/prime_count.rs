@@ -0,0 +1,424 @@
+// The prime counting function, by the combinatorial method of Meissel, Lehmer,
+// Lagarias, Miller and Odlyzko, with the refinements of Deleglise and Rivat
+//
+// With y at least the cube root of x and a = pi(y),
+//
+//     pi(x) = phi(x, a) + a - 1 - P2(x, a)
+//
+// where phi(x, b) counts the numbers up to x with no prime factor among the first b
+// primes, and P2(x, a) counts the numbers up to x which are the product of two primes
+// above y. P2 is a sum of pi(x / p) over the primes p in (y, sqrt(x)], which all lie
+// below z = x / y and are counted with a segmented sieve.
+//
+// Expanding phi(x, a) with phi(x, b) = phi(x, b - 1) - phi(x / p_b, b - 1) gives a tree
+// whose leaves are the terms mu(n) phi(x / n, b). Stopping at n <= y and at the first
+// c primes leaves the ordinary leaves, mu(n) phi(x / n, c) for squarefree n <= y with
+// no prime factor among the first c, which are summed directly using the period of
+// phi(n, c). The special leaves are -mu(m) phi(x / (m p_{b+1}), b) for m <= y < m p_{b+1}
+// with every prime factor of m above p_{b+1}. When p_{b+1} is above the fourth root of
+// x, m must be a prime q, and phi(x / (p q), b) is either 1 or pi(x / (p q)) - b + 1,
+// which can be looked up in a table while x / (p q) is below y. The remaining leaves lie
+// below z, where phi(u, b) is found by sieving [1, z) a segment at a time, crossing off
+// the primes in order and counting the survivors with a count for each block of bits.
+
+use core::convert::TryFrom;
+
+use crate::natural::Natural;
+use crate::sieve::SegmentedSieve;
+
+/// Numbers below this are counted by sieving
+const SIEVE_BELOW: u64 = 1 << 24;
+
+/// The number of leading primes whose multiples are removed using the period of phi
+const C: usize = 6;
+
+/// The product of the first C primes
+const PRIMORIAL: u64 = 2 * 3 * 5 * 7 * 11 * 13;
+
+/// The number of bits sieved at once
+const SEGMENT_BITS: u64 = 1 << 19;
+
+/// The number of words in each block of the sieve with its own count of survivors
+const BLOCK_WORDS: usize = 16;
+
+/// Returns the floor of the kth root of n
+fn root_u64(n: u64, k: u64) -> u64 {
+    u64::try_from(&Natural::from(n).root(k)).unwrap()
+}
+
+/// The primes and prime counting function up to a bound, and the numbers which can
+/// appear in leaves
+struct Tables {
+    /// The primes, counting from 1, so that primes[k] is the kth prime
+    primes: Vec<u64>,
+    pi: Vec<u32>,
+    /// The squarefree numbers with no prime factor among the first C primes, in order
+    squarefree: Vec<u32>,
+    /// The least prime factor of each of these, negated if it has an odd number of
+    /// prime factors, taking that of 1 to be infinite
+    signed_lpf: Vec<i32>,
+}
+
+impl Tables {
+    /// Builds the tables up to and including n, with a linear sieve
+    fn new(n: u64) -> Self {
+        let n = n as usize;
+        let mut primes = vec!(1);
+        let mut lpf = vec!(0u32; n + 1);
+        let mut mu = vec!(0i8; n + 1);
+        let mut pi = vec!(0u32; n + 1);
+        lpf[1] = u32::MAX;
+        mu[1] = 1;
+        for i in 2..=n {
+            if lpf[i] == 0 {
+                lpf[i] = i as u32;
+                mu[i] = -1;
+                primes.push(i as u64);
+            }
+            for &p in &primes[1..] {
+                let p = p as usize;
+                if p > lpf[i] as usize || i * p > n {
+                    break;
+                }
+                lpf[i * p] = p as u32;
+                mu[i * p] = if p == lpf[i] as usize { 0 } else { -mu[i] };
+            }
+            pi[i] = primes.len() as u32 - 1;
+        }
+        let (squarefree, signed_lpf) = (1..=n)
+            .filter(|&m| mu[m] != 0 && lpf[m] as u64 > primes[C])
+            .map(|m| (m as u32, (mu[m] as i32) * (lpf[m].min(i32::MAX as u32) as i32)))
+            .unzip();
+        Self { primes, pi, squarefree, signed_lpf }
+    }
+}
+
+/// phi(n, C), using the numbers coprime to the first C primes within one period
+struct SmallPhi {
+    /// The number of residues in [1, r] coprime to PRIMORIAL, for each r
+    counts: Vec<u32>,
+    /// A bit for each residue, set if it is coprime to PRIMORIAL, with the period
+    /// continued for another two words so that any 64 bits of it can be read
+    pattern: Vec<u64>,
+}
+
+impl SmallPhi {
+    fn new(primes: &[u64]) -> Self {
+        let mut counts = Vec::with_capacity(PRIMORIAL as usize);
+        let mut pattern = vec!(0; PRIMORIAL as usize / 64 + 3);
+        let coprime = |r: u64| primes.iter().all(|&p| !r.is_multiple_of(p));
+        let mut count = 0;
+        for r in 0..PRIMORIAL {
+            count += coprime(r) as u32;
+            counts.push(count);
+        }
+        for i in 0..64 * pattern.len() as u64 {
+            if coprime(i % PRIMORIAL) {
+                pattern[i as usize / 64] |= 1 << (i % 64);
+            }
+        }
+        Self { counts, pattern }
+    }
+
+    fn phi(&self, n: u64) -> u64 {
+        let total = *self.counts.last().unwrap() as u64;
+        n / PRIMORIAL * total + self.counts[(n % PRIMORIAL) as usize] as u64
+    }
+
+    /// The 64 bits of the pattern for the residues from r
+    fn word(&self, r: u64) -> u64 {
+        let (i, shift) = (r as usize / 64, r % 64);
+        match shift {
+            0 => self.pattern[i],
+            _ => self.pattern[i] >> shift | self.pattern[i + 1] << (64 - shift),
+        }
+    }
+}
+
+/// A segment of the sieve for phi, with one bit for each number in [low, low + len)
+struct Segment {
+    words: Vec<u64>,
+    /// The number of bits set in each block of BLOCK_WORDS words
+    counters: Vec<u32>,
+    low: u64,
+}
+
+impl Segment {
+    /// Starts the segment [low, high) with the multiples of the first C primes crossed off
+    fn new(low: u64, high: u64, small: &SmallPhi) -> Self {
+        let len = (high - low) as usize;
+        let mut r = low % PRIMORIAL;
+        let mut words: Vec<u64> = (0..len.div_ceil(64)).map(|_| {
+            let word = small.word(r);
+            r = (r + 64) % PRIMORIAL;
+            word
+        }).collect();
+        if !len.is_multiple_of(64) {
+            *words.last_mut().unwrap() &= (1 << (len % 64)) - 1;
+        }
+        let counters = words.chunks(BLOCK_WORDS)
+            .map(|block| block.iter().map(|w| w.count_ones()).sum())
+            .collect();
+        Self { words, counters, low }
+    }
+
+    /// Crosses off the odd multiples of p, keeping the block counts up to date
+    fn cross_off(&mut self, p: u64) {
+        let mut m = self.low.div_ceil(p) * p;
+        if m.is_multiple_of(2) {
+            m += p;
+        }
+        let mut i = (m - self.low) as usize;
+        let len = 64 * self.words.len();
+        while i < len {
+            let word = &mut self.words[i / 64];
+            let bit = 1 << (i % 64);
+            self.counters[i / 64 / BLOCK_WORDS] -= (*word & bit != 0) as u32;
+            *word &= !bit;
+            i += 2 * p as usize;
+        }
+    }
+
+    /// Counts the numbers left in [low, low + end), moving on from a previous count up
+    /// to a word boundary, given as the word and the count up to it
+    fn count(&self, cursor: &mut (usize, u64), end: usize) -> u64 {
+        let (word, count) = cursor;
+        let target = end / 64;
+        while *word < target {
+            if *word % BLOCK_WORDS == 0 && *word + BLOCK_WORDS <= target {
+                *count += self.counters[*word / BLOCK_WORDS] as u64;
+                *word += BLOCK_WORDS;
+            }
+            else {
+                *count += self.words[*word].count_ones() as u64;
+                *word += 1;
+            }
+        }
+        let partial = match end % 64 {
+            0 => 0,
+            bits => (self.words[target] & ((1 << bits) - 1)).count_ones() as u64,
+        };
+        *count + partial
+    }
+
+    /// Counts all the numbers left in the segment
+    fn total(&self) -> u64 {
+        self.counters.iter().map(|&c| c as u64).sum()
+    }
+}
+
+/// The parameters shared by the parts of the computation
+struct Lmo {
+    x: u64,
+    y: u64,
+    z: u64,
+    /// The floor of the fourth root of x
+    x14: u64,
+    /// pi(y)
+    a: usize,
+    tables: Tables,
+}
+
+impl Lmo {
+    fn new(x: u64) -> Self {
+        // A larger y means fewer numbers to sieve but more leaves, and this balances
+        // the two for the sizes which can be reached
+        let log = (x as f64).ln();
+        let alpha = (log * log * log / 5000.0).max(1.0);
+        let cube_root = root_u64(x, 3);
+        let y = ((alpha * cube_root as f64) as u64).clamp(cube_root + 1, root_u64(x, 2));
+        let tables = Tables::new(y);
+        Self {
+            x,
+            y,
+            z: x / y,
+            x14: root_u64(x, 4),
+            a: tables.pi[y as usize] as usize,
+            tables,
+        }
+    }
+
+    /// The sum of the ordinary leaves, mu(n) phi(x / n, C) for n <= y with no prime factor
+    /// among the first C primes
+    fn ordinary_leaves(&self, small: &SmallPhi) -> i128 {
+        let Tables { squarefree, signed_lpf, .. } = &self.tables;
+        squarefree.iter().zip(signed_lpf)
+            .map(|(&n, &l)| l.signum() as i128 * small.phi(self.x / n as u64) as i128)
+            .sum()
+    }
+
+    /// The sum of the special leaves whose prime p_{b+1} is above the fourth root of x
+    /// and for which x / (p q) lies below y
+    fn easy_leaves(&self) -> i128 {
+        let Tables { primes, pi, .. } = &self.tables;
+        let (x, y) = (self.x, self.y);
+        let mut sum = 0;
+        for b in C.max(pi[self.x14 as usize] as usize)..self.a {
+            let p = primes[b + 1];
+            let start = p.max(y / p);
+            // phi(x / (p q), b) = 1 once x / (p q) < p
+            let trivial = start.max(x / (p * p)).min(y);
+            sum += (pi[y as usize] - pi[trivial as usize]) as i128;
+            // Otherwise phi(x / (p q), b) = pi(x / (p q)) - b + 1, which is the same for
+            // runs of consecutive q
+            let mut k = pi[start.max(x / (p * y)).min(y) as usize] as usize + 1;
+            let last = pi[trivial as usize] as usize;
+            // Below sqrt(x / p), x / (p q) changes by more than the gaps between primes
+            let sparse = (pi[root_u64(x / p, 2).min(y) as usize] as usize).min(last);
+            while k <= sparse {
+                let u = x / (p * primes[k]);
+                sum += (pi[u as usize] as usize + 1 - b) as i128;
+                k += 1;
+            }
+            while k <= last {
+                let u = x / (p * primes[k]);
+                let l = pi[u as usize] as usize;
+                let run_end = (pi[(x / (p * primes[l])).min(y) as usize] as usize).min(last);
+                sum += ((run_end + 1 - k) * (l + 1 - b)) as i128;
+                k = run_end + 1;
+            }
+        }
+        sum
+    }
+
+    /// The sum of the special leaves which need the sieve, those whose prime p_{b+1} is at
+    /// most the fourth root of x, and those for which x / (p q) is at least y
+    fn hard_leaves(&self, small: &SmallPhi) -> i128 {
+        let Tables { primes, pi, squarefree, signed_lpf } = &self.tables;
+        let (x, y) = (self.x, self.y);
+        let limit = self.z + 1;
+        let stages = self.a.min(pi[root_u64(self.z, 2) as usize] as usize);
+        // phi(low - 1, b) for the start of the current segment
+        let mut phi = vec!(0u64; stages);
+        let mut sum = 0;
+        let mut low = 1;
+        while low < limit {
+            let high = (low + SEGMENT_BITS).min(limit);
+            let mut segment = Segment::new(low, high, small);
+            for b in C..stages {
+                let p = primes[b + 1];
+                let m_high = y.min(x / (low * p));
+                if m_high <= p {
+                    // There are no leaves left for this prime or any larger one
+                    break;
+                }
+                let m_low = (y / p).max(x / high.saturating_mul(p));
+                let leaf = |m: u64, cursor: &mut (usize, u64)| {
+                    let u = x / (p * m);
+                    phi[b] + segment.count(cursor, (u - low + 1) as usize)
+                };
+                let mut cursor = (0, 0);
+                if p <= self.x14 {
+                    let start = squarefree.partition_point(|&m| m as u64 <= m_low);
+                    let end = squarefree.partition_point(|&m| m as u64 <= m_high);
+                    for i in (start..end).rev() {
+                        if signed_lpf[i].unsigned_abs() as u64 > p {
+                            sum -= signed_lpf[i].signum() as i128 * leaf(squarefree[i] as u64, &mut cursor) as i128;
+                        }
+                    }
+                }
+                else {
+                    let m_high = m_high.min(x / (p * y));
+                    let m_low = m_low.max(p);
+                    if m_low < m_high {
+                        for k in (pi[m_low as usize] as usize + 1..=pi[m_high as usize] as usize).rev() {
+                            sum += leaf(primes[k], &mut cursor) as i128;
+                        }
+                    }
+                }
+                phi[b] += segment.total();
+                segment.cross_off(p);
+            }
+            low = high;
+        }
+        sum
+    }
+
+    /// P2(x, a), the sum of pi(x / p) - pi(p) + 1 over the primes p in (y, sqrt(x)]
+    fn p2(&self) -> u64 {
+        let root = root_u64(self.x, 2);
+        let primes: Vec<u64> = SegmentedSieve::new(&Natural::from(self.y + 1), &Natural::from(root + 1))
+            .map(|p| u64::try_from(&p).unwrap())
+            .collect();
+        let mut pi = (self.a + primes.len()) as u64;
+        let mut counter = SegmentedSieve::new(&Natural::from(root + 1), &Natural::from(self.z + 1));
+        let mut sum = 0;
+        for (k, &p) in primes.iter().enumerate().rev() {
+            pi += counter.count_below(self.x / p + 1);
+            sum += pi - (self.a + k) as u64;
+        }
+        sum
+    }
+
+    fn prime_count(&self) -> u64 {
+        let small = SmallPhi::new(&self.tables.primes[1..=C]);
+        let phi = self.ordinary_leaves(&small) + self.easy_leaves() + self.hard_leaves(&small);
+        (phi + self.a as i128 - 1 - self.p2() as i128) as u64
+    }
+}
+
+///
+/// Returns pi(x), the number of primes up to and including x
+///
+/// This uses the combinatorial method of Lagarias, Miller and Odlyzko, taking time
+/// around x^(2/3) and space around x^(1/3), so that pi(10^15) is a matter of seconds.
+///
+/// # Panics
+/// If x is 2^64 or more, which is far beyond the reach of the method in any case.
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, prime_count};
+///
+/// assert_eq!(prime_count(&Natural::from(100u64)), Natural::from(25u64));
+/// assert_eq!(prime_count(&Natural::from(10u64).pow(12)), Natural::from(37_607_912_018u64));
+/// ```
+pub fn prime_count(x: &Natural) -> Natural {
+    let x = u64::try_from(x).expect("prime_count is limited to x below 2^64");
+    if x < SIEVE_BELOW {
+        return Natural::from(SegmentedSieve::new(&Natural::ZERO, &Natural::from(x + 1)).count() as u64);
+    }
+    Natural::from(Lmo::new(x).prime_count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sieve_count(x: u64) -> u64 {
+        SegmentedSieve::new(&Natural::ZERO, &Natural::from(x + 1)).count() as u64
+    }
+
+    #[test]
+    fn test_small() {
+        for (x, pi) in [(0u64, 0u64), (1, 0), (2, 1), (3, 2), (10, 4), (100, 25), (1000, 168), (1 << 20, 82_025)] {
+            assert_eq!(prime_count(&Natural::from(x)), Natural::from(pi));
+        }
+    }
+
+    #[test]
+    fn test_matches_sieve() {
+        // The combinatorial method, well below where it takes over from the sieve
+        for x in [10_000u64, 65_536, 99_991, 100_000, 1_000_003, 3_000_000, 12_345_678, 50_000_000] {
+            assert_eq!(Lmo::new(x).prime_count(), sieve_count(x), "x = {}", x);
+        }
+        for x in (SIEVE_BELOW - 2..SIEVE_BELOW + 2).chain([123_456_789, 1 << 30]) {
+            assert_eq!(prime_count(&Natural::from(x)), Natural::from(sieve_count(x)), "x = {}", x);
+        }
+    }
+
+    #[test]
+    fn test_powers_of_ten() {
+        let counts = [4u64, 25, 168, 1_229, 9_592, 78_498, 664_579, 5_761_455, 50_847_534,
+                      455_052_511, 4_118_054_813, 37_607_912_018, 346_065_536_839];
+        for (k, &pi) in counts.iter().enumerate() {
+            assert_eq!(prime_count(&Natural::from(10u64).pow(k as u64 + 1)), Natural::from(pi), "10^{}", k + 1);
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ten_to_the_fifteen() {
+        assert_eq!(prime_count(&Natural::from(10u64).pow(15)), Natural::from(29_844_570_422_669u64));
+    }
+}
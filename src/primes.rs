@@ -0,0 +1,248 @@
+// Searching for primes among the Natural numbers
+//
+// Candidates are taken a block at a time. Within a block, numbers sharing a factor
+// with 30 are skipped using a wheel, and multiples of the other primes below 1000
+// are crossed off with a sieve, starting from the residue of the block's first number.
+// Only the survivors, about 8% of all numbers, are given a full primality test.
+
+use core::convert::TryFrom;
+use core::iter::FusedIterator;
+
+use crate::natural::Natural;
+use crate::primality::{Primality, SMALL_PRIMES};
+
+/// The number of consecutive numbers sieved at once
+const BLOCK: u64 = 4096;
+
+/// Whether each residue modulo 30 is coprime to 30
+const WHEEL: [bool; 30] = {
+    let mut wheel = [false; 30];
+    let mut r = 0;
+    while r < 30 {
+        wheel[r] = r % 2 != 0 && r % 3 != 0 && r % 5 != 0;
+        r += 1;
+    }
+    wheel
+};
+
+/// Returns a flag for each number in [start, start + len) which is not divisible
+/// by any prime below 1000, other than the prime itself
+fn sieve_block(start: &Natural, len: usize) -> Vec<bool> {
+    let r = start.rem_digit(30) as usize;
+    let mut flags: Vec<bool> = (0..len).map(|i| WHEEL[(r + i) % 30]).collect();
+    for &p in &SMALL_PRIMES[3..] {
+        let r = start.rem_digit(p);
+        let mut i = ((p - r) % p) as usize;
+        while i < len {
+            flags[i] = false;
+            i += p as usize;
+        }
+    }
+    // The sieve crosses off the small primes themselves, and 1 survives it
+    if let Some(start) = u64::try_from(start).ok().filter(|&s| s < 1000) {
+        for (n, flag) in (start..1000).zip(flags.iter_mut()) {
+            *flag = SMALL_PRIMES.contains(&n);
+        }
+    }
+    flags
+}
+
+/// Whether a number which survived the sieve is prime
+fn is_prime_candidate(n: &Natural) -> bool {
+    n.is_probable_prime(0) != Primality::Composite
+}
+
+///
+/// An iterator over the primes in an interval, in increasing order
+///
+/// Numbers above 2^64 are reported when they pass the Baillie-PSW test, for which
+/// no composite counterexample is known.
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, Primes};
+///
+/// let primes: Vec<Natural> = Primes::new(Natural::from(10u64), Natural::from(30u64)).collect();
+/// assert_eq!(primes, [11u64, 13, 17, 19, 23, 29].iter().map(|&p| Natural::from(p)).collect::<Vec<_>>());
+/// ```
+#[derive(Debug,Clone)]
+pub struct Primes {
+    /// The first number of the current block
+    base: Natural,
+    /// The sieve flags of the current block
+    flags: Vec<bool>,
+    /// The position in the current block of the next number to examine
+    index: usize,
+    /// The end of the interval, exclusive, or None if it is unbounded
+    end: Option<Natural>,
+}
+
+impl Primes {
+    ///
+    /// Creates an iterator over the primes in [a, b)
+    pub fn new(a: Natural, b: Natural) -> Primes {
+        Primes {
+            base: a,
+            flags: Vec::new(),
+            index: 0,
+            end: Some(b),
+        }
+    }
+
+    ///
+    /// Creates an iterator over the primes in [a, b]
+    pub fn inclusive(a: Natural, b: Natural) -> Primes {
+        Primes::new(a, &b + &Natural::from(1u64))
+    }
+
+    ///
+    /// Creates an iterator over all the primes greater than or equal to a
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::{Natural, Primes};
+    ///
+    /// let mut primes = Primes::starting_at(Natural::from(1u64 << 40));
+    /// assert_eq!(primes.next(), Some(Natural::from((1u64 << 40) + 15)));
+    /// ```
+    pub fn starting_at(a: Natural) -> Primes {
+        Primes {
+            base: a,
+            flags: Vec::new(),
+            index: 0,
+            end: None,
+        }
+    }
+
+    /// Moves on to the next block, returning false if the interval is exhausted
+    fn next_block(&mut self) -> bool {
+        self.base = &self.base + &Natural::from(self.flags.len() as u64);
+        let len = match &self.end {
+            Some(end) if *end <= self.base => return false,
+            Some(end) => u64::try_from(&(end - &self.base)).map_or(BLOCK, |remaining| remaining.min(BLOCK)),
+            None => BLOCK,
+        };
+        self.flags = sieve_block(&self.base, len as usize);
+        self.index = 0;
+        true
+    }
+}
+
+impl Iterator for Primes {
+    type Item = Natural;
+
+    fn next(&mut self) -> Option<Natural> {
+        loop {
+            while self.index < self.flags.len() {
+                let i = self.index;
+                self.index += 1;
+                if self.flags[i] {
+                    let n = &self.base + &Natural::from(i as u64);
+                    if is_prime_candidate(&n) {
+                        return Some(n);
+                    }
+                }
+            }
+            if !self.next_block() {
+                // Stay exhausted
+                self.flags.clear();
+                self.index = 0;
+                return None;
+            }
+        }
+    }
+}
+
+impl FusedIterator for Primes {}
+
+impl Natural {
+    ///
+    /// Returns the smallest prime greater than this number
+    ///
+    /// Numbers above 2^64 are reported when they pass the Baillie-PSW test.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(13u64).next_prime(), Natural::from(17u64));
+    /// assert_eq!(Natural::ZERO.next_prime(), Natural::from(2u64));
+    /// ```
+    pub fn next_prime(&self) -> Natural {
+        Primes::starting_at(self + &Natural::from(1u64)).next().unwrap()
+    }
+
+    ///
+    /// Returns the largest prime less than this number, or None if there is none
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(13u64).prev_prime(), Some(Natural::from(11u64)));
+    /// assert_eq!(Natural::from(2u64).prev_prime(), None);
+    /// ```
+    pub fn prev_prime(&self) -> Option<Natural> {
+        let mut end = self.clone();
+        let block = Natural::from(BLOCK);
+        while !end.is_zero() {
+            let start = end.saturating_sub(&block);
+            let len = u64::try_from(&(&end - &start)).unwrap() as usize;
+            let flags = sieve_block(&start, len);
+            for i in (0..len).rev().filter(|&i| flags[i]) {
+                let n = &start + &Natural::from(i as u64);
+                if is_prime_candidate(&n) {
+                    return Some(n);
+                }
+            }
+            end = start;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_prime(n: u64) -> bool {
+        n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn test_primes_match_trial_division() {
+        let expected: Vec<Natural> = (0..20_000u64).filter(|&n| is_prime(n)).map(Natural::from).collect();
+        let primes: Vec<Natural> = Primes::new(Natural::ZERO, Natural::from(20_000u64)).collect();
+        assert_eq!(primes, expected);
+        let primes: Vec<Natural> = Primes::inclusive(Natural::from(7u64), Natural::from(29u64)).collect();
+        assert_eq!(primes.len(), 7);
+        assert_eq!(Primes::new(Natural::from(24u64), Natural::from(29u64)).next(), None);
+    }
+
+    #[test]
+    fn test_next_and_prev_prime() {
+        for n in 0u64..3000 {
+            let next = (n + 1..).find(|&m| is_prime(m)).unwrap();
+            assert_eq!(Natural::from(n).next_prime(), Natural::from(next));
+            let prev = (0..n).rev().find(|&m| is_prime(m)).map(Natural::from);
+            assert_eq!(Natural::from(n).prev_prime(), prev);
+        }
+        // The largest prime below 2^64 and the smallest above it
+        let two_64 = Natural::from(vec!(0, 1));
+        assert_eq!(two_64.prev_prime(), Some(Natural::from(u64::MAX - 58)));
+        assert_eq!(two_64.next_prime(), Natural::from(vec!(13, 1)));
+        // A prime gap of 1132 above 1693182318746371
+        let p = Natural::from(1693182318746371u64);
+        assert_eq!(p.next_prime(), Natural::from(1693182318747503u64));
+        assert_eq!(Natural::from(1693182318747503u64).prev_prime(), Some(p));
+    }
+
+    #[test]
+    fn test_iterator_is_fused() {
+        let mut primes = Primes::new(Natural::from(2u64), Natural::from(4u64));
+        assert_eq!(primes.next(), Some(Natural::from(2u64)));
+        assert_eq!(primes.next(), Some(Natural::from(3u64)));
+        assert_eq!(primes.next(), None);
+        assert_eq!(primes.next(), None);
+    }
+}
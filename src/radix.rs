@@ -0,0 +1,252 @@
+// Conversion of Natural and Integer to and from strings in radixes 2 to 36
+//
+// Power-of-two radixes are converted by reading bits straight out of the limbs.
+// Other radixes repeatedly divide by the largest power of the radix that fits in a
+// limb, producing a whole chunk of digits from each single-limb remainder.
+
+use core::fmt;
+
+use crate::natural::{Natural, Limb};
+use crate::integer::{Integer, Sign};
+use crate::parse::{ParseError, ParseErrorKind, parse_digits};
+use crate::algorithms::div_by_single_digit;
+
+/// Returns the ASCII digits of n in the given radix, most significant first
+///
+/// Zero is written as a single "0" digit.
+pub(crate) fn to_radix_digits(n: &Natural, radix: u32, uppercase: bool) -> Vec<u8> {
+    assert!((2..=36).contains(&radix), "Radix must be in the range 2 to 36");
+    if n.is_zero() {
+        return vec!(b'0');
+    }
+    let mut values = if radix.is_power_of_two() {
+        power_of_two_digits(&n.digits, radix.trailing_zeros())
+    }
+    else {
+        general_digits(&n.digits, radix)
+    };
+    values.reverse();
+    let alphabet: &[u8] = if uppercase {
+        b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ"
+    }
+    else {
+        b"0123456789abcdefghijklmnopqrstuvwxyz"
+    };
+    values.iter().map(|&v| alphabet[v as usize]).collect()
+}
+
+/// Returns the digit values of a non-zero number in radix 2^bits, least significant first
+fn power_of_two_digits(digits: &[Limb], bits: u32) -> Vec<u8> {
+    let bit_length = 64 * digits.len() - digits[digits.len()-1].leading_zeros() as usize;
+    let mask = (1 << bits) - 1;
+    let mut values = Vec::with_capacity(bit_length / bits as usize + 1);
+    let mut position = 0;
+    while position < bit_length {
+        let limb = position / 64;
+        let offset = position % 64;
+        let mut value = digits[limb] >> offset;
+        // A digit may straddle two limbs
+        if offset + bits as usize > 64 && limb + 1 < digits.len() {
+            value |= digits[limb + 1] << (64 - offset);
+        }
+        values.push((value & mask) as u8);
+        position += bits as usize;
+    }
+    values
+}
+
+/// Returns the digit values of a non-zero number in any radix, least significant first
+fn general_digits(digits: &[Limb], radix: u32) -> Vec<u8> {
+    let mut chunk_size = 0;
+    let mut chunk_divisor: Limb = 1;
+    while let Some(m) = chunk_divisor.checked_mul(radix as Limb) {
+        chunk_divisor = m;
+        chunk_size += 1;
+    }
+
+    let mut values = Vec::new();
+    let mut quotient = digits.to_vec();
+    while !quotient.is_empty() {
+        let (d, mut r) = div_by_single_digit(&quotient, chunk_divisor);
+        quotient = d;
+        // Every chunk but the most significant is padded with zeroes
+        for _ in 0..chunk_size {
+            if quotient.is_empty() && r == 0 {
+                break;
+            }
+            values.push((r % radix as Limb) as u8);
+            r /= radix as Limb;
+        }
+    }
+    values
+}
+
+/// Parses an optionally signed number in the given radix, without a radix prefix
+fn parse_radix(s: &str, radix: u32) -> Result<(Sign, Natural), ParseError> {
+    assert!((2..=36).contains(&radix), "Radix must be in the range 2 to 36");
+    let (sign, digits, offset) = match s.as_bytes().first() {
+        Some(b'+') => (Sign::Positive, &s[1..], 1),
+        Some(b'-') => (Sign::Negative, &s[1..], 1),
+        _ => (Sign::Positive, s, 0),
+    };
+    Ok((sign, parse_digits(digits, offset, radix)?))
+}
+
+impl Natural {
+    ///
+    /// Returns the number written in the given radix, using lowercase letters for digits above 9
+    ///
+    /// # Panics
+    /// Panics if the radix is not in the range 2 to 36
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(255u64).to_str_radix(16), "ff");
+    /// assert_eq!(Natural::from(35u64).to_str_radix(36), "z");
+    /// ```
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        String::from_utf8(to_radix_digits(self, radix, false)).unwrap()
+    }
+
+    ///
+    /// Parses a number written in the given radix
+    ///
+    /// This accepts an optional '+' followed by digits with '_' separators allowed
+    /// between digits. Unlike parsing with `str::parse`, no radix prefix or surrounding
+    /// whitespace is accepted.
+    ///
+    /// # Panics
+    /// Panics if the radix is not in the range 2 to 36
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from_str_radix("zz", 36), Ok(Natural::from(1295u64)));
+    /// assert!(Natural::from_str_radix("12", 2).is_err());
+    /// ```
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseError> {
+        match parse_radix(s, radix)? {
+            (Sign::Positive, n) => Ok(n),
+            (Sign::Negative, _) => Err(ParseError::new(0, ParseErrorKind::Negative)),
+        }
+    }
+}
+
+impl Integer {
+    ///
+    /// Returns the number written in the given radix, using lowercase letters for digits above 9
+    ///
+    /// # Panics
+    /// Panics if the radix is not in the range 2 to 36
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Integer;
+    ///
+    /// assert_eq!(Integer::from(-255).to_str_radix(16), "-ff");
+    /// ```
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        let digits = self.size.to_str_radix(radix);
+        match self.sign {
+            Sign::Positive => digits,
+            Sign::Negative => format!("-{}", digits),
+        }
+    }
+
+    ///
+    /// Parses a number written in the given radix
+    ///
+    /// This accepts an optional '+' or '-' followed by digits with '_' separators allowed
+    /// between digits. Unlike parsing with `str::parse`, no radix prefix or surrounding
+    /// whitespace is accepted.
+    ///
+    /// # Panics
+    /// Panics if the radix is not in the range 2 to 36
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseError> {
+        let (sign, size) = parse_radix(s, radix)?;
+        Ok(Integer::from_sign_and_size(sign, size))
+    }
+}
+
+macro_rules! radix_fmt {
+    ($($tr:ident, $radix:expr, $uppercase:expr, $prefix:expr;)*) => ($(
+        impl fmt::$tr for Natural {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let digits = to_radix_digits(self, $radix, $uppercase);
+                f.pad_integral(true, $prefix, core::str::from_utf8(&digits).unwrap())
+            }
+        }
+
+        impl fmt::$tr for Integer {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let digits = to_radix_digits(&self.size, $radix, $uppercase);
+                f.pad_integral(self.sign == Sign::Positive, $prefix, core::str::from_utf8(&digits).unwrap())
+            }
+        }
+    )*)
+}
+
+radix_fmt! {
+    LowerHex, 16, false, "0x";
+    UpperHex, 16, true, "0x";
+    Octal, 8, false, "0o";
+    Binary, 2, false, "0b";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NINE: u64 = u64::MAX;
+
+    #[test]
+    fn test_to_str_radix() {
+        assert_eq!(Natural::ZERO.to_str_radix(7), "0");
+        assert_eq!(Natural::from(vec!(0, 1)).to_str_radix(16), "10000000000000000");
+        assert_eq!(Natural::from(vec!(0, 1)).to_str_radix(10), "18446744073709551616");
+        assert_eq!(Natural::from(vec!(0, 1)).to_str_radix(8), "2000000000000000000000");
+        assert_eq!(Natural::from(vec!(NINE, NINE)).to_str_radix(32), format!("7{}", "v".repeat(25)));
+        assert_eq!(Natural::from(u128::MAX).to_str_radix(3), {
+            let mut s = String::new();
+            let mut v = u128::MAX;
+            while v > 0 {
+                s.insert(0, std::char::from_digit((v % 3) as u32, 3).unwrap());
+                v /= 3;
+            }
+            s
+        });
+        assert_eq!(Natural::from(10000000000000000000u64).to_str_radix(10), "10000000000000000000");
+    }
+
+    #[test]
+    fn test_from_str_radix_round_trip() {
+        let n = Natural::from(vec!(0x0123_4567_89ab_cdef, 0xfedc_ba98_7654_3210, 42));
+        for radix in 2..=36 {
+            assert_eq!(Natural::from_str_radix(&n.to_str_radix(radix), radix), Ok(n.clone()));
+        }
+        assert_eq!(Natural::from_str_radix("-1", 10), Err(ParseError::new(0, ParseErrorKind::Negative)));
+        assert_eq!(Integer::from_str_radix("-Ff", 16), Ok(Integer::from(-255)));
+        assert_eq!(Integer::from_str_radix("0x10", 16), Err(ParseError::new(1, ParseErrorKind::InvalidDigit)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_radix() {
+        Natural::from(1u64).to_str_radix(37);
+    }
+
+    #[test]
+    fn test_fmt_radix_traits() {
+        let n = Natural::from(vec!(0xdead_beef, 0xa));
+        assert_eq!(format!("{:x}", n), "a00000000deadbeef");
+        assert_eq!(format!("{:#X}", n), "0xA00000000DEADBEEF");
+        assert_eq!(format!("{:o}", Natural::from(8u64)), "10");
+        assert_eq!(format!("{:#b}", Natural::from(5u64)), "0b101");
+        assert_eq!(format!("{:x}", Natural::ZERO), "0");
+        assert_eq!(format!("{:#x}", Integer::from(-255)), "-0xff");
+        assert_eq!(format!("{:b}", Integer::from(-2)), "-10");
+    }
+}
@@ -1,41 +1,196 @@
+
 // When 'Step' is stabilised, it will become ergonomic to use Rust ranges with Naturals
-// Until then, we provide our own range object with an iterator
-// This is memory efficient, as it maintains just the end and loop variable
-// Only supports increasing loops this way. May change this in future
+// Until then, we provide our own range objects with iterators
+// A range keeps its first and last remaining values along with the number of values
+// left, so iteration from either end, nth and len are all simple arithmetic.
 
-use core::iter::Iterator;
+use core::iter::{Iterator, DoubleEndedIterator};
+use core::mem;
+use core::convert::TryFrom;
 use crate::natural::Natural;
+use crate::integer::Integer;
+
+/// Returns the number of terms start, start + step, ... which lie before end,
+/// or up to and including end if the range is inclusive
+///
+/// The distance is the size of end - start, and the step must be non-zero
+fn count_terms(distance: &Natural, step: &Natural, inclusive: bool) -> Natural {
+    let (d, r) = distance.div(step);
+    if inclusive || !r.is_zero() {
+        &d + &Natural::from(1u64)
+    }
+    else {
+        d
+    }
+}
+
+/// Converts a remaining length into an iterator size hint
+fn size_hint_of(count: &Natural) -> (usize, Option<usize>) {
+    match usize::try_from(count) {
+        Ok(n) => (n, Some(n)),
+        Err(_) => (usize::MAX, None),
+    }
+}
 
 ///
 /// A range of positive integers
-/// 
+///
 /// # Example
 /// ```rust
 /// use skewes::{Natural,Range};
-/// 
+///
 /// // Create a range
-/// let numbers = Range::new(Natural::from(1), Natural::from(10));
+/// let numbers = Range::new(Natural::from(1u64), Natural::from(10u64));
 /// for number in numbers {
 ///     println!("{}", number);
 /// }
+///
+/// // Ranges can be inclusive, have larger steps and run backwards
+/// let odd: Vec<Natural> = Range::inclusive(Natural::from(1u64), Natural::from(9u64))
+///     .with_step(Natural::from(2u64))
+///     .rev()
+///     .collect();
+/// assert_eq!(odd[0], Natural::from(9u64));
+/// assert_eq!(odd.len(), 5);
 /// ```
-/// 
+///
+#[derive(Debug,Clone)]
 pub struct Range {
-    current: Natural,
+    front: Natural,
+    back: Natural,
+    step: Natural,
+    count: Natural,
     end: Natural,
+    inclusive: bool,
 }
 
 impl Range {
     ///
     /// Create a new range [a,b)
-    /// 
+    ///
     /// The created range represents natural numbers greater than or equal to a
     /// and strictly less than b
     pub fn new(a: Natural, b: Natural) -> Range {
+        Self::build(a, b, Natural::from(1u64), false)
+    }
+
+    ///
+    /// Create a new range [a,b]
+    ///
+    /// The created range represents natural numbers greater than or equal to a
+    /// and less than or equal to b
+    pub fn inclusive(a: Natural, b: Natural) -> Range {
+        Self::build(a, b, Natural::from(1u64), true)
+    }
+
+    fn build(front: Natural, end: Natural, step: Natural, inclusive: bool) -> Range {
+        assert!(!step.is_zero(), "The step of a range must be non-zero");
+        let count = match front.checked_sub(&end) {
+            // end is at least front
+            None => count_terms(&end.abs_diff(&front), &step, inclusive),
+            Some(d) if d.is_zero() && inclusive => Natural::from(1u64),
+            Some(_) => Natural::ZERO,
+        };
+        let back = Self::term(&front, &step, &count);
         Self {
-            current: a,
-            end: b,
+            front,
+            back,
+            step,
+            count,
+            end,
+            inclusive,
+        }
+    }
+
+    /// Returns the last of count terms from front, or front if count is zero
+    fn term(front: &Natural, step: &Natural, count: &Natural) -> Natural {
+        match count.checked_sub(&Natural::from(1u64)) {
+            Some(n) => front + &(&n * step),
+            None => front.clone(),
+        }
+    }
+
+    ///
+    /// Changes the difference between successive values in the range
+    ///
+    /// The range will yield the next value it would have yielded, then values
+    /// increasing by step until the end of the range, or the last value taken from
+    /// the back, is reached.
+    ///
+    /// # Panics
+    /// Panics if step is zero
+    pub fn with_step(self, step: Natural) -> Range {
+        Self::build(self.front, self.end, step, self.inclusive)
+    }
+
+    ///
+    /// The number of values remaining in the range
+    ///
+    pub fn len(&self) -> Natural {
+        self.count.clone()
+    }
+
+    ///
+    /// Whether there are no values remaining in the range
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.count.is_zero()
+    }
+
+    ///
+    /// Whether the range will yield the given value
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::{Natural,Range};
+    ///
+    /// let r = Range::new(Natural::from(1u64), Natural::from(10u64)).with_step(Natural::from(3u64));
+    /// assert!(r.contains(&Natural::from(7u64)));
+    /// assert!(!r.contains(&Natural::from(8u64)));
+    /// ```
+    pub fn contains(&self, n: &Natural) -> bool {
+        match n.checked_sub(&self.front) {
+            Some(d) => {
+                let (k, r) = d.div(&self.step);
+                r.is_zero() && k < self.count
+            },
+            None => false,
+        }
+    }
+
+    /// Empties the range, leaving nothing for a change of step to bring back
+    fn exhaust(&mut self) {
+        self.count = Natural::ZERO;
+        self.end = self.front.clone();
+        self.inclusive = false;
+    }
+
+    ///
+    /// Returns the value n steps from the front of the range, consuming
+    /// it and all the values before it
+    ///
+    /// This is the same as `Iterator::nth`, but for offsets larger than a usize
+    pub fn nth_natural(&mut self, n: &Natural) -> Option<Natural> {
+        if *n >= self.count {
+            self.exhaust();
+            return None;
+        }
+        self.front = &self.front + &(n * &self.step);
+        self.count = &self.count - n;
+        self.next()
+    }
+
+    ///
+    /// Returns the value n steps from the back of the range, consuming
+    /// it and all the values after it
+    pub fn nth_back_natural(&mut self, n: &Natural) -> Option<Natural> {
+        if *n >= self.count {
+            self.exhaust();
+            return None;
         }
+        self.back = &self.back - &(n * &self.step);
+        self.count = &self.count - n;
+        self.next_back()
     }
 }
 
@@ -43,13 +198,365 @@ impl Iterator for Range {
     type Item = Natural;
 
     fn next(&mut self) -> Option<Natural> {
-        let value = if self.current < self.end {
-            Some(self.current.clone())
+        if self.count.is_zero() {
+            return None;
+        }
+        self.count.dec();
+        let next = &self.front + &self.step;
+        Some(mem::replace(&mut self.front, next))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        size_hint_of(&self.count)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Natural> {
+        self.nth_natural(&Natural::from(n))
+    }
+
+    fn last(mut self) -> Option<Natural> {
+        self.next_back()
+    }
+}
+
+impl DoubleEndedIterator for Range {
+    fn next_back(&mut self) -> Option<Natural> {
+        if self.count.is_zero() {
+            return None;
+        }
+        self.count.dec();
+        // A later change of step must stop short of the value taken from the back
+        self.end = self.back.clone();
+        self.inclusive = false;
+        if self.count.is_zero() {
+            return Some(self.back.clone());
+        }
+        let next = &self.back - &self.step;
+        Some(mem::replace(&mut self.back, next))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Natural> {
+        self.nth_back_natural(&Natural::from(n))
+    }
+}
+
+///
+/// A range of integers, which may be decreasing
+///
+/// # Example
+/// ```rust
+/// use skewes::{Integer,IntegerRange};
+///
+/// let numbers: Vec<Integer> = IntegerRange::new(Integer::from(4), Integer::from(-4))
+///     .with_step(Integer::from(-3))
+///     .collect();
+/// assert_eq!(numbers, vec!(Integer::from(4), Integer::from(1), Integer::from(-2)));
+/// ```
+///
+#[derive(Debug,Clone)]
+pub struct IntegerRange {
+    front: Integer,
+    back: Integer,
+    step: Integer,
+    count: Natural,
+    end: Integer,
+    inclusive: bool,
+}
+
+impl IntegerRange {
+    ///
+    /// Create a new range [a,b)
+    ///
+    /// The created range represents integers greater than or equal to a
+    /// and strictly less than b
+    pub fn new(a: Integer, b: Integer) -> IntegerRange {
+        Self::build(a, b, Integer::from(1), false)
+    }
+
+    ///
+    /// Create a new range [a,b]
+    ///
+    /// The created range represents integers greater than or equal to a
+    /// and less than or equal to b
+    pub fn inclusive(a: Integer, b: Integer) -> IntegerRange {
+        Self::build(a, b, Integer::from(1), true)
+    }
+
+    fn build(front: Integer, end: Integer, step: Integer, inclusive: bool) -> IntegerRange {
+        assert!(!step.size.is_zero(), "The step of a range must be non-zero");
+        let distance = &end - &front;
+        let count = if distance.size.is_zero() {
+            if inclusive { Natural::from(1u64) } else { Natural::ZERO }
+        }
+        else if distance.sign == step.sign {
+            count_terms(&distance.size, &step.size, inclusive)
         }
         else {
-            None
+            Natural::ZERO
         };
-        self.current = &self.current + &Natural::from(1);
-        value
+        let back = Self::term(&front, &step, &count);
+        Self {
+            front,
+            back,
+            step,
+            count,
+            end,
+            inclusive,
+        }
+    }
+
+    /// Returns the last of count terms from front, or front if count is zero
+    fn term(front: &Integer, step: &Integer, count: &Natural) -> Integer {
+        match count.checked_sub(&Natural::from(1u64)) {
+            Some(n) => front + &(&Integer::from(n) * step),
+            None => front.clone(),
+        }
     }
-}
\ No newline at end of file
+
+    ///
+    /// Changes the difference between successive values in the range
+    ///
+    /// The range will yield the next value it would have yielded, then values
+    /// separated by step until the end of the range, or the last value taken from
+    /// the back, is reached. A negative step
+    /// gives a decreasing range, which is empty unless the end is below the start.
+    ///
+    /// # Panics
+    /// Panics if step is zero
+    pub fn with_step(self, step: Integer) -> IntegerRange {
+        Self::build(self.front, self.end, step, self.inclusive)
+    }
+
+    ///
+    /// The number of values remaining in the range
+    ///
+    pub fn len(&self) -> Natural {
+        self.count.clone()
+    }
+
+    ///
+    /// Whether there are no values remaining in the range
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.count.is_zero()
+    }
+
+    ///
+    /// Whether the range will yield the given value
+    ///
+    pub fn contains(&self, n: &Integer) -> bool {
+        let d = n - &self.front;
+        if !d.size.is_zero() && d.sign != self.step.sign {
+            return false;
+        }
+        let (k, r) = d.size.div(&self.step.size);
+        r.is_zero() && k < self.count
+    }
+
+    /// Empties the range, leaving nothing for a change of step to bring back
+    fn exhaust(&mut self) {
+        self.count = Natural::ZERO;
+        self.end = self.front.clone();
+        self.inclusive = false;
+    }
+
+    ///
+    /// Returns the value n steps from the front of the range, consuming
+    /// it and all the values before it
+    ///
+    /// This is the same as `Iterator::nth`, but for offsets larger than a usize
+    pub fn nth_natural(&mut self, n: &Natural) -> Option<Integer> {
+        if *n >= self.count {
+            self.exhaust();
+            return None;
+        }
+        self.front = &self.front + &(&Integer::from(n.clone()) * &self.step);
+        self.count = &self.count - n;
+        self.next()
+    }
+
+    ///
+    /// Returns the value n steps from the back of the range, consuming
+    /// it and all the values after it
+    pub fn nth_back_natural(&mut self, n: &Natural) -> Option<Integer> {
+        if *n >= self.count {
+            self.exhaust();
+            return None;
+        }
+        self.back = &self.back - &(&Integer::from(n.clone()) * &self.step);
+        self.count = &self.count - n;
+        self.next_back()
+    }
+}
+
+impl Iterator for IntegerRange {
+    type Item = Integer;
+
+    fn next(&mut self) -> Option<Integer> {
+        if self.count.is_zero() {
+            return None;
+        }
+        self.count.dec();
+        let next = &self.front + &self.step;
+        Some(mem::replace(&mut self.front, next))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        size_hint_of(&self.count)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Integer> {
+        self.nth_natural(&Natural::from(n))
+    }
+
+    fn last(mut self) -> Option<Integer> {
+        self.next_back()
+    }
+}
+
+impl DoubleEndedIterator for IntegerRange {
+    fn next_back(&mut self) -> Option<Integer> {
+        if self.count.is_zero() {
+            return None;
+        }
+        self.count.dec();
+        // A later change of step must stop short of the value taken from the back
+        self.end = self.back.clone();
+        self.inclusive = false;
+        if self.count.is_zero() {
+            return Some(self.back.clone());
+        }
+        let next = &self.back - &self.step;
+        Some(mem::replace(&mut self.back, next))
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Integer> {
+        self.nth_back_natural(&Natural::from(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naturals(values: &[u64]) -> Vec<Natural> {
+        values.iter().map(|&v| Natural::from(v)).collect()
+    }
+
+    fn integers(values: &[i64]) -> Vec<Integer> {
+        values.iter().map(|&v| Integer::from(v)).collect()
+    }
+
+    fn range(a: u64, b: u64) -> Range {
+        Range::new(Natural::from(a), Natural::from(b))
+    }
+
+    #[test]
+    fn test_range_forwards_and_backwards() {
+        assert_eq!(range(3, 7).collect::<Vec<_>>(), naturals(&[3, 4, 5, 6]));
+        assert_eq!(range(3, 7).rev().collect::<Vec<_>>(), naturals(&[6, 5, 4, 3]));
+        assert_eq!(range(7, 3).count(), 0);
+        assert_eq!(range(3, 3).count(), 0);
+        let r = Range::inclusive(Natural::from(3u64), Natural::from(3u64));
+        assert_eq!(r.collect::<Vec<_>>(), naturals(&[3]));
+    }
+
+    #[test]
+    fn test_range_is_fused() {
+        let mut r = range(0, 2);
+        assert_eq!(r.next(), Some(Natural::ZERO));
+        assert_eq!(r.next_back(), Some(Natural::from(1u64)));
+        assert_eq!(r.next(), None);
+        assert_eq!(r.next_back(), None);
+        assert_eq!(r.next(), None);
+    }
+
+    #[test]
+    fn test_range_with_step() {
+        let r = range(1, 10).with_step(Natural::from(3u64));
+        assert_eq!(r.len(), Natural::from(3u64));
+        assert_eq!(r.size_hint(), (3, Some(3)));
+        assert_eq!(r.clone().collect::<Vec<_>>(), naturals(&[1, 4, 7]));
+        assert_eq!(r.rev().collect::<Vec<_>>(), naturals(&[7, 4, 1]));
+
+        let r = Range::inclusive(Natural::from(1u64), Natural::from(10u64)).with_step(Natural::from(3u64));
+        assert_eq!(r.collect::<Vec<_>>(), naturals(&[1, 4, 7, 10]));
+    }
+
+    #[test]
+    fn test_range_with_step_after_next_back() {
+        // 9 and 8 have been taken, so 9 must not come back
+        let mut r = range(0, 10);
+        r.next_back();
+        r.next_back();
+        assert_eq!(r.with_step(Natural::from(3u64)).collect::<Vec<_>>(), naturals(&[0, 3, 6]));
+        let mut r = Range::inclusive(Natural::from(1u64), Natural::from(10u64)).with_step(Natural::from(3u64));
+        assert_eq!(r.nth_back(1), Some(Natural::from(7u64)));
+        assert_eq!(r.clone().with_step(Natural::from(2u64)).collect::<Vec<_>>(), naturals(&[1, 3, 5]));
+        assert_eq!(r.next(), Some(Natural::from(1u64)));
+        assert_eq!(r.with_step(Natural::from(1u64)).collect::<Vec<_>>(), naturals(&[4, 5, 6]));
+        // An exhausted range stays exhausted, however it was emptied
+        let mut r = range(0, 10);
+        assert_eq!(r.nth(20), None);
+        assert!(r.with_step(Natural::from(3u64)).is_empty());
+        let mut r = range(0, 10);
+        assert_eq!(r.nth_back(20), None);
+        assert!(r.with_step(Natural::from(3u64)).is_empty());
+        let mut r = range(0, 2);
+        r.next();
+        r.next_back();
+        assert!(r.with_step(Natural::from(3u64)).is_empty());
+        // Changing the step still looks past the old last value towards the end
+        let r = range(0, 10).with_step(Natural::from(5u64));
+        assert_eq!(r.with_step(Natural::from(3u64)).collect::<Vec<_>>(), naturals(&[0, 3, 6, 9]));
+    }
+
+    #[test]
+    fn test_range_nth_and_contains() {
+        let huge = Natural::from(vec!(0, 0, 1));
+        let mut r = Range::new(Natural::ZERO, huge.clone());
+        assert_eq!(r.len(), huge);
+        assert_eq!(r.size_hint(), (usize::MAX, None));
+        assert_eq!(r.nth(5), Some(Natural::from(5u64)));
+        assert_eq!(r.nth_back(0), Some(&huge - &Natural::from(1u64)));
+        assert!(r.contains(&Natural::from(vec!(0, 1))));
+        assert!(!r.contains(&huge));
+        assert!(!r.contains(&Natural::from(5u64)));
+        let mid = Natural::from(vec!(7, 1));
+        assert_eq!(r.nth_natural(&(&mid - &Natural::from(6u64))), Some(mid));
+        assert_eq!(range(0, 5).nth(5), None);
+        assert_eq!(range(0, 5).last(), Some(Natural::from(4u64)));
+    }
+
+    #[test]
+    fn test_integer_range() {
+        let r = IntegerRange::new(Integer::from(-2), Integer::from(2));
+        assert_eq!(r.collect::<Vec<_>>(), integers(&[-2, -1, 0, 1]));
+
+        let r = IntegerRange::inclusive(Integer::from(5), Integer::from(-5)).with_step(Integer::from(-5));
+        assert_eq!(r.len(), Natural::from(3u64));
+        assert!(r.contains(&Integer::from(0)));
+        assert!(!r.contains(&Integer::from(10)));
+        assert!(!r.contains(&Integer::from(-10)));
+        assert_eq!(r.clone().rev().collect::<Vec<_>>(), integers(&[-5, 0, 5]));
+        assert_eq!(r.collect::<Vec<_>>(), integers(&[5, 0, -5]));
+
+        let r = IntegerRange::new(Integer::from(5), Integer::from(-5));
+        assert!(r.is_empty());
+        assert!(r.clone().with_step(Integer::from(-4)).eq(integers(&[5, 1, -3])));
+        assert_eq!(r.with_step(Integer::from(-4)).nth(2), Some(Integer::from(-3)));
+
+        let mut r = IntegerRange::inclusive(Integer::from(5), Integer::from(-5)).with_step(Integer::from(-1));
+        assert_eq!(r.nth_back(2), Some(Integer::from(-3)));
+        assert_eq!(r.with_step(Integer::from(-3)).collect::<Vec<_>>(), integers(&[5, 2, -1]));
+        let mut r = IntegerRange::new(Integer::from(-2), Integer::from(2));
+        assert_eq!(r.nth_back(5), None);
+        assert!(r.with_step(Integer::from(1)).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_step() {
+        range(0, 5).with_step(Natural::ZERO);
+    }
+}
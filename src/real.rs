@@ -0,0 +1,410 @@
+// Real numbers held as intervals with fixed-point bounds
+//
+// A RealInterval at precision p is a pair of Integers l <= u standing for the closed
+// interval [l / 2^p, u / 2^p], which is known to contain some real number. Each
+// operation rounds its lower bound down and its upper bound up, so that the result
+// contains the exact result of the operation on any numbers in the operands, and the
+// error from truncating a series is added to the bounds explicitly. The bounds are
+// then a proof of where the number lies, and the width of the interval shows how much
+// precision has been lost on the way.
+//
+// The logarithm of a Natural n = 2^e t, with t in [1, 2), is e ln 2 + ln t, where
+// ln t = 2 atanh((t - 1) / (t + 1)). Euler's constant comes from the method of Brent and
+// McMillan: with B_k = (m^k / k!)^2 and H_k the harmonic numbers,
+//
+//     gamma = sum(B_k H_k) / sum(B_k) - ln m - K_0(2m) / I_0(2m)
+//
+// where 0 < K_0(2m) / I_0(2m) < pi e^(-4m).
+
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::natural::Natural;
+use crate::integer::{Integer, Sign};
+use crate::algorithms::{shl_slice, shr_slice};
+
+/// Returns n * 2^k
+fn shl(n: &Integer, k: u64) -> Integer {
+    Integer::from_sign_and_size(n.sign, Natural::from(shl_slice(&n.size.digits, k as usize)))
+}
+
+/// Returns the floor or, if ceiling is set, the ceiling of n / d for d > 0
+fn div_round(n: &Integer, d: &Natural, ceiling: bool) -> Integer {
+    let (q, r) = n.size.div(d);
+    // Truncation rounds the magnitude down, which is the wrong way for one of the signs
+    let away = !r.is_zero() && ceiling == (n.sign == Sign::Positive);
+    let q = if away { &q + &Natural::from(1u64) } else { q };
+    Integer::from_sign_and_size(n.sign, q)
+}
+
+/// Returns the floor or, if ceiling is set, the ceiling of n / 2^k
+fn shr_round(n: &Integer, k: u64, ceiling: bool) -> Integer {
+    let q = Natural::from(shr_slice(&n.size.digits, k as usize));
+    let exact = n.size.trailing_zeros().is_none_or(|z| z >= k);
+    let away = !exact && ceiling == (n.sign == Sign::Positive);
+    let q = if away { &q + &Natural::from(1u64) } else { q };
+    Integer::from_sign_and_size(n.sign, q)
+}
+
+///
+/// A closed interval known to contain a real number, with bounds in fixed point
+///
+/// The bounds are Integers l and u standing for l / 2^p and u / 2^p, where p is the
+/// precision of the interval. Functions returning a RealInterval guarantee that the
+/// number lies within it, and aim for a width of a few units of 2^-p.
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, Integer, li};
+///
+/// let value = li(&Natural::from(1000u64), 64);
+/// assert_eq!(value.floor(), Some(Integer::from(177i64)));
+/// assert_eq!(format!("{:.5}", value), "[177.60965, 177.60966]");
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct RealInterval {
+    lower: Integer,
+    upper: Integer,
+    precision: u64,
+}
+
+impl RealInterval {
+    /// The interval holding just n
+    pub(crate) fn exact(n: &Integer, precision: u64) -> Self {
+        let n = shl(n, precision);
+        Self { lower: n.clone(), upper: n, precision }
+    }
+
+    pub(crate) fn from_natural(n: &Natural, precision: u64) -> Self {
+        Self::exact(&Integer::from(n.clone()), precision)
+    }
+
+    ///
+    /// Returns the numerator of the lower bound, which is this over 2^precision
+    pub fn lower(&self) -> &Integer {
+        &self.lower
+    }
+
+    ///
+    /// Returns the numerator of the upper bound, which is this over 2^precision
+    pub fn upper(&self) -> &Integer {
+        &self.upper
+    }
+
+    ///
+    /// Returns the number of fractional bits in the bounds
+    pub fn precision(&self) -> u64 {
+        self.precision
+    }
+
+    ///
+    /// Returns the floor of the number, if it is the same throughout the interval
+    pub fn floor(&self) -> Option<Integer> {
+        let lower = shr_round(&self.lower, self.precision, false);
+        Some(lower).filter(|lower| *lower == shr_round(&self.upper, self.precision, false))
+    }
+
+    ///
+    /// Returns the ceiling of the number, if it is the same throughout the interval
+    pub fn ceil(&self) -> Option<Integer> {
+        let lower = shr_round(&self.lower, self.precision, true);
+        Some(lower).filter(|lower| *lower == shr_round(&self.upper, self.precision, true))
+    }
+
+    /// Whether every number in the interval is above zero
+    pub(crate) fn is_positive(&self) -> bool {
+        self.lower.sign == Sign::Positive && !self.lower.size.is_zero()
+    }
+
+    /// Whether the upper bound is at most one unit of 2^-precision
+    pub(crate) fn is_negligible(&self) -> bool {
+        self.upper <= Integer::from(1i64)
+    }
+
+    /// The upper bound, rounded up to an integer
+    pub(crate) fn ceil_upper(&self) -> Integer {
+        shr_round(&self.upper, self.precision, true)
+    }
+
+    pub(crate) fn add(&self, other: &Self) -> Self {
+        debug_assert_eq!(self.precision, other.precision);
+        Self {
+            lower: &self.lower + &other.lower,
+            upper: &self.upper + &other.upper,
+            precision: self.precision,
+        }
+    }
+
+    pub(crate) fn sub(&self, other: &Self) -> Self {
+        debug_assert_eq!(self.precision, other.precision);
+        Self {
+            lower: &self.lower - &other.upper,
+            upper: &self.upper - &other.lower,
+            precision: self.precision,
+        }
+    }
+
+    pub(crate) fn mul(&self, other: &Self) -> Self {
+        debug_assert_eq!(self.precision, other.precision);
+        let (lower, upper) = if self.lower.sign == Sign::Positive && other.lower.sign == Sign::Positive {
+            (&self.lower * &other.lower, &self.upper * &other.upper)
+        }
+        else {
+            let mut products = [&self.lower * &other.lower, &self.lower * &other.upper,
+                                &self.upper * &other.lower, &self.upper * &other.upper];
+            products.sort();
+            let [lower, _, _, upper] = products;
+            (lower, upper)
+        };
+        Self {
+            lower: shr_round(&lower, self.precision, false),
+            upper: shr_round(&upper, self.precision, true),
+            precision: self.precision,
+        }
+    }
+
+    /// Divides by an interval lying above zero
+    pub(crate) fn div(&self, other: &Self) -> Self {
+        debug_assert_eq!(self.precision, other.precision);
+        assert!(other.is_positive(), "division by an interval which may not be positive");
+        let (lower_divisor, upper_divisor) = match (self.lower.sign, self.upper.sign) {
+            (Sign::Positive, _) => (&other.upper, &other.lower),
+            (_, Sign::Negative) => (&other.lower, &other.upper),
+            _ => (&other.lower, &other.lower),
+        };
+        Self {
+            lower: div_round(&shl(&self.lower, self.precision), &lower_divisor.size, false),
+            upper: div_round(&shl(&self.upper, self.precision), &upper_divisor.size, true),
+            precision: self.precision,
+        }
+    }
+
+    /// Multiplies by a Natural, which needs no rounding
+    pub(crate) fn scale(&self, n: &Natural) -> Self {
+        let n = Integer::from(n.clone());
+        Self {
+            lower: &self.lower * &n,
+            upper: &self.upper * &n,
+            precision: self.precision,
+        }
+    }
+
+    /// Divides by a positive Natural
+    pub(crate) fn div_natural(&self, d: &Natural) -> Self {
+        Self {
+            lower: div_round(&self.lower, d, false),
+            upper: div_round(&self.upper, d, true),
+            precision: self.precision,
+        }
+    }
+
+    /// Moves each bound outwards by the given number of units of 2^-precision
+    pub(crate) fn widen(&self, units: u64) -> Self {
+        let units = Integer::from(units);
+        Self {
+            lower: &self.lower - &units,
+            upper: &self.upper + &units,
+            precision: self.precision,
+        }
+    }
+
+    /// Moves the upper bound up by the given number of units of 2^-precision
+    pub(crate) fn raise_upper(&self, units: u64) -> Self {
+        Self { upper: &self.upper + &Integer::from(units), ..self.clone() }
+    }
+
+    /// Changes the precision, rounding the bounds outwards if it is reduced
+    pub(crate) fn with_precision(&self, precision: u64) -> Self {
+        let (lower, upper) = match precision.cmp(&self.precision) {
+            Ordering::Less => {
+                let k = self.precision - precision;
+                (shr_round(&self.lower, k, false), shr_round(&self.upper, k, true))
+            }
+            _ => {
+                let k = precision - self.precision;
+                (shl(&self.lower, k), shl(&self.upper, k))
+            }
+        };
+        Self { lower, upper, precision }
+    }
+
+    /// The natural logarithm, of an interval lying above zero
+    pub(crate) fn ln(&self) -> Self {
+        assert!(self.is_positive(), "logarithm of an interval which may not be positive");
+        let precision = self.precision + GUARD_BITS + bits(self.precision);
+        let shift = ln2(precision).scale(&Natural::from(self.precision));
+        let lower = ln_natural(&self.lower.size, precision).sub(&shift).lower;
+        let upper = ln_natural(&self.upper.size, precision).sub(&shift).upper;
+        Self { lower, upper, precision }.with_precision(self.precision)
+    }
+}
+
+impl fmt::Display for RealInterval {
+    /// Writes the bounds in decimal as [lower, upper], rounded outwards to the precision
+    /// of the formatter, or else to as many places as the precision of the interval
+    /// justifies
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let places = f.precision().unwrap_or((self.precision * 30103 / 100_000) as usize);
+        let scale = Natural::from(10u64).pow(places as u64);
+        let decimal = |n: &Integer, ceiling: bool| {
+            let n = shr_round(&(n * &Integer::from(scale.clone())), self.precision, ceiling);
+            let digits = n.size.to_string();
+            let digits = format!("{:0>width$}", digits, width = places + 1);
+            let (whole, fraction) = digits.split_at(digits.len() - places);
+            let sign = if n.sign == Sign::Negative { "-" } else { "" };
+            if places == 0 { format!("{}{}", sign, whole) } else { format!("{}{}.{}", sign, whole, fraction) }
+        };
+        write!(f, "[{}, {}]", decimal(&self.lower, false), decimal(&self.upper, true))
+    }
+}
+
+/// Extra bits carried by the functions below, to absorb the rounding of many terms
+const GUARD_BITS: u64 = 32;
+
+/// The number of bits in n, for sizing the guard against errors growing with n
+fn bits(n: u64) -> u64 {
+    64 - n.leading_zeros() as u64
+}
+
+/// atanh(a / b) for 0 <= a / b <= 1/3, from the series sum(u^(2k+1) / (2k+1))
+fn atanh(a: &Natural, b: &Natural, precision: u64) -> RealInterval {
+    let (a2, b2) = (a * a, b * b);
+    // Bounds on u^(2k+1) in fixed point
+    let mut power = RealInterval::from_natural(a, precision).div_natural(b);
+    let mut sum = power.clone();
+    for k in 1u64.. {
+        power = power.scale(&a2).div_natural(&b2);
+        sum = sum.add(&power.div_natural(&Natural::from(2 * k + 1)));
+        if power.is_negligible() {
+            // The remaining terms add up to less than u^(2k+1) / 8
+            return sum.widen(1);
+        }
+    }
+    unreachable!()
+}
+
+/// Bounds on ln 2 = 2 atanh(1/3)
+pub(crate) fn ln2(precision: u64) -> RealInterval {
+    atanh(&Natural::from(1u64), &Natural::from(3u64), precision + GUARD_BITS)
+        .scale(&Natural::from(2u64))
+        .with_precision(precision)
+}
+
+/// Bounds on ln m, for m at least one, exactly as given
+fn ln_exact(m: &Natural, precision: u64, ln2: &RealInterval) -> RealInterval {
+    let e = m.bit_length() - 1;
+    let power = Natural::from(shl_slice(&[1], e as usize));
+    // Take t = m / 2^e or m / 2^(e+1), whichever is closer to 1, so that |u| < 0.172
+    let double = &power + &power;
+    if m * m < &power * &double {
+        let u = atanh(&(m - &power), &(m + &power), precision);
+        ln2.scale(&Natural::from(e)).add(&u.scale(&Natural::from(2u64)))
+    }
+    else {
+        let u = atanh(&(&double - m), &(&double + m), precision);
+        ln2.scale(&Natural::from(e + 1)).sub(&u.scale(&Natural::from(2u64)))
+    }
+}
+
+///
+/// Bounds on the natural logarithm of n, which must be at least one
+pub(crate) fn ln_natural(n: &Natural, precision: u64) -> RealInterval {
+    assert!(!n.is_zero(), "logarithm of zero");
+    // ln 2 is multiplied by up to the length of n
+    let (result, precision) = (precision, precision + GUARD_BITS + bits(n.bit_length()));
+    let ln2 = ln2(precision);
+    // Digits of n beyond the precision change its logarithm by too little to matter, so
+    // n is rounded down and up and the logarithm taken of each
+    let shift = n.bit_length().saturating_sub(precision + 16);
+    if shift == 0 {
+        return ln_exact(n, precision, &ln2).with_precision(result);
+    }
+    let low = Natural::from(shr_slice(&n.digits, shift as usize));
+    let high = &low + &Natural::from(1u64);
+    let offset = ln2.scale(&Natural::from(shift));
+    RealInterval {
+        lower: ln_exact(&low, precision, &ln2).add(&offset).lower,
+        upper: ln_exact(&high, precision, &ln2).add(&offset).upper,
+        precision,
+    }.with_precision(result)
+}
+
+/// Bounds on Euler's constant gamma, by the method of Brent and McMillan
+pub(crate) fn euler_gamma(precision: u64) -> RealInterval {
+    let (result, precision) = (precision, precision + GUARD_BITS + bits(precision));
+    // pi e^(-4m) < 2^-precision when 4m > precision ln 2 + ln pi, for which m above
+    // (0.17330 precision + 0.3) is enough, and the series are cut off after 4m terms
+    let m = (precision * 17_330 + 30_000) / 100_000 + 1;
+    let m2 = Natural::from(m * m);
+    let one = RealInterval::from_natural(&Natural::from(1u64), precision);
+    let mut b = one.clone();
+    let mut h = RealInterval::from_natural(&Natural::ZERO, precision);
+    let mut sum_bh = h.clone();
+    let mut sum_b = one.clone();
+    for k in 1..=4 * m {
+        let k = Natural::from(k);
+        b = b.scale(&m2).div_natural(&(&k * &k));
+        h = h.add(&one.div_natural(&k));
+        sum_bh = sum_bh.add(&b.mul(&h));
+        sum_b = sum_b.add(&b);
+    }
+    // Beyond 4m the B_k fall by a factor of 16 or more at each step, and the H_k grow by
+    // less than one over all of them, so the tails are below B_4m / 15 and B_4m (H_4m + 1) / 15
+    let fifteen = Natural::from(15u64);
+    let tail_b = b.div_natural(&fifteen);
+    let tail_bh = b.mul(&h.add(&one)).div_natural(&fifteen);
+    let sum_bh = RealInterval { upper: sum_bh.add(&tail_bh).upper, ..sum_bh };
+    let sum_b = RealInterval { upper: sum_b.add(&tail_b).upper, ..sum_b };
+    let ratio = sum_bh.div(&sum_b).sub(&ln_natural(&Natural::from(m), precision));
+    // Less K_0(2m) / I_0(2m), which lies in (0, 2^-precision)
+    RealInterval { lower: &ratio.lower - &Integer::from(1i64), ..ratio }.with_precision(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the interval meets the number with the given decimal digits, to within
+    /// a unit in the last digit, and is narrow
+    fn check(interval: &RealInterval, digits: &str) {
+        let (whole, fraction) = digits.split_once('.').unwrap();
+        let scale = Integer::from(Natural::from(10u64).pow(fraction.len() as u64));
+        let n: Integer = format!("{}{}", whole, fraction).parse().unwrap();
+        let one = Integer::from(1i64);
+        let p = interval.precision;
+        assert!(&interval.lower * &scale <= shl(&(&n + &one), p), "{} is above {}", interval, digits);
+        assert!(&interval.upper * &scale >= shl(&(&n - &one), p), "{} is below {}", interval, digits);
+        assert!(&interval.upper - &interval.lower <= Integer::from(64i64), "{} is too wide", interval);
+    }
+
+    #[test]
+    fn test_constants() {
+        for precision in [64, 200, 1000] {
+            check(&ln2(precision), "0.693147180559945309417232121458176568075500134360255254120680");
+            check(&euler_gamma(precision), "0.577215664901532860606512090082402431042159335939923598805767");
+        }
+        assert_eq!(format!("{:.20}", ln2(100)), "[0.69314718055994530941, 0.69314718055994530942]");
+    }
+
+    #[test]
+    fn test_ln() {
+        check(&ln_natural(&Natural::from(1u64), 100), "0.0");
+        check(&ln_natural(&Natural::from(10u64), 200), "2.302585092994045684017991454684364207601101488628772976033328");
+        // A number of 400 bits, rounded to the precision before taking the logarithm
+        check(&ln_natural(&Natural::from(10u64).pow(120), 100), "276.310211159285482082158974562123704912");
+        let half = RealInterval::from_natural(&Natural::from(1u64), 100).div_natural(&Natural::from(2u64));
+        check(&half.ln(), "-0.693147180559945309417232121458");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let third = RealInterval::from_natural(&Natural::from(1u64), 64).div_natural(&Natural::from(3u64));
+        let minus = RealInterval::from_natural(&Natural::ZERO, 64).sub(&third);
+        check(&third.mul(&minus), "-0.1111111111111111111");
+        check(&minus.div(&third), "-1.0");
+        assert_eq!(minus.floor(), Some(Integer::from(-1i64)));
+        assert_eq!(minus.ceil(), Some(Integer::from(0i64)));
+        assert_eq!(third.with_precision(2).upper, Integer::from(2i64));
+        assert_eq!(format!("{:.3}", minus), "[-0.334, -0.333]");
+    }
+}
@@ -0,0 +1,264 @@
+// Integer roots of Natural numbers
+//
+// Roots are found by Newton's iteration x -> ((k - 1)x + n / x^(k - 1)) / k, started
+// from a floating-point estimate. By the AM-GM inequality one step from any positive
+// x lands on or above the true root, and from there the iteration decreases
+// monotonically to the floor of the root.
+
+use crate::natural::Natural;
+use crate::algorithms::{shl_slice, div_by_single_digit};
+
+/// Returns a positive estimate of the k-th root of a non-zero n, accurate to
+/// roughly the precision of an f64
+fn estimate_root(n: &Natural, k: u64) -> Natural {
+    let (m, e) = n.to_f64_with_exponent();
+    let log = (e as f64 + m.log2()) / k as f64;
+    if log < 60.0 {
+        Natural::from(2f64.powf(log) as u64 + 1)
+    }
+    else {
+        // Keep 52 bits of the estimate and shift the rest in
+        let shift = log.floor() - 52.0;
+        let mantissa = 2f64.powf(log - shift) as u64 + 1;
+        Natural::from(shl_slice(&[mantissa], shift as usize))
+    }
+}
+
+/// Takes one Newton step towards the k-th root of n from x
+fn newton_step(n: &Natural, k: u64, x: &Natural) -> Natural {
+    let k_natural = Natural::from(k);
+    let (q, _) = n.div(&x.pow(k - 1));
+    let numerator = &(&Natural::from(k - 1) * x) + &q;
+    numerator.div(&k_natural).0
+}
+
+/// Returns a bitmask with bit r set when r is a square modulo the given modulus
+const fn square_residues(modulus: u64) -> u128 {
+    let mut mask = 0;
+    let mut x = 0;
+    while x < modulus {
+        mask |= 1 << (x * x % modulus);
+        x += 1;
+    }
+    mask
+}
+
+const SQUARES_MOD_64: u128 = square_residues(64);
+const SQUARES_MOD_63: u128 = square_residues(63);
+const SQUARES_MOD_65: u128 = square_residues(65);
+const SQUARES_MOD_11: u128 = square_residues(11);
+
+impl Natural {
+    ///
+    /// Returns the floor of the k-th root of this number
+    ///
+    /// # Panics
+    /// Panics if k is zero
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(1000u64).root(3), Natural::from(10u64));
+    /// assert_eq!(Natural::from(999u64).root(3), Natural::from(9u64));
+    /// ```
+    pub fn root(&self, k: u64) -> Natural {
+        assert!(k > 0, "Cannot take the zeroth root of a number");
+        if k == 1 || self.is_zero() {
+            return self.clone();
+        }
+        if k >= self.bit_length() {
+            // The root is less than two
+            return Natural::from(1u64);
+        }
+        let mut x = newton_step(self, k, &estimate_root(self, k));
+        loop {
+            let y = newton_step(self, k, &x);
+            if y >= x {
+                return x;
+            }
+            x = y;
+        }
+    }
+
+    ///
+    /// Returns the floor of the k-th root of this number and the remainder,
+    /// i.e. (r, n - r^k)
+    ///
+    /// # Panics
+    /// Panics if k is zero
+    pub fn root_rem(&self, k: u64) -> (Natural, Natural) {
+        let r = self.root(k);
+        let rem = self - &r.pow(k);
+        (r, rem)
+    }
+
+    ///
+    /// Returns the floor of the square root of this number
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(99u64).sqrt(), Natural::from(9u64));
+    /// ```
+    pub fn sqrt(&self) -> Natural {
+        self.root(2)
+    }
+
+    ///
+    /// Returns the floor of the square root of this number and the remainder,
+    /// i.e. (s, n - s^2)
+    pub fn sqrt_rem(&self) -> (Natural, Natural) {
+        self.root_rem(2)
+    }
+
+    ///
+    /// Whether this number is the square of a natural number
+    ///
+    /// Most non-squares are rejected by looking at residues modulo small numbers,
+    /// before any square root is taken.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert!(Natural::from(144u64).is_perfect_square());
+    /// assert!(!Natural::from(145u64).is_perfect_square());
+    /// ```
+    pub fn is_perfect_square(&self) -> bool {
+        self.could_be_square() && self.sqrt_rem().1.is_zero()
+    }
+
+    /// Checks whether the residues of this number modulo 64, 63, 65 and 11 are all
+    /// squares, which rejects all but about 1 in 100 non-squares
+    fn could_be_square(&self) -> bool {
+        if self.is_zero() {
+            return true;
+        }
+        let has = |mask: u128, r: u64| (mask >> r) & 1 == 1;
+        if !has(SQUARES_MOD_64, self.digits[0] % 64) {
+            return false;
+        }
+        // 63 * 65 * 11 = 45045, so one single-digit division gives all three residues
+        let (_, r) = div_by_single_digit(&self.digits, 45045);
+        has(SQUARES_MOD_63, r % 63) && has(SQUARES_MOD_65, r % 65) && has(SQUARES_MOD_11, r % 11)
+    }
+
+    ///
+    /// If this number is a perfect power b^e with e at least 2, returns the
+    /// smallest such base b together with its exponent e
+    ///
+    /// Zero and one are returned as themselves squared.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// assert_eq!(Natural::from(64u64).is_perfect_power(), Some((Natural::from(2u64), 6)));
+    /// assert_eq!(Natural::from(72u64).is_perfect_power(), None);
+    /// ```
+    pub fn is_perfect_power(&self) -> Option<(Natural, u64)> {
+        if *self <= Natural::from(1u64) {
+            return Some((self.clone(), 2));
+        }
+        // If n = b^e then n is a p-th power for every prime p dividing e, and the
+        // exponent can be at most the bit length of n
+        let max_exponent = self.bit_length();
+        for p in 2..=max_exponent {
+            if !is_small_prime(p) {
+                continue;
+            }
+            if p == 2 && !self.could_be_square() {
+                continue;
+            }
+            let (r, rem) = self.root_rem(p);
+            if rem.is_zero() {
+                return match r.is_perfect_power() {
+                    Some((b, e)) if r > Natural::from(1u64) => Some((b, e * p)),
+                    _ => Some((r, p)),
+                };
+            }
+        }
+        None
+    }
+}
+
+/// Trial division primality test for small exponents
+fn is_small_prime(p: u64) -> bool {
+    p >= 2 && (2..).take_while(|d| d * d <= p).all(|d| !p.is_multiple_of(d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    const NINE: u64 = u64::MAX;
+
+    #[test]
+    fn test_sqrt_small() {
+        for n in 0u64..2000 {
+            let s = Natural::from(n).sqrt();
+            let s = u64::try_from(&s).unwrap();
+            assert!(s * s <= n && (s + 1) * (s + 1) > n, "sqrt({}) = {}", n, s);
+        }
+    }
+
+    #[test]
+    fn test_root_rem_large() {
+        let base = Natural::from(vec!(0x1234_5678_9abc_def1, 0xfedc_ba98, 42));
+        for k in 2..8 {
+            let n = base.pow(k);
+            assert_eq!(n.root_rem(k), (base.clone(), Natural::ZERO));
+            let below = &n - &Natural::from(1u64);
+            let (r, rem) = below.root_rem(k);
+            assert_eq!(r, &base - &Natural::from(1u64));
+            assert_eq!(&r.pow(k) + &rem, below);
+        }
+    }
+
+    #[test]
+    fn test_root_of_huge_numbers() {
+        // 1000 limbs exercises the non-trivial estimate and Karatsuba multiplication
+        let n = Natural::from(vec!(0x9e37_79b9_7f4a_7c15; 1000));
+        let (s, rem) = n.sqrt_rem();
+        assert_eq!(&(&s * &s) + &rem, n);
+        assert!(rem <= &s + &s);
+        let r = n.root(7);
+        assert!(r.pow(7) <= n);
+        assert!((&r + &Natural::from(1u64)).pow(7) > n);
+    }
+
+    #[test]
+    fn test_large_k() {
+        let n = Natural::from(vec!(0, 0, 1)); // 2^128
+        assert_eq!(n.root(128), Natural::from(2u64));
+        assert_eq!(n.root(129), Natural::from(1u64));
+        assert_eq!(n.root(1000), Natural::from(1u64));
+        assert_eq!(Natural::from(5u64).root(1), Natural::from(5u64));
+        assert_eq!(Natural::ZERO.root(3), Natural::ZERO);
+    }
+
+    #[test]
+    fn test_is_perfect_square() {
+        for n in 0u64..3000 {
+            let s = (n as f64).sqrt() as u64;
+            assert_eq!(Natural::from(n).is_perfect_square(), s * s == n, "{}", n);
+        }
+        let big = Natural::from(vec!(NINE, 7, 3)).pow(2);
+        assert!(big.is_perfect_square());
+        assert!(!(&big + &Natural::from(1u64)).is_perfect_square());
+    }
+
+    #[test]
+    fn test_is_perfect_power() {
+        assert_eq!(Natural::from(1u64 << 60).is_perfect_power(), Some((Natural::from(2u64), 60)));
+        assert_eq!(Natural::from(36u64).is_perfect_power(), Some((Natural::from(6u64), 2)));
+        assert_eq!(Natural::from(6u64.pow(15)).is_perfect_power(), Some((Natural::from(6u64), 15)));
+        assert_eq!(Natural::from(2u64 * 3u64.pow(20)).is_perfect_power(), None);
+        assert_eq!(Natural::from(7u64).is_perfect_power(), None);
+        let b = Natural::from(vec!(3, 5));
+        assert_eq!(b.pow(21).is_perfect_power(), Some((b, 21)));
+    }
+}
@@ -0,0 +1,382 @@
+// Segmented sieve of Eratosthenes over windows of the Natural numbers
+//
+// A window [a, b) is sieved one segment at a time, each segment small enough to stay
+// in the L1 cache. Within a segment, each byte holds the 8 residues modulo 30 which are
+// coprime to 30, so multiples of 2, 3 and 5 take no space at all. Each sieving prime p
+// keeps the offset of its next multiple p * k from one segment to the next, with k
+// stepping over the same residues so that only multiples not already removed by the
+// wheel are crossed off. The byte and bit of each of these multiples follow a cycle
+// of length 8, which is worked out once per prime.
+//
+// The sieving primes go up to the square root of b, but no further than SIEVE_LIMIT.
+// Windows beyond SIEVE_LIMIT^2 are sieved as deeply as that allows, and the survivors
+// are then given a full primality test.
+
+use core::convert::TryFrom;
+use core::iter::FusedIterator;
+
+use crate::natural::Natural;
+use crate::primality::Primality;
+
+/// The number of bytes in a segment
+const SEGMENT_BYTES: usize = 32 * 1024;
+
+/// The number of consecutive numbers in a segment
+const SEGMENT_LEN: u64 = 30 * SEGMENT_BYTES as u64;
+
+/// The largest sieving prime
+const SIEVE_LIMIT: u64 = 1 << 22;
+
+/// The residues modulo 30 held in each byte, one per bit
+const RESIDUES: [u64; 8] = [1, 7, 11, 13, 17, 19, 23, 29];
+
+/// The gap from each residue to the next, wrapping around from 29 to 31
+const STEPS: [u64; 8] = [6, 4, 2, 4, 2, 4, 6, 2];
+
+/// The bit holding each residue modulo 30, or zero for residues sharing a factor with 30
+const BIT: [u8; 30] = {
+    let mut bit = [0; 30];
+    let mut i = 0;
+    while i < 8 {
+        bit[RESIDUES[i] as usize] = 1 << i;
+        i += 1;
+    }
+    bit
+};
+
+/// The index into RESIDUES of a residue coprime to 30
+fn wheel_index(r: u64) -> usize {
+    RESIDUES.iter().position(|&s| s == r).unwrap()
+}
+
+/// Returns the primes from 7 up to and including limit
+fn sieving_primes(limit: u64) -> Vec<u64> {
+    let limit = limit as usize;
+    let mut composite = vec!(false; limit + 1);
+    let mut primes = Vec::new();
+    for n in 2..=limit {
+        if !composite[n] {
+            if n >= 7 {
+                primes.push(n as u64);
+            }
+            for m in (n * n..=limit).step_by(n) {
+                composite[m] = true;
+            }
+        }
+    }
+    primes
+}
+
+/// A sieving prime, with the next multiple of it to cross off
+#[derive(Debug,Clone)]
+struct SievingPrime {
+    /// The offset in bytes of the next multiple p * k from the start of the current segment
+    byte: usize,
+    /// The index into RESIDUES of k modulo 30
+    wheel: usize,
+    /// The byte offset between each multiple and the next, by the index of k
+    steps: [usize; 8],
+    /// The bit of each multiple within its byte, by the index of k
+    bits: [u8; 8],
+}
+
+impl SievingPrime {
+    /// Finds the first multiple of p to cross off at or above base, which is a multiple of 30
+    fn new(p: u64, base: &Natural) -> Self {
+        let square = p * p;
+        let (k, offset) = match u64::try_from(base) {
+            Ok(base) if base <= square => (p, square - base),
+            _ => {
+                // With base = 30pq + r, the first multiple above base is 30pq + p * ceil(r / p)
+                let r = base.rem_digit(30 * p);
+                let mut k = r.div_ceil(p);
+                while BIT[(k % 30) as usize] == 0 {
+                    k += 1;
+                }
+                (k, p * k - r)
+            }
+        };
+        // With p = 30q + s, the multiple p * k lies in byte qk + floor(sk / 30) at residue sk mod 30
+        let (q, s) = (p / 30, p % 30);
+        let mut steps = [0; 8];
+        let mut bits = [0; 8];
+        for i in 0..8 {
+            let (k, next) = (RESIDUES[i], RESIDUES[i] + STEPS[i]);
+            steps[i] = (q * STEPS[i] + s * next / 30 - s * k / 30) as usize;
+            bits[i] = BIT[(s * k % 30) as usize];
+        }
+        Self {
+            byte: (offset / 30) as usize,
+            wheel: wheel_index(k % 30),
+            steps,
+            bits,
+        }
+    }
+
+    /// Crosses off the multiples in a segment, then moves on to the next
+    fn cross_off(&mut self, bytes: &mut [u8]) {
+        while self.byte < bytes.len() {
+            bytes[self.byte] &= !self.bits[self.wheel];
+            self.byte += self.steps[self.wheel];
+            self.wheel = (self.wheel + 1) & 7;
+        }
+        self.byte -= bytes.len();
+    }
+}
+
+///
+/// A segmented sieve of Eratosthenes over the primes in [a, b), in increasing order
+///
+/// The window may start far beyond 2^64. When b is above 2^44 the sieve cannot reach
+/// the square root of b, and numbers surviving it are given the Baillie-PSW test, so
+/// that above 2^64 the same caveat applies as for `Primes`.
+///
+/// `count` works on the sieve directly, without building each prime as a Natural.
+///
+/// # Example
+/// ```rust
+/// use skewes::{Natural, SegmentedSieve};
+///
+/// let primes: Vec<Natural> = SegmentedSieve::new(&Natural::from(90u64), &Natural::from(110u64)).collect();
+/// assert_eq!(primes, [97u64, 101, 103, 107, 109].iter().map(|&p| Natural::from(p)).collect::<Vec<_>>());
+/// assert_eq!(SegmentedSieve::new(&Natural::ZERO, &Natural::from(10u64).pow(6)).count(), 78498);
+/// ```
+#[derive(Debug,Clone)]
+pub struct SegmentedSieve {
+    /// The first number of the current segment, a multiple of 30
+    base: Natural,
+    /// The end of the window, exclusive
+    end: Natural,
+    /// The start of the window, for masking the first segment
+    start: Natural,
+    /// Whether every number surviving the sieve is prime
+    exact: bool,
+    primes: Vec<SievingPrime>,
+    /// The primes 2, 3 and 5 which lie in the window, in decreasing order
+    small: Vec<u64>,
+    /// The bits of the current segment
+    bytes: Vec<u8>,
+    /// The position in the current segment of the next byte to examine
+    index: usize,
+    /// The bits of the current byte not yet examined
+    bits: u8,
+}
+
+impl SegmentedSieve {
+    ///
+    /// Creates a sieve over the primes in [a, b)
+    pub fn new(a: &Natural, b: &Natural) -> Self {
+        let end = if a < b { b.clone() } else { a.clone() };
+        let base = a - &Natural::from(a.rem_digit(30));
+        let small = [5u64, 3, 2].iter().copied()
+            .filter(|&p| *a <= Natural::from(p) && Natural::from(p) < end)
+            .collect();
+        let root = end.saturating_sub(&Natural::from(1u64)).sqrt();
+        let limit = u64::try_from(&root).map_or(SIEVE_LIMIT, |root| root.min(SIEVE_LIMIT));
+        let primes = sieving_primes(limit).into_iter().map(|p| SievingPrime::new(p, &base)).collect();
+        Self {
+            base,
+            start: a.clone(),
+            exact: root <= Natural::from(SIEVE_LIMIT),
+            end,
+            primes,
+            small,
+            bytes: Vec::new(),
+            index: 0,
+            bits: 0,
+        }
+    }
+
+    /// Sieves the next segment, returning false if the window is exhausted
+    fn next_segment(&mut self) -> bool {
+        self.base = &self.base + &Natural::from(30 * self.bytes.len() as u64);
+        if self.end <= self.base {
+            self.bytes.clear();
+            self.index = 0;
+            return false;
+        }
+        let remaining = u64::try_from(&(&self.end - &self.base)).unwrap_or(SEGMENT_LEN);
+        let len = remaining.min(SEGMENT_LEN).div_ceil(30) * 30;
+        self.bytes.clear();
+        self.bytes.resize((len / 30) as usize, 0xff);
+        for prime in &mut self.primes {
+            prime.cross_off(&mut self.bytes);
+        }
+        if self.base.is_zero() {
+            // 1 is not prime
+            self.bytes[0] &= !1;
+        }
+        if self.base < self.start {
+            let below = u64::try_from(&(&self.start - &self.base)).unwrap();
+            self.mask(|r| r < below);
+        }
+        if remaining < len {
+            self.mask(|r| r >= remaining);
+        }
+        self.index = 0;
+        self.bits = 0;
+        true
+    }
+
+    /// Clears the bits of the numbers in the current segment whose offset satisfies the predicate
+    fn mask<F: Fn(u64) -> bool>(&mut self, outside: F) {
+        for (i, byte) in self.bytes.iter_mut().enumerate() {
+            for (j, &r) in RESIDUES.iter().enumerate() {
+                if outside(30 * i as u64 + r) {
+                    *byte &= !(1 << j);
+                }
+            }
+        }
+    }
+
+    /// Returns the next prime in the current segment
+    fn next_in_segment(&mut self) -> Option<Natural> {
+        loop {
+            while self.bits == 0 {
+                self.bits = *self.bytes.get(self.index)?;
+                self.index += 1;
+            }
+            let j = self.bits.trailing_zeros() as usize;
+            self.bits &= self.bits - 1;
+            let n = &self.base + &Natural::from(30 * (self.index as u64 - 1) + RESIDUES[j]);
+            if self.exact || n.is_probable_prime(0) != Primality::Composite {
+                return Some(n);
+            }
+        }
+    }
+
+    /// Counts the primes below n which have not yet been returned, moving past them, so
+    /// that a series of increasing bounds can be counted up to without building each
+    /// prime. The sieve must be exact.
+    pub(crate) fn count_below(&mut self, n: u64) -> u64 {
+        debug_assert!(self.exact);
+        let mut count = 0;
+        while self.small.last().is_some_and(|&p| p < n) {
+            self.small.pop();
+            count += 1;
+        }
+        loop {
+            let offset = match u64::try_from(&Natural::from(n).saturating_sub(&self.base)) {
+                Ok(offset) if offset < 30 * self.bytes.len() as u64 => offset,
+                _ => {
+                    // The whole of the current segment lies below n
+                    count += self.bits.count_ones() as u64;
+                    count += self.bytes[self.index..].iter().map(|b| b.count_ones() as u64).sum::<u64>();
+                    if !self.next_segment() {
+                        return count;
+                    }
+                    continue;
+                }
+            };
+            let (k, r) = ((offset / 30) as usize, offset % 30);
+            let below = RESIDUES.iter().enumerate()
+                .filter(|&(_, &s)| s < r)
+                .fold(0u8, |mask, (j, _)| mask | 1 << j);
+            if self.index > k {
+                // Only the byte being examined can still hold primes below n
+                if self.index == k + 1 {
+                    count += (self.bits & below).count_ones() as u64;
+                    self.bits &= !below;
+                }
+                return count;
+            }
+            count += self.bits.count_ones() as u64;
+            count += self.bytes[self.index..k].iter().map(|b| b.count_ones() as u64).sum::<u64>();
+            count += (self.bytes[k] & below).count_ones() as u64;
+            self.bits = self.bytes[k] & !below;
+            self.index = k + 1;
+            return count;
+        }
+    }
+}
+
+impl Iterator for SegmentedSieve {
+    type Item = Natural;
+
+    fn next(&mut self) -> Option<Natural> {
+        if let Some(p) = self.small.pop() {
+            return Some(Natural::from(p));
+        }
+        loop {
+            if let Some(n) = self.next_in_segment() {
+                return Some(n);
+            }
+            if !self.next_segment() {
+                return None;
+            }
+        }
+    }
+
+    fn count(mut self) -> usize {
+        let mut count = self.small.len();
+        loop {
+            if self.exact {
+                count += self.bits.count_ones() as usize;
+                count += self.bytes[self.index..].iter().map(|b| b.count_ones() as usize).sum::<usize>();
+            } else {
+                while self.next_in_segment().is_some() {
+                    count += 1;
+                }
+            }
+            if !self.next_segment() {
+                return count;
+            }
+        }
+    }
+}
+
+impl FusedIterator for SegmentedSieve {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Primes;
+
+    fn sieve(a: &Natural, b: &Natural) -> Vec<Natural> {
+        SegmentedSieve::new(a, b).collect()
+    }
+
+    #[test]
+    fn test_matches_primes() {
+        // Windows with every alignment modulo 30, small and crossing segments
+        for (a, b) in [(0u64, 1u64), (0, 2), (2, 3), (4, 5), (0, 100), (1, 31), (7, 49), (49, 50), (13, 1_000_000), (1_966_070, 1_966_110)] {
+            let (a, b) = (Natural::from(a), Natural::from(b));
+            assert_eq!(sieve(&a, &b), Primes::new(a.clone(), b.clone()).collect::<Vec<_>>());
+            assert_eq!(SegmentedSieve::new(&a, &b).count(), Primes::new(a, b).count());
+        }
+        assert_eq!(SegmentedSieve::new(&Natural::from(100u64), &Natural::from(10u64)).next(), None);
+    }
+
+    #[test]
+    fn test_counts() {
+        assert_eq!(SegmentedSieve::new(&Natural::ZERO, &Natural::from(10_000_000u64)).count(), 664_579);
+        let mut sieve = SegmentedSieve::new(&Natural::ZERO, &Natural::from(100u64));
+        sieve.nth(4);
+        assert_eq!(sieve.count(), 20);
+        // Counting up to increasing bounds, between primes taken one at a time
+        let mut sieve = SegmentedSieve::new(&Natural::ZERO, &Natural::from(2_000_000u64));
+        assert_eq!(sieve.count_below(10), 4);
+        assert_eq!(sieve.count_below(100), 21);
+        assert_eq!(sieve.next(), Some(Natural::from(101u64)));
+        assert_eq!(sieve.count_below(50), 0);
+        assert_eq!(sieve.count_below(1_000_000), 78_498 - 26);
+        // Bounds beyond the window stop at its end
+        assert_eq!(sieve.count_below(3_000_000), 148_933 - 78_498);
+    }
+
+    #[test]
+    fn test_large_windows() {
+        let a = Natural::from(1_000_000_000_000u64);
+        let b = &a + &Natural::from(100_000u64);
+        assert_eq!(sieve(&a, &b), Primes::new(a.clone(), b.clone()).collect::<Vec<_>>());
+        // Beyond the reach of the sieving primes, and beyond u64
+        let a = Natural::from(u64::MAX - 100_000);
+        let b = &a + &Natural::from(200_000u64);
+        assert_eq!(sieve(&a, &b), Primes::new(a.clone(), b.clone()).collect::<Vec<_>>());
+        let a = Natural::from(3u64).pow(200);
+        let b = &a + &Natural::from(20_000u64);
+        let primes = sieve(&a, &b);
+        assert!(!primes.is_empty());
+        assert_eq!(primes, Primes::new(a, b).collect::<Vec<_>>());
+    }
+}
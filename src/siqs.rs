@@ -0,0 +1,704 @@
+// The self-initialising quadratic sieve
+//
+// To factor n, a multiplier k is chosen to make small primes plentiful in the factor
+// base, the primes p for which kn is a square modulo p. Relations are found among the
+// values of polynomials Q(x) = (ax + b)^2 - kn, where a is a product of factor base
+// primes near sqrt(2kn) / M and b^2 = kn (mod a), so that Q(x) = a g(x) with
+// g(x) = ax^2 + 2bx + c and |g(x)| below M sqrt(kn / 2) for x in [-M, M). Each a
+// gives many b, stepped through in a Gray code so that only one addition per prime
+// is needed to move the sieve roots from one polynomial to the next.
+//
+// For each polynomial, the approximate base 2 logarithm of each factor base prime is
+// added at the x where it divides g(x), and those x where the total comes close to the
+// size of g(x) are trial divided. Values left with a single prime above the factor
+// base, below a large prime bound, are kept as partial relations, and two with the
+// same large prime make a full relation.
+//
+// Once there are more relations than primes, Gaussian elimination over GF(2) finds
+// subsets whose product is a square on both sides, X^2 = Y^2 (mod n), and the gcd of
+// X - Y with n is usually a proper factor.
+//
+// The elimination is dense, so its time and memory grow with the cube and square of
+// the number of columns. It takes seconds at the factor base for 70 digits but minutes
+// beyond 80 digits, where it outweighs the sieve, and structured elimination or block
+// Lanczos would be needed to go much further.
+
+use core::convert::{TryFrom, TryInto};
+use std::collections::HashMap;
+
+use crate::natural::Natural;
+use crate::integer::{Integer, Sign};
+use crate::primality::{Primality, mul_mod_u64, pow_mod_u64};
+use crate::jacobi::jacobi_u64;
+use crate::primes::Primes;
+use crate::algorithms::shr_slice;
+
+/// The factor base size and sieve half-width M for numbers of up to the given number
+/// of decimal digits. Past 80 digits the dense elimination dominates the running time.
+const PARAMETERS: [(u32, usize, usize); 9] = [
+    (20, 80, 16_384),
+    (30, 150, 32_768),
+    (40, 400, 32_768),
+    (50, 1_200, 32_768),
+    (60, 3_000, 32_768),
+    (70, 6_000, 98_304),
+    (80, 12_000, 131_072),
+    (90, 24_000, 196_608),
+    (100, 40_000, 262_144),
+];
+
+/// The squarefree multipliers considered for kn
+const MULTIPLIERS: [u64; 31] = [1, 2, 3, 5, 6, 7, 10, 11, 13, 14, 15, 17, 19, 21, 22, 23, 26, 29, 30,
+                                31, 33, 34, 35, 37, 38, 39, 41, 42, 43, 46, 47];
+
+/// Primes below this are left out of the sieve, and only found by trial division
+const SIEVE_MIN: u64 = 32;
+
+/// An allowance in bits for the small primes and prime powers left out of the sieve
+const SIEVE_SLACK: f64 = 16.0;
+
+/// The large prime bound as a multiple of the largest prime in the factor base
+const LARGE_PRIME_MULTIPLIER: u64 = 64;
+
+/// The number of relations gathered beyond the number of columns in the matrix
+const EXTRA_RELATIONS: usize = 32;
+
+/// The ideal size in bits of the primes making up a
+const A_PRIME_BITS: f64 = 11.0;
+
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// An approximation to the base 2 logarithm of a non-zero number
+fn log2(n: &Natural) -> f64 {
+    match n.digits.len() {
+        1 => (n.digits[0] as f64).log2(),
+        len => {
+            let top = n.digits[len - 1] as f64 * 2f64.powi(64) + n.digits[len - 2] as f64;
+            top.log2() + 64.0 * (len - 2) as f64
+        }
+    }
+}
+
+/// Returns x modulo p in [0, p)
+fn rem_integer(x: &Integer, p: u64) -> u64 {
+    let r = x.size.rem_digit(p);
+    if x.sign == Sign::Negative && r != 0 { p - r } else { r }
+}
+
+fn inv_mod_u64(a: u64, p: u64) -> u64 {
+    pow_mod_u64(a, p - 2, p)
+}
+
+/// Returns a square root of a quadratic residue a modulo an odd prime p, with the
+/// Tonelli-Shanks algorithm
+fn sqrt_mod_u64(a: u64, p: u64) -> u64 {
+    let a = a % p;
+    if a == 0 {
+        return 0;
+    }
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+    let z = (2..).find(|&z| jacobi_u64(z, p) == -1).unwrap();
+    let mut m = s;
+    let mut c = pow_mod_u64(z, q, p);
+    let mut t = pow_mod_u64(a, q, p);
+    let mut r = pow_mod_u64(a, q.div_ceil(2), p);
+    while t != 1 {
+        let mut i = 0;
+        let mut u = t;
+        while u != 1 {
+            u = mul_mod_u64(u, u, p);
+            i += 1;
+        }
+        let b = pow_mod_u64(c, 1 << (m - i - 1), p);
+        m = i;
+        c = mul_mod_u64(b, b, p);
+        t = mul_mod_u64(t, c, p);
+        r = mul_mod_u64(r, b, p);
+    }
+    r
+}
+
+/// Chooses the multiplier k with the Knuth-Schroeppel function, which estimates how
+/// much the small primes contribute to the values of Q(x)
+fn choose_multiplier(n: &Natural) -> u64 {
+    let score = |k: u64| {
+        let kn = n * &Natural::from(k);
+        let mut score = -0.5 * (k as f64).ln();
+        score += match kn.rem_digit(8) {
+            1 => 2.0,
+            5 => 1.0,
+            3 | 7 => 0.5,
+            _ => 0.0,
+        } * 2f64.ln();
+        for p in (3..1000).step_by(2).filter(|&p| (3..p).take_while(|d| d * d <= p).all(|d| p % d != 0)) {
+            let r = kn.rem_digit(p);
+            if r == 0 {
+                score += (p as f64).ln() / p as f64;
+            }
+            else if jacobi_u64(r, p) == 1 {
+                score += 2.0 * (p as f64).ln() / (p - 1) as f64;
+            }
+        }
+        score
+    };
+    MULTIPLIERS.iter().copied()
+        .map(|k| (k, score(k)))
+        .fold((1, f64::MIN), |best, (k, s)| if s > best.1 { (k, s) } else { best })
+        .0
+}
+
+/// The primes modulo which kn is a square, with the square roots and the logarithms
+struct FactorBase {
+    primes: Vec<u64>,
+    /// A square root of kn modulo each prime
+    roots: Vec<u64>,
+    logs: Vec<u8>,
+}
+
+impl FactorBase {
+    fn new(kn: &Natural, size: usize) -> Self {
+        let mut primes = vec!(2);
+        let mut roots = vec!(kn.rem_digit(2));
+        for p in Primes::starting_at(Natural::from(3u64)) {
+            if primes.len() == size {
+                break;
+            }
+            let p = u64::try_from(&p).unwrap();
+            let r = kn.rem_digit(p);
+            if r == 0 || jacobi_u64(r, p) == 1 {
+                primes.push(p);
+                roots.push(sqrt_mod_u64(r, p));
+            }
+        }
+        let logs = primes.iter().map(|&p| (p as f64).log2().round() as u8).collect();
+        Self {
+            primes,
+            roots,
+            logs,
+        }
+    }
+}
+
+/// A relation (ax + b)^2 = Q(x) (mod n), with Q(x) factored over the factor base
+#[derive(Debug,Clone)]
+struct Relation {
+    /// The square root side, ax + b or a product of these, modulo n
+    y: Natural,
+    /// Whether Q(x) is negative
+    negative: bool,
+    /// The indices in the factor base of the prime factors of Q(x), with repetition
+    factors: Vec<usize>,
+    /// A number whose square also divides Q(x), left over when combining two partial
+    /// relations with the same large prime
+    extra: Natural,
+}
+
+impl Relation {
+    /// Combines two partial relations with the same large prime into a full one
+    fn combine(&self, other: &Relation, large_prime: u64, n: &Natural) -> Relation {
+        Relation {
+            y: &(&self.y * &other.y) % n,
+            negative: self.negative != other.negative,
+            factors: self.factors.iter().chain(&other.factors).copied().collect(),
+            extra: Natural::from(large_prime),
+        }
+    }
+}
+
+/// A choice of a, with the values derived from it which every b shares
+struct Polynomials {
+    a: Natural,
+    /// The indices in the factor base of the primes whose product is a
+    a_factors: Vec<usize>,
+    /// B_l, with b taking the values B_0 +- B_1 +- ... +- B_(s-1)
+    b_terms: Vec<Natural>,
+    /// 2 B_l a^-1 modulo each prime, for moving the roots when B_l changes sign
+    b_steps: Vec<Vec<u64>>,
+    /// a^-1 modulo each prime
+    a_inverses: Vec<u64>,
+}
+
+/// Gathers relations for the number n, which has no factors in the factor base
+struct Sieve<'a> {
+    n: &'a Natural,
+    kn: Integer,
+    base: FactorBase,
+    /// The sieve half-width M
+    m: usize,
+    /// The sieve total above which values are trial divided
+    threshold: u8,
+    large_prime_bound: u64,
+    /// The target size of a, sqrt(2kn) / M
+    a_target: f64,
+    rng: u64,
+    used: Vec<Natural>,
+    relations: Vec<Relation>,
+    partials: HashMap<u64, Relation>,
+}
+
+impl<'a> Sieve<'a> {
+    fn new(n: &'a Natural, k: u64, base_size: usize, m: usize) -> Self {
+        let kn = n * &Natural::from(k);
+        let base = FactorBase::new(&kn, base_size);
+        let largest = *base.primes.last().unwrap();
+        let large_prime_bound = largest.saturating_mul(LARGE_PRIME_MULTIPLIER.min(largest));
+        // The largest values of g(x) are about M sqrt(kn / 2)
+        let g_bits = (m as f64).log2() + log2(&kn) / 2.0 - 0.5;
+        let threshold = g_bits - (large_prime_bound as f64).log2() - SIEVE_SLACK;
+        Self {
+            n,
+            a_target: log2(&(&kn * &Natural::from(2u64)).sqrt()) - (m as f64).log2(),
+            kn: Integer::from(kn),
+            base,
+            m,
+            threshold: threshold.max(0.0) as u8,
+            large_prime_bound,
+            rng: 0x2545_f491_4f6c_dd1d,
+            used: Vec::new(),
+            relations: Vec::new(),
+            partials: HashMap::new(),
+        }
+    }
+
+    /// Whether a factor base prime may be used in a, i.e. it is sieved and does not divide kn
+    fn a_candidate(&self, i: usize) -> bool {
+        self.base.primes[i] >= SIEVE_MIN && self.base.roots[i] != 0
+    }
+
+    /// Chooses a new a as a product of s primes, picking all but the last at random
+    /// from those of about the ideal size, and the last to bring a close to the target
+    fn choose_a(&mut self) -> (Natural, Vec<usize>) {
+        let eligible: Vec<usize> = (0..self.base.primes.len()).filter(|&i| self.a_candidate(i)).collect();
+        let largest_bits = (*self.base.primes.last().unwrap() as f64).log2() - 1.0;
+        let s = (self.a_target / A_PRIME_BITS.min(largest_bits)).ceil().max(1.0) as usize;
+        let prime_bits = self.a_target / s as f64;
+        let mut candidates: Vec<usize> = eligible.iter().copied()
+            .filter(|&i| ((self.base.primes[i] as f64).log2() - prime_bits).abs() <= 1.0)
+            .collect();
+        if candidates.len() < s + 2 {
+            candidates = eligible.clone();
+        }
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let mut chosen: Vec<usize> = Vec::new();
+            while chosen.len() + 1 < s {
+                let i = candidates[(xorshift(&mut self.rng) % candidates.len() as u64) as usize];
+                if !chosen.contains(&i) {
+                    chosen.push(i);
+                }
+            }
+            let bits: f64 = chosen.iter().map(|&i| (self.base.primes[i] as f64).log2()).sum();
+            let wanted = self.a_target - bits;
+            let last = eligible.iter().copied()
+                .filter(|i| !chosen.contains(i))
+                .min_by(|&i, &j| {
+                    let d = |i: usize| ((self.base.primes[i] as f64).log2() - wanted).abs();
+                    d(i).partial_cmp(&d(j)).unwrap()
+                })
+                .unwrap();
+            chosen.push(last);
+            chosen.sort_unstable();
+            let a = chosen.iter().fold(Natural::from(1u64), |a, &i| &a * &Natural::from(self.base.primes[i]));
+            // With few primes to choose from, an a may have to be used again
+            if !self.used.contains(&a) || attempts > 100 {
+                self.used.push(a.clone());
+                return (a, chosen);
+            }
+        }
+    }
+
+    /// Sets up the polynomials for a new a
+    fn polynomials(&mut self) -> Polynomials {
+        let (a, a_factors) = self.choose_a();
+        // B_l is divisible by every prime of a but q_l, and its square is kn modulo q_l
+        let b_terms: Vec<Natural> = a_factors.iter().map(|&i| {
+            let q = self.base.primes[i];
+            let rest = a.div(&Natural::from(q)).0;
+            let mut gamma = mul_mod_u64(self.base.roots[i], inv_mod_u64(rest.rem_digit(q), q), q);
+            if gamma > q / 2 {
+                gamma = q - gamma;
+            }
+            &rest * &Natural::from(gamma)
+        }).collect();
+        let a_inverses: Vec<u64> = self.base.primes.iter().enumerate()
+            .map(|(i, &p)| if p == 2 || a_factors.contains(&i) { 0 } else { inv_mod_u64(a.rem_digit(p), p) })
+            .collect();
+        let b_steps = b_terms.iter().map(|b| {
+            self.base.primes.iter().zip(&a_inverses)
+                .map(|(&p, &inverse)| mul_mod_u64(2 * b.rem_digit(p) % p, inverse, p))
+                .collect()
+        }).collect();
+        Polynomials {
+            a,
+            a_factors,
+            b_terms,
+            b_steps,
+            a_inverses,
+        }
+    }
+
+    /// Sieves every polynomial with the given a, recording the relations found
+    fn sieve_polynomials(&mut self, polynomials: &Polynomials) {
+        let primes = self.base.primes.clone();
+        let m = self.m as u64;
+        let s = polynomials.b_terms.len();
+        let in_a: Vec<bool> = (0..primes.len()).map(|i| polynomials.a_factors.contains(&i)).collect();
+        let mut b = polynomials.b_terms.iter().fold(Integer::from(0), |b, term| &b + &Integer::from(term.clone()));
+        // The positions i = x + M, modulo each prime, at which p divides g(x)
+        let mut roots: Vec<(u64, u64)> = (0..primes.len()).map(|i| {
+            let (p, inverse) = (primes[i], polynomials.a_inverses[i]);
+            if p == 2 || in_a[i] {
+                return (0, 0);
+            }
+            let (t, b) = (self.base.roots[i], rem_integer(&b, p));
+            let root1 = mul_mod_u64(inverse, (t + p - b) % p, p);
+            let root2 = mul_mod_u64(inverse, (2 * p - t - b) % p, p);
+            ((root1 + m) % p, (root2 + m) % p)
+        }).collect();
+        let mut sieve = vec!(0u8; 2 * self.m);
+        for index in 0..1usize << (s - 1) {
+            if index > 0 {
+                // Gray code: flip the sign of one B_l, for l below s - 1
+                let l = index.trailing_zeros() as usize;
+                let term = Integer::from(&polynomials.b_terms[l] * &Natural::from(2u64));
+                let negate = (index ^ (index >> 1)) >> l & 1 == 1;
+                b = if negate { &b - &term } else { &b + &term };
+                let add = |r: u64, step: u64, p: u64| if r + step >= p { r + step - p } else { r + step };
+                for ((root, &p), &step) in roots.iter_mut().zip(&primes).zip(&polynomials.b_steps[l]) {
+                    let step = if negate || step == 0 { step } else { p - step };
+                    *root = (add(root.0, step, p), add(root.1, step, p));
+                }
+            }
+            // Starting from 128 - threshold, the values worth trial dividing have the top bit set
+            let start = 128 - self.threshold.min(128);
+            sieve.iter_mut().for_each(|v| *v = start);
+            for i in 0..primes.len() {
+                let p = primes[i];
+                if p < SIEVE_MIN || in_a[i] {
+                    continue;
+                }
+                let log = self.base.logs[i];
+                let (root1, root2) = roots[i];
+                let (p, mut j, mut k) = (p as usize, root1 as usize, root2 as usize);
+                if root1 == root2 {
+                    k = sieve.len();
+                }
+                while j < sieve.len() {
+                    sieve[j] = sieve[j].wrapping_add(log);
+                    j += p;
+                }
+                while k < sieve.len() {
+                    sieve[k] = sieve[k].wrapping_add(log);
+                    k += p;
+                }
+            }
+            let candidates: Vec<usize> = sieve.chunks_exact(8).enumerate()
+                .filter(|(_, chunk)| u64::from_ne_bytes((*chunk).try_into().unwrap()) & 0x8080_8080_8080_8080 != 0)
+                .flat_map(|(c, chunk)| (0..8).filter(move |&i| chunk[i] & 0x80 != 0).map(move |i| 8 * c + i))
+                .collect();
+            for i in candidates {
+                self.trial_divide(polynomials, &in_a, &roots, &b, i);
+            }
+        }
+    }
+
+    /// Factors g(x) for x = i - M over the factor base, recording a full or partial relation
+    fn trial_divide(&mut self, polynomials: &Polynomials, in_a: &[bool], roots: &[(u64, u64)], b: &Integer, i: usize) {
+        let x = Integer::from(i as i64 - self.m as i64);
+        let y = &(&Integer::from(polynomials.a.clone()) * &x) + b;
+        let g = &(&(&y * &y) - &self.kn) / &Integer::from(polynomials.a.clone());
+        let mut factors = polynomials.a_factors.clone();
+        let mut rest = g.size.clone();
+        if rest.is_zero() {
+            return;
+        }
+        let twos = rest.trailing_zeros().unwrap();
+        if twos > 0 {
+            rest = Natural::from(shr_slice(&rest.digits, twos as usize));
+            factors.extend((0..twos).map(|_| 0));
+        }
+        for (j, &p) in self.base.primes.iter().enumerate().skip(1) {
+            let position = i as u64 % p;
+            if in_a[j] || position == roots[j].0 || position == roots[j].1 {
+                while rest.rem_digit(p) == 0 {
+                    rest = rest.div(&Natural::from(p)).0;
+                    factors.push(j);
+                }
+            }
+        }
+        let relation = Relation {
+            y: &y.size % self.n,
+            negative: g.sign == Sign::Negative,
+            factors,
+            extra: Natural::from(1u64),
+        };
+        match u64::try_from(&rest) {
+            Ok(1) => self.relations.push(relation),
+            Ok(large) if large < self.large_prime_bound => match self.partials.get(&large) {
+                Some(other) if other.y != relation.y => {
+                    let full = other.combine(&relation, large, self.n);
+                    self.relations.push(full);
+                }
+                Some(_) => {}
+                None => {
+                    self.partials.insert(large, relation);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Looks for a factor among the combinations of relations which are squares
+    fn find_factor(&self) -> Option<Natural> {
+        let n = self.n;
+        let one = Natural::from(1u64);
+        for dependency in filtered_dependencies(&self.relations, self.base.primes.len() + 1) {
+            let mut x = Natural::from(1u64);
+            let mut y = Natural::from(1u64);
+            let mut exponents = vec!(0u64; self.base.primes.len());
+            for &r in &dependency {
+                let relation = &self.relations[r];
+                x = &(&x * &relation.y) % n;
+                y = &(&y * &relation.extra) % n;
+                for &j in &relation.factors {
+                    exponents[j] += 1;
+                }
+            }
+            for (j, &e) in exponents.iter().enumerate().filter(|&(_, &e)| e > 0) {
+                y = &(&y * &Natural::from(self.base.primes[j]).pow_mod(&Natural::from(e / 2), n)) % n;
+            }
+            let difference = if x >= y { &x - &y } else { &(&x + n) - &y };
+            let g = difference.gcd(n);
+            if g != one && g != *n {
+                return Some(g);
+            }
+        }
+        None
+    }
+}
+
+impl Relation {
+    /// The columns in which this relation has an odd exponent, with the sign in column 0
+    /// and the prime with index j in column j + 1
+    fn odd_columns(&self) -> Vec<usize> {
+        let mut columns: Vec<usize> = self.factors.iter().map(|&j| j + 1).collect();
+        if self.negative {
+            columns.push(0);
+        }
+        columns.sort_unstable();
+        let mut odd = Vec::new();
+        for c in columns {
+            if odd.last() == Some(&c) {
+                odd.pop();
+            }
+            else {
+                odd.push(c);
+            }
+        }
+        odd
+    }
+}
+
+/// Returns sets of relations whose products are squares, after first discarding the
+/// relations which cannot take part in one because they alone have an odd exponent
+/// in some column, and the columns left empty
+fn filtered_dependencies(relations: &[Relation], columns: usize) -> Vec<Vec<usize>> {
+    let odd: Vec<Vec<usize>> = relations.iter().map(Relation::odd_columns).collect();
+    let mut active: Vec<usize> = (0..relations.len()).collect();
+    let mut counts = vec!(0usize; columns);
+    loop {
+        counts.iter_mut().for_each(|c| *c = 0);
+        for &r in &active {
+            for &c in &odd[r] {
+                counts[c] += 1;
+            }
+        }
+        let before = active.len();
+        active.retain(|&r| odd[r].iter().all(|&c| counts[c] > 1));
+        if active.len() == before {
+            break;
+        }
+    }
+    let mut renumbered = vec!(0; columns);
+    let mut used = 0;
+    for (c, &count) in counts.iter().enumerate() {
+        if count > 0 {
+            renumbered[c] = used;
+            used += 1;
+        }
+    }
+    // A few more relations than columns are enough for several dependencies
+    active.truncate(used + EXTRA_RELATIONS);
+    let rows: Vec<Vec<usize>> = active.iter().map(|&r| odd[r].iter().map(|&c| renumbered[c]).collect()).collect();
+    dependencies(&rows, used).into_iter()
+        .map(|dependency| dependency.into_iter().map(|i| active[i]).collect())
+        .collect()
+}
+
+/// Returns the sets of rows which sum to zero over GF(2), found by Gaussian elimination,
+/// where each row is given by the columns in which it has a 1
+fn dependencies(rows: &[Vec<usize>], columns: usize) -> Vec<Vec<usize>> {
+    // Each row holds its columns, followed by the set of original rows whose sum it is
+    let matrix_words = columns.div_ceil(64);
+    let width = matrix_words + rows.len().div_ceil(64);
+    let count = rows.len();
+    let mut rows: Vec<Vec<u64>> = rows.iter().enumerate().map(|(r, row)| {
+        let mut bits = vec!(0u64; width);
+        for &c in row.iter().chain(Some(&(64 * matrix_words + r))) {
+            bits[c / 64] ^= 1 << (c % 64);
+        }
+        bits
+    }).collect();
+    let mut pivot = vec!(false; rows.len());
+    for c in 0..columns {
+        let (word, bit) = (c / 64, 1 << (c % 64));
+        let found = (0..rows.len()).find(|&r| !pivot[r] && rows[r][word] & bit != 0);
+        if let Some(p) = found {
+            pivot[p] = true;
+            let pivot_row = rows[p].clone();
+            for (r, row) in rows.iter_mut().enumerate() {
+                if !pivot[r] && row[word] & bit != 0 {
+                    for (w, v) in row.iter_mut().zip(&pivot_row).skip(word) {
+                        *w ^= v;
+                    }
+                }
+            }
+        }
+    }
+    // The rows which were never pivots are now zero in the matrix part
+    rows.iter().zip(&pivot)
+        .filter(|&(_, &p)| !p)
+        .map(|(row, _)| (0..count).filter(|&r| {
+            let c = 64 * matrix_words + r;
+            row[c / 64] >> (c % 64) & 1 == 1
+        }).collect())
+        .collect()
+}
+
+/// Finds a proper factor of n, which must be odd, composite and not a perfect power
+pub(crate) fn siqs(n: &Natural) -> Natural {
+    let digits = (log2(n) * 2f64.log10()).ceil() as u32;
+    let &(_, base_size, m) = PARAMETERS.iter().find(|&&(d, _, _)| digits <= d).unwrap_or(&PARAMETERS[8]);
+    let k = choose_multiplier(n);
+    let mut sieve = Sieve::new(n, k, base_size, m);
+    if let Some(&p) = sieve.base.primes.iter().find(|&&p| n.rem_digit(p) == 0) {
+        return Natural::from(p);
+    }
+    let mut wanted = sieve.base.primes.len() + 1 + EXTRA_RELATIONS;
+    loop {
+        while sieve.relations.len() < wanted {
+            let polynomials = sieve.polynomials();
+            sieve.sieve_polynomials(&polynomials);
+        }
+        if let Some(g) = sieve.find_factor() {
+            return g;
+        }
+        wanted = sieve.relations.len() + EXTRA_RELATIONS;
+    }
+}
+
+impl Natural {
+    ///
+    /// Looks for a proper factor of this number with the self-initialising quadratic
+    /// sieve, returning None if the number is prime, 0 or 1
+    ///
+    /// The factor found is not necessarily prime, nor the smallest. This is the method
+    /// of choice for numbers of 40 to 80 digits whose factors are all large, taking
+    /// seconds at 60 digits and under a minute at 70. Larger numbers up to 100 digits
+    /// are accepted, but the matrix step alone then takes minutes. Numbers below
+    /// 2^64 are split with `factor` instead.
+    ///
+    /// # Example
+    /// ```rust
+    /// use skewes::Natural;
+    ///
+    /// let (p, q) = (Natural::from(1_000_000_007u64), Natural::from(10_000_000_000_000_000_051u128));
+    /// let factor = (&p * &q).quadratic_sieve().unwrap();
+    /// assert!(factor == p || factor == q);
+    /// ```
+    pub fn quadratic_sieve(&self) -> Option<Natural> {
+        if *self <= Natural::from(3u64) || self.is_probable_prime(0) != Primality::Composite {
+            return None;
+        }
+        if u64::try_from(self).is_ok() {
+            return self.factor().first().map(|(p, _)| p.clone());
+        }
+        if self.digits[0] & 1 == 0 {
+            return Some(Natural::from(2u64));
+        }
+        if let Some((root, _)) = self.is_perfect_power() {
+            return Some(root);
+        }
+        Some(siqs(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt_mod() {
+        for &p in &[3u64, 5, 13, 17, 97, 257, 65537, 1_000_000_007, 998_244_353] {
+            for a in 1..200 {
+                if jacobi_u64(a, p) == 1 {
+                    let r = sqrt_mod_u64(a, p);
+                    assert_eq!(mul_mod_u64(r, r, p), a % p);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_dependencies() {
+        // The products of 6 = 2 3, 10 = 2 5, 15 = 3 5 and of 2, 2 are squares
+        let rows = vec!(vec!(0, 1), vec!(0, 2), vec!(1, 2), vec!(0), vec!(0));
+        assert_eq!(dependencies(&rows, 3), vec!(vec!(0, 1, 2), vec!(3, 4)));
+        let relation = |factors: Vec<usize>, negative| Relation { y: Natural::ZERO, negative, factors, extra: Natural::from(1u64) };
+        assert_eq!(relation(vec!(1, 4, 1, 1, 3), true).odd_columns(), vec!(0, 2, 4, 5));
+        // The relation with 7 alone is filtered out
+        let relations = vec!(relation(vec!(0, 1), false), relation(vec!(0, 2), false), relation(vec!(1, 2), false),
+                             relation(vec!(3, 0), false));
+        assert_eq!(filtered_dependencies(&relations, 5), vec!(vec!(0, 1, 2)));
+    }
+
+    #[test]
+    fn test_balanced_semiprimes() {
+        // Products of two primes of 15 and 20 digits
+        for (p, q) in [(100_000_000_000_031u64, 100_000_000_000_067u64),
+                       (10_000_000_000_000_000_051, 10_000_000_000_000_000_087)] {
+            let (p, q) = (Natural::from(p), Natural::from(q));
+            let factor = (&p * &q).quadratic_sieve().unwrap();
+            assert!(factor == p || factor == q);
+        }
+        assert_eq!(Natural::from(101u64).quadratic_sieve(), None);
+        assert_eq!(Natural::from(vec!(0, 1)).quadratic_sieve(), Some(Natural::from(2u64)));
+    }
+
+    #[test]
+    fn test_forty_digit_semiprime() {
+        // Two primes of 21 digits, whose product of 42 digits takes a factor base of 1200
+        let (p, q): (Natural, Natural) = ("300000000000000000053".parse().unwrap(), "700000000000000000051".parse().unwrap());
+        let factor = (&p * &q).quadratic_sieve().unwrap();
+        assert!(factor == p || factor == q);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_large_semiprimes() {
+        // Products of two primes of 30 and 35 digits
+        for (p, q) in [("300000000000000000000000000007", "700000000000000000000000000033"),
+                       ("30000000000000000000000000000000029", "70000000000000000000000000000000027")] {
+            let (p, q): (Natural, Natural) = (p.parse().unwrap(), q.parse().unwrap());
+            let factor = (&p * &q).quadratic_sieve().unwrap();
+            assert!(factor == p || factor == q);
+        }
+    }
+}